@@ -317,6 +317,7 @@ fn create_scalar_handles<R: Runtime, E1: CubeElement, E2: CubeElement, E3: CubeE
     // It is crucial that scalars follow this order: float, int, uint
     let element_priority = |elem: Elem| match elem {
         Elem::Float(_) => 0,
+        Elem::AtomicFloat(_) => 0,
         Elem::Int(_) => 1,
         Elem::AtomicInt(_) => 1,
         Elem::UInt(_) => 2,
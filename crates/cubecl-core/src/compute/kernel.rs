@@ -1,6 +1,10 @@
 use std::{fmt::Display, marker::PhantomData};
 
-use crate::{codegen::CompilerRepresentation, ir::CubeDim, Compiler, Kernel, KernelId};
+use crate::{
+    codegen::CompilerRepresentation,
+    ir::{CubeDim, KernelDefinition},
+    Compiler, Kernel, KernelId,
+};
 use alloc::sync::Arc;
 use cubecl_runtime::ExecutionMode;
 
@@ -176,6 +180,17 @@ pub trait CubeTask<C: Compiler>: Send + Sync {
     fn name(&self) -> &'static str {
         core::any::type_name::<Self>()
     }
+    /// The execution mode the kernel should be compiled with instead of the requested one, see
+    /// [Kernel::execution_mode].
+    fn execution_mode(&self) -> Option<ExecutionMode> {
+        None
+    }
+    /// The backend-independent definition of the kernel, before it's compiled, if available.
+    ///
+    /// Only used for debugging, since the kernel is expanded again every time.
+    fn define(&self) -> Option<KernelDefinition> {
+        None
+    }
 }
 
 /// Wraps a [kernel](Kernel) to create a [cube task](CubeTask).
@@ -209,6 +224,14 @@ impl<C: Compiler, K: Kernel> CubeTask<C> for KernelTask<C, K> {
     fn name(&self) -> &'static str {
         core::any::type_name::<K>()
     }
+
+    fn execution_mode(&self) -> Option<ExecutionMode> {
+        self.kernel_definition.execution_mode()
+    }
+
+    fn define(&self) -> Option<KernelDefinition> {
+        Some(self.kernel_definition.define())
+    }
 }
 
 impl<C: Compiler> CubeTask<C> for Arc<dyn CubeTask<C>> {
@@ -222,6 +245,14 @@ impl<C: Compiler> CubeTask<C> for Arc<dyn CubeTask<C>> {
     fn name(&self) -> &'static str {
         self.as_ref().name()
     }
+
+    fn execution_mode(&self) -> Option<ExecutionMode> {
+        self.as_ref().execution_mode()
+    }
+
+    fn define(&self) -> Option<KernelDefinition> {
+        self.as_ref().define()
+    }
 }
 
 impl<C: Compiler> CubeTask<C> for Box<dyn CubeTask<C>> {
@@ -236,4 +267,12 @@ impl<C: Compiler> CubeTask<C> for Box<dyn CubeTask<C>> {
     fn name(&self) -> &'static str {
         self.as_ref().name()
     }
+
+    fn execution_mode(&self) -> Option<ExecutionMode> {
+        self.as_ref().execution_mode()
+    }
+
+    fn define(&self) -> Option<KernelDefinition> {
+        self.as_ref().define()
+    }
 }
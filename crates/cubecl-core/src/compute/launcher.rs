@@ -160,7 +160,7 @@ impl<R: Runtime> KernelLauncher<R> {
 
         for elem in self.scalar_order.drain(..) {
             match elem {
-                Elem::Float(kind) => match kind {
+                Elem::Float(kind) | Elem::AtomicFloat(kind) => match kind {
                     FloatKind::F16 => self.scalar_f16.register::<R>(client, &mut bindings),
                     FloatKind::BF16 => self.scalar_bf16.register::<R>(client, &mut bindings),
                     FloatKind::TF32 => self.scalar_f32.register::<R>(client, &mut bindings),
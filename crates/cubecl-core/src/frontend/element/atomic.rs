@@ -9,8 +9,8 @@ use super::{
 use crate::{
     frontend::{CubeContext, CubePrimitive, CubeType, ExpandElement},
     ir::{
-        BinaryOperator, CompareAndSwapOperator, Elem, Instruction, IntKind, Item, Operation,
-        UIntKind, UnaryOperator,
+        BinaryOperator, CompareAndSwapOperator, Elem, FloatKind, Instruction, IntKind, Item,
+        Operation, UIntKind, UnaryOperator,
     },
     prelude::KernelBuilder,
     unexpanded,
@@ -188,6 +188,7 @@ where
         cmp: <Self::Primitive as CubeType>::ExpandType,
         value: <Self::Primitive as CubeType>::ExpandType,
     ) -> <Self::Primitive as CubeType>::ExpandType {
+        assert_integer_atomic::<Self::Primitive>("compare_and_swap");
         let pointer: ExpandElement = pointer.into();
         let cmp: ExpandElement = cmp.into();
         let value: ExpandElement = value.into();
@@ -244,6 +245,7 @@ where
         pointer: <Self as CubeType>::ExpandType,
         value: <Self::Primitive as CubeType>::ExpandType,
     ) -> <Self::Primitive as CubeType>::ExpandType {
+        assert_integer_atomic::<Self::Primitive>("max");
         let ptr: ExpandElement = pointer.into();
         let value: ExpandElement = value.into();
         let new_var = context.create_local_binding(Item::new(Self::Primitive::as_elem()));
@@ -262,6 +264,7 @@ where
         pointer: <Self as CubeType>::ExpandType,
         value: <Self::Primitive as CubeType>::ExpandType,
     ) -> <Self::Primitive as CubeType>::ExpandType {
+        assert_integer_atomic::<Self::Primitive>("min");
         let ptr: ExpandElement = pointer.into();
         let value: ExpandElement = value.into();
         let new_var = context.create_local_binding(Item::new(Self::Primitive::as_elem()));
@@ -280,6 +283,7 @@ where
         pointer: <Self as CubeType>::ExpandType,
         value: <Self::Primitive as CubeType>::ExpandType,
     ) -> <Self::Primitive as CubeType>::ExpandType {
+        assert_integer_atomic::<Self::Primitive>("and");
         let ptr: ExpandElement = pointer.into();
         let value: ExpandElement = value.into();
         let new_var = context.create_local_binding(Item::new(Self::Primitive::as_elem()));
@@ -298,6 +302,7 @@ where
         pointer: <Self as CubeType>::ExpandType,
         value: <Self::Primitive as CubeType>::ExpandType,
     ) -> <Self::Primitive as CubeType>::ExpandType {
+        assert_integer_atomic::<Self::Primitive>("or");
         let ptr: ExpandElement = pointer.into();
         let value: ExpandElement = value.into();
         let new_var = context.create_local_binding(Item::new(Self::Primitive::as_elem()));
@@ -316,6 +321,7 @@ where
         pointer: <Self as CubeType>::ExpandType,
         value: <Self::Primitive as CubeType>::ExpandType,
     ) -> <Self::Primitive as CubeType>::ExpandType {
+        assert_integer_atomic::<Self::Primitive>("xor");
         let ptr: ExpandElement = pointer.into();
         let value: ExpandElement = value.into();
         let new_var = context.create_local_binding(Item::new(Self::Primitive::as_elem()));
@@ -424,6 +430,54 @@ impl LaunchArgExpand for AtomicU32 {
     }
 }
 
+/// An atomic version of `f32`. Can only be acted on atomically.
+///
+/// Only [load](Atomic::load), [store](Atomic::store), [swap](Atomic::swap), [add](Atomic::add)
+/// and [sub](Atomic::sub) are available, and only on devices supporting
+/// `Feature::Type(Elem::AtomicFloat(FloatKind::F32))`, which should be checked before launching.
+/// The other operations panic when the kernel is expanded. On Vulkan, the feature only guarantees
+/// atomics on global memory, not on shared memory.
+#[derive(Clone, Copy, PartialEq)]
+pub struct AtomicF32 {
+    pub val: f32,
+}
+
+impl core::fmt::Debug for AtomicF32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}", self.val))
+    }
+}
+
+impl CubeType for AtomicF32 {
+    type ExpandType = ExpandElementTyped<Self>;
+}
+
+impl CubePrimitive for AtomicF32 {
+    fn as_elem() -> Elem {
+        Elem::AtomicFloat(FloatKind::F32)
+    }
+}
+
+impl IntoRuntime for AtomicF32 {
+    fn __expand_runtime_method(self, _context: &mut CubeContext) -> ExpandElementTyped<Self> {
+        unimplemented!("Atomics don't exist at compile time")
+    }
+}
+
+impl ExpandElementBaseInit for AtomicF32 {
+    fn init_elem(context: &mut CubeContext, elem: ExpandElement) -> ExpandElement {
+        init_expand_element(context, elem)
+    }
+}
+
+impl LaunchArgExpand for AtomicF32 {
+    type CompilationArg = ();
+
+    fn expand(_: &Self::CompilationArg, builder: &mut KernelBuilder) -> ExpandElementTyped<Self> {
+        builder.scalar(Elem::AtomicFloat(FloatKind::F32)).into()
+    }
+}
+
 impl Atomic for AtomicI32 {
     type Primitive = i32;
 }
@@ -433,6 +487,18 @@ impl Atomic for AtomicI64 {
 impl Atomic for AtomicU32 {
     type Primitive = u32;
 }
+impl Atomic for AtomicF32 {
+    type Primitive = f32;
+}
+
+/// Bitwise, comparing and compare and swap operations only exist for integer atomics.
+fn assert_integer_atomic<P: CubePrimitive>(op: &str) {
+    if let Elem::Float(kind) = P::as_elem() {
+        panic!(
+            "Atomic {op} isn't supported on {kind:?} atomics, only load, store, swap, add and sub are"
+        );
+    }
+}
 
 impl From<AtomicOp> for Operation {
     fn from(value: AtomicOp) -> Self {
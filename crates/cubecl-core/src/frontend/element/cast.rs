@@ -1,11 +1,16 @@
-use crate::ir::{Instruction, Item, UnaryOperator, Variable};
+use half::{bf16, f16};
+
+use crate::ir::{
+    BinaryOperator, ClampOperator, ConstantScalarValue, Elem, FloatKind, Instruction, Item, Select,
+    UnaryOperator, Variable,
+};
 use crate::{frontend::ExpandElement, unexpanded};
 use crate::{
     frontend::{cast, CubeContext, CubePrimitive, CubeType},
     ir::Operator,
 };
 
-use super::ExpandElementTyped;
+use super::{ExpandElementTyped, Float, Int};
 
 /// Enable elegant casting from any to any CubeElem
 pub trait Cast: CubePrimitive {
@@ -62,3 +67,176 @@ pub trait BitCast: CubePrimitive {
 }
 
 impl<P: CubePrimitive> BitCast for P {}
+
+/// Enables casting from floating point values to integers with saturation.
+pub trait SaturatingCast: Int {
+    /// Convert a float to this integer type, rounding toward zero.
+    ///
+    /// Values outside of the range of the integer are clamped to its bounds and NaN becomes zero,
+    /// on every backend. A plain [cast](Cast) is only guaranteed to saturate when the
+    /// [saturating cast feature](crate::Feature::SaturatingCast) is registered, and leaves NaN
+    /// undefined.
+    #[allow(unused_variables)]
+    fn cast_saturate<From: Float>(value: From) -> Self {
+        unexpanded!()
+    }
+
+    fn __expand_cast_saturate<From: Float>(
+        context: &mut CubeContext,
+        value: ExpandElementTyped<From>,
+    ) -> <Self as CubeType>::ExpandType {
+        let value: ExpandElement = value.into();
+        let input = *value;
+        let vectorization = input.item.vectorization;
+        let float_kind = match input.item.elem {
+            Elem::Float(kind) => kind,
+            elem => panic!("Can only saturate casts from floats, got {elem:?}"),
+        };
+        let (int_min, int_max) = int_bounds(<Self as CubePrimitive>::as_elem());
+
+        let float_constant =
+            |value: f64| Variable::constant(ConstantScalarValue::Float(value, float_kind));
+        let int_constant = |value: i128| {
+            Variable::constant(match <Self as CubePrimitive>::as_elem() {
+                Elem::Int(kind) => ConstantScalarValue::Int(value as i64, kind),
+                Elem::UInt(kind) => ConstantScalarValue::UInt(value as u64, kind),
+                elem => unreachable!("{elem:?} isn't an integer"),
+            })
+        };
+        let register = |context: &mut CubeContext, operator: Operator, elem: Elem| {
+            let out = context.create_local_binding(Item::vectorized(elem, vectorization));
+            context.register(Instruction::new(operator, *out));
+            out
+        };
+
+        // The conversion is only defined for values that fit in the integer, so clamp to the
+        // biggest bounds representable by the float type first.
+        let max = representable_toward_zero(int_max, float_kind);
+        let clamped = register(
+            context,
+            Operator::Clamp(ClampOperator {
+                input,
+                min_value: float_constant(representable_toward_zero(int_min, float_kind)),
+                max_value: float_constant(max),
+            }),
+            Elem::Float(float_kind),
+        );
+        let mut output = register(
+            context,
+            Operator::Cast(UnaryOperator { input: *clamped }),
+            <Self as CubePrimitive>::as_elem(),
+        );
+
+        // The maximum of the integer isn't always representable, for instance `i32::MAX` as an
+        // `f32`, so values past the clamped bound still have to map to it.
+        let past_max = int_max + 1;
+        if (max as i128) < int_max
+            && representable_toward_zero(past_max, float_kind) as i128 == past_max
+        {
+            let overflow = register(
+                context,
+                Operator::GreaterEqual(BinaryOperator {
+                    lhs: input,
+                    rhs: float_constant(past_max as f64),
+                }),
+                Elem::Bool,
+            );
+            output = register(
+                context,
+                Operator::Select(Select {
+                    cond: *overflow,
+                    then: int_constant(int_max),
+                    or_else: *output,
+                }),
+                <Self as CubePrimitive>::as_elem(),
+            );
+        }
+
+        let is_nan = register(
+            context,
+            Operator::NotEqual(BinaryOperator {
+                lhs: input,
+                rhs: input,
+            }),
+            Elem::Bool,
+        );
+        register(
+            context,
+            Operator::Select(Select {
+                cond: *is_nan,
+                then: int_constant(0),
+                or_else: *output,
+            }),
+            <Self as CubePrimitive>::as_elem(),
+        )
+        .into()
+    }
+}
+
+impl<I: Int> SaturatingCast for I {}
+
+/// The range of an integer type.
+fn int_bounds(elem: Elem) -> (i128, i128) {
+    let bits = elem.size() as u32 * 8;
+    match elem {
+        Elem::Int(_) => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+        Elem::UInt(_) => (0, (1 << bits) - 1),
+        elem => panic!("Can only saturate casts to integers, got {elem:?}"),
+    }
+}
+
+/// The closest integer to `value` representable by the float type, rounding toward zero.
+fn representable_toward_zero(value: i128, kind: FloatKind) -> f64 {
+    // Stepping the bits down reduces the magnitude of both positive and negative floats.
+    let (rounded, stepped_down) = match kind {
+        FloatKind::F16 => {
+            let rounded = f16::from_f64(value as f64);
+            let stepped_down = f16::from_bits(rounded.to_bits().wrapping_sub(1));
+            (rounded.to_f64(), stepped_down.to_f64())
+        }
+        FloatKind::BF16 => {
+            let rounded = bf16::from_f64(value as f64);
+            let stepped_down = bf16::from_bits(rounded.to_bits().wrapping_sub(1));
+            (rounded.to_f64(), stepped_down.to_f64())
+        }
+        FloatKind::Flex32 | FloatKind::F32 | FloatKind::TF32 => {
+            let rounded = value as f32;
+            let stepped_down = f32::from_bits(rounded.to_bits().wrapping_sub(1));
+            (rounded as f64, stepped_down as f64)
+        }
+        FloatKind::F64 => {
+            let rounded = value as f64;
+            (rounded, f64::from_bits(rounded.to_bits().wrapping_sub(1)))
+        }
+    };
+
+    match (rounded as i128).unsigned_abs() > value.unsigned_abs() {
+        true => stepped_down,
+        false => rounded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::IntKind;
+
+    #[test]
+    fn saturation_bounds_are_representable() {
+        let (min, max) = int_bounds(Elem::Int(IntKind::I32));
+        assert_eq!((min, max), (i32::MIN as i128, i32::MAX as i128));
+
+        assert_eq!(representable_toward_zero(max, FloatKind::F32), 2147483520.0);
+        assert_eq!(
+            representable_toward_zero(min, FloatKind::F32),
+            i32::MIN as f64
+        );
+        assert_eq!(
+            representable_toward_zero(max, FloatKind::F64),
+            i32::MAX as f64
+        );
+        // Out of the range of the float, the bounds are its finite extremes.
+        assert_eq!(representable_toward_zero(max, FloatKind::F16), 65504.0);
+        assert_eq!(representable_toward_zero(min, FloatKind::F16), -65504.0);
+    }
+}
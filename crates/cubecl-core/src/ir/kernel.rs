@@ -61,6 +61,7 @@ pub enum UIntKind {
 #[allow(missing_docs)]
 pub enum Elem {
     Float(FloatKind),
+    AtomicFloat(FloatKind),
     Int(IntKind),
     AtomicInt(IntKind),
     UInt(UIntKind),
@@ -74,7 +75,7 @@ impl Elem {
     /// The output will have the same type as the element.
     pub fn constant_from_f64(&self, val: f64) -> Variable {
         Variable::constant(match self {
-            Elem::Float(kind) => ConstantScalarValue::Float(val, *kind),
+            Elem::Float(kind) | Elem::AtomicFloat(kind) => ConstantScalarValue::Float(val, *kind),
             Elem::Int(kind) => ConstantScalarValue::Int(val as i64, *kind),
             Elem::UInt(kind) => ConstantScalarValue::UInt(val as u64, *kind),
             Elem::Bool => ConstantScalarValue::Bool(val > 0.0),
@@ -87,7 +88,9 @@ impl Elem {
     /// The output will have the same type as the element.
     pub fn constant_from_i64(&self, val: i64) -> Variable {
         Variable::constant(match self {
-            Elem::Float(kind) => ConstantScalarValue::Float(val as f64, *kind),
+            Elem::Float(kind) | Elem::AtomicFloat(kind) => {
+                ConstantScalarValue::Float(val as f64, *kind)
+            }
             Elem::Int(kind) => ConstantScalarValue::Int(val, *kind),
             Elem::UInt(kind) => ConstantScalarValue::UInt(val as u64, *kind),
             Elem::Bool => ConstantScalarValue::Bool(val > 0),
@@ -100,7 +103,9 @@ impl Elem {
     /// The output will have the same type as the element.
     pub fn constant_from_u64(&self, val: u64) -> Variable {
         Variable::constant(match self {
-            Elem::Float(kind) => ConstantScalarValue::Float(val as f64, *kind),
+            Elem::Float(kind) | Elem::AtomicFloat(kind) => {
+                ConstantScalarValue::Float(val as f64, *kind)
+            }
             Elem::Int(kind) => ConstantScalarValue::Int(val as i64, *kind),
             Elem::UInt(kind) => ConstantScalarValue::UInt(val, *kind),
             Elem::Bool => ConstantScalarValue::Bool(val > 0),
@@ -113,7 +118,9 @@ impl Elem {
     /// The output will have the same type as the element.
     pub fn constant_from_bool(&self, val: bool) -> Variable {
         Variable::constant(match self {
-            Elem::Float(kind) => ConstantScalarValue::Float(val as u32 as f64, *kind),
+            Elem::Float(kind) | Elem::AtomicFloat(kind) => {
+                ConstantScalarValue::Float(val as u32 as f64, *kind)
+            }
             Elem::Int(kind) => ConstantScalarValue::Int(val as i64, *kind),
             Elem::AtomicInt(kind) => ConstantScalarValue::Int(val as i64, *kind),
             Elem::UInt(kind) => ConstantScalarValue::UInt(val as u64, *kind),
@@ -139,7 +146,7 @@ impl Elem {
     /// Get the size in bytes.
     pub const fn size(&self) -> usize {
         match self {
-            Elem::Float(kind) => match kind {
+            Elem::Float(kind) | Elem::AtomicFloat(kind) => match kind {
                 FloatKind::F16 => core::mem::size_of::<half::f16>(),
                 FloatKind::BF16 => core::mem::size_of::<half::bf16>(),
                 FloatKind::F32 => core::mem::size_of::<f32>(),
@@ -176,7 +183,10 @@ impl Elem {
     }
 
     pub fn is_atomic(&self) -> bool {
-        matches!(self, Elem::AtomicInt(_) | Elem::AtomicUInt(_))
+        matches!(
+            self,
+            Elem::AtomicFloat(_) | Elem::AtomicInt(_) | Elem::AtomicUInt(_)
+        )
     }
 
     pub fn is_int(&self) -> bool {
@@ -204,6 +214,7 @@ impl Display for Elem {
                 FloatKind::F32 => f.write_str("f32"),
                 FloatKind::F64 => f.write_str("f64"),
             },
+            Self::AtomicFloat(kind) => write!(f, "atomic<{}>", Elem::Float(*kind)),
             Self::Int(kind) => match kind {
                 IntKind::I8 => f.write_str("i8"),
                 IntKind::I16 => f.write_str("i16"),
@@ -50,6 +50,15 @@ pub trait Kernel: Send + Sync + 'static + Sized {
     fn id(&self) -> KernelId {
         KernelId::new::<Self>()
     }
+    /// The [execution mode](ExecutionMode) this kernel should always be compiled with, replacing
+    /// the one requested at launch.
+    ///
+    /// Returning [ExecutionMode::Unchecked] skips the bound checks even for checked launches, so
+    /// only do it for kernels that can't access out of bounds. Runtimes that ignore the execution
+    /// mode, such as wgpu on a robust device, still do.
+    fn execution_mode(&self) -> Option<ExecutionMode> {
+        None
+    }
 }
 
 /// Calculate the number of cubes required to execute an operation where one cube unit is
@@ -5,12 +5,12 @@ pub use crate::compute::{CompiledKernel, CubeTask, KernelBuilder, KernelLauncher
 pub use crate::frontend::cmma;
 pub use crate::frontend::{branch::*, synchronization::*, vectorization_of};
 pub use crate::ir::{CubeDim, KernelDefinition};
-pub use crate::runtime::Runtime;
+pub use crate::runtime::{FeatureQueries, Runtime};
 
 /// Elements
 pub use crate::frontend::{
-    Array, ArrayHandleRef, AtomicI32, AtomicI64, AtomicU32, Float, LaunchArg, Slice, SliceMut,
-    Tensor, TensorArg,
+    Array, ArrayHandleRef, AtomicF32, AtomicI32, AtomicI64, AtomicU32, Float, LaunchArg, Slice,
+    SliceMut, Tensor, TensorArg,
 };
 pub use crate::pod::CubeElement;
 
@@ -1,5 +1,8 @@
 use crate::{codegen::Compiler, compute::CubeTask, ir::Elem};
-use cubecl_runtime::{channel::ComputeChannel, client::ComputeClient, server::ComputeServer};
+use alloc::vec::Vec;
+use cubecl_runtime::{
+    channel::ComputeChannel, client::ComputeClient, server::ComputeServer, DeviceProperties,
+};
 
 pub use cubecl_runtime::channel;
 pub use cubecl_runtime::client;
@@ -39,14 +42,143 @@ pub enum Feature {
     /// The plane feature enables all basic warp/subgroup operations.
     Plane,
     /// The cmma feature enables cooperative matrix-multiply and accumulate operations.
+    ///
+    /// `a` and `b` are the types of the input matrices, `c` the type of the accumulator and `d`
+    /// the type of the result, which may differ from the accumulator. `scope` is the set of units
+    /// sharing the matrices.
     Cmma {
         a: Elem,
         b: Elem,
         c: Elem,
+        d: Elem,
         m: u8,
         k: u8,
         n: u8,
+        scope: CmmaScope,
     },
     CmmaWarpSize(i32),
     Type(Elem),
+    /// Casts from floats to integers clamp out-of-range values to the bounds of the integer
+    /// instead of being undefined. NaN is still undefined, see
+    /// [cast_saturate](crate::prelude::SaturatingCast::cast_saturate) for a cast that is
+    /// deterministic on every device.
+    SaturatingCast,
+    /// [Dot products](crate::prelude::Dot) of integer lines are done by a single instruction.
+    ///
+    /// Only reported by the SPIR-V runtime, where integer dot products can't be compiled without
+    /// it. Kernels should fall back to a multiply-accumulate of the line elements otherwise.
+    DotProduct,
+}
+
+/// The units cooperating on the matrices of a [cmma](Feature::Cmma) instruction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CmmaScope {
+    /// The matrices are shared by the units of a plane. Supported by every runtime with cmma.
+    #[default]
+    Plane,
+    /// The matrices are shared by all the units of a cube, which allows bigger shapes.
+    ///
+    /// Only reported by the SPIR-V runtime, on drivers exposing workgroup-scoped cooperative
+    /// matrices. Kernels are still compiled with plane-scoped matrices.
+    Cube,
+}
+
+/// Queries on the [features](Feature) registered in the [device properties](DeviceProperties).
+pub trait FeatureQueries {
+    /// The `(m, n, k)` shapes of the plane-scoped cmma instructions supported for the given input
+    /// and output element types.
+    fn supported_cmma_shapes(&self, input: Elem, output: Elem) -> Vec<(u8, u8, u8)>;
+}
+
+impl FeatureQueries for DeviceProperties<Feature> {
+    fn supported_cmma_shapes(&self, input: Elem, output: Elem) -> Vec<(u8, u8, u8)> {
+        self.features()
+            .filter_map(|feature| match feature {
+                Feature::Cmma {
+                    a,
+                    b,
+                    c,
+                    d,
+                    m,
+                    k,
+                    n,
+                    scope: CmmaScope::Plane,
+                } if a == input && b == input && c == output && d == output => Some((m, n, k)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::FloatKind;
+    use cubecl_runtime::memory_management::{HardwareProperties, MemoryDeviceProperties};
+
+    #[test]
+    fn supported_cmma_shapes_filters_by_types() {
+        let f16 = Elem::Float(FloatKind::F16);
+        let f32 = Elem::Float(FloatKind::F32);
+        let cmma = |c, m, k, n| Feature::Cmma {
+            a: f16,
+            b: f16,
+            c,
+            d: c,
+            m,
+            k,
+            n,
+            scope: CmmaScope::Plane,
+        };
+
+        let props = DeviceProperties::new(
+            &[
+                Feature::Plane,
+                cmma(f32, 16, 16, 16),
+                cmma(f32, 32, 16, 8),
+                cmma(f16, 16, 16, 16),
+                // The result type differs from the accumulator.
+                Feature::Cmma {
+                    a: f16,
+                    b: f16,
+                    c: f32,
+                    d: f16,
+                    m: 8,
+                    k: 16,
+                    n: 32,
+                    scope: CmmaScope::Plane,
+                },
+                // Cube-scoped shapes aren't used by the plane-scoped instructions.
+                Feature::Cmma {
+                    a: f16,
+                    b: f16,
+                    c: f32,
+                    d: f32,
+                    m: 64,
+                    k: 16,
+                    n: 64,
+                    scope: CmmaScope::Cube,
+                },
+            ],
+            MemoryDeviceProperties {
+                max_page_size: 1024,
+                alignment: 32,
+                total_memory: None,
+            },
+            HardwareProperties {
+                plane_size_min: 32,
+                plane_size_max: 32,
+                plane_size_actual: None,
+                max_bindings: 32,
+                max_shared_memory_bytes: 48 * 1024,
+                max_cube_count: (u16::MAX as u32, u16::MAX as u32, u16::MAX as u32),
+            },
+        );
+
+        assert_eq!(
+            props.supported_cmma_shapes(f16, f32),
+            vec![(16, 16, 16), (32, 8, 16)]
+        );
+        assert!(props.supported_cmma_shapes(f32, f32).is_empty());
+    }
 }
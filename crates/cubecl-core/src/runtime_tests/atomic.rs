@@ -0,0 +1,50 @@
+use crate as cubecl;
+
+use crate::Feature;
+use cubecl::{
+    ir::{Elem, FloatKind},
+    prelude::*,
+};
+
+#[cube(launch)]
+pub fn kernel_atomic_add_f32(output: &mut Array<AtomicF32>) {
+    AtomicF32::add(&output[0], 2.5);
+}
+
+pub fn test_atomic_add_f32<R: Runtime>(client: ComputeClient<R::Server, R::Channel>) {
+    if !client
+        .properties()
+        .feature_enabled(Feature::Type(Elem::AtomicFloat(FloatKind::F32)))
+    {
+        // We can't execute the test, skip.
+        return;
+    }
+
+    let output = client.create(f32::as_bytes(&[1.0]));
+
+    kernel_atomic_add_f32::launch::<R>(
+        &client,
+        CubeCount::Static(1, 1, 1),
+        CubeDim::new(32, 1, 1),
+        unsafe { ArrayArg::from_raw_parts::<AtomicF32>(&output, 1, 1) },
+    );
+
+    let actual = client.read(output.binding());
+    let actual = f32::from_bytes(&actual);
+
+    assert_eq!(actual[0], 81.0);
+}
+
+#[allow(missing_docs)]
+#[macro_export]
+macro_rules! testgen_atomic {
+    () => {
+        use super::*;
+
+        #[test]
+        fn test_atomic_add_f32() {
+            let client = TestRuntime::client(&Default::default());
+            cubecl_core::runtime_tests::atomic::test_atomic_add_f32::<TestRuntime>(client);
+        }
+    };
+}
@@ -0,0 +1,56 @@
+use crate as cubecl;
+use crate::prelude::*;
+
+#[cube(launch)]
+pub fn kernel_cast_saturate(input: &Array<f32>, output: &mut Array<i32>) {
+    if UNIT_POS < input.len() {
+        output[UNIT_POS] = i32::cast_saturate(input[UNIT_POS]);
+    }
+}
+
+pub fn test_cast_saturate<R: Runtime>(client: ComputeClient<R::Server, R::Channel>) {
+    let values = [
+        1.0e10,
+        -1.0e10,
+        f32::INFINITY,
+        f32::NEG_INFINITY,
+        f32::NAN,
+        2147483520.0,
+        3.7,
+        -3.7,
+    ];
+    let input = client.create(f32::as_bytes(&values));
+    let output = client.empty(values.len() * core::mem::size_of::<i32>());
+
+    unsafe {
+        kernel_cast_saturate::launch::<R>(
+            &client,
+            CubeCount::Static(1, 1, 1),
+            CubeDim::new(values.len() as u32, 1, 1),
+            ArrayArg::from_raw_parts::<f32>(&input, values.len(), 1),
+            ArrayArg::from_raw_parts::<i32>(&output, values.len(), 1),
+        )
+    };
+
+    let actual = client.read(output.binding());
+    let actual = i32::from_bytes(&actual);
+
+    assert_eq!(
+        actual,
+        &[i32::MAX, i32::MIN, i32::MAX, i32::MIN, 0, 2147483520, 3, -3]
+    );
+}
+
+#[allow(missing_docs)]
+#[macro_export]
+macro_rules! testgen_cast {
+    () => {
+        use super::*;
+
+        #[test]
+        fn test_cast_saturate() {
+            let client = TestRuntime::client(&Default::default());
+            cubecl_core::runtime_tests::cast::test_cast_saturate::<TestRuntime>(client);
+        }
+    };
+}
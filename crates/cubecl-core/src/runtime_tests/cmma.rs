@@ -1,6 +1,6 @@
 use crate as cubecl;
 
-use crate::Feature;
+use crate::{CmmaScope, Feature};
 use cubecl::{
     ir::{Elem, FloatKind},
     prelude::*,
@@ -92,9 +92,11 @@ pub fn test_simple_1<R: Runtime>(client: ComputeClient<R::Server, R::Channel>) {
         a: Elem::Float(FloatKind::F16),
         b: Elem::Float(FloatKind::F16),
         c: Elem::Float(FloatKind::F32),
+        d: Elem::Float(FloatKind::F32),
         m: 16,
         k: 16,
         n: 16,
+        scope: CmmaScope::Plane,
     }) {
         // We can't execute the test, skip.
         return;
@@ -155,9 +157,11 @@ pub fn test_simple_tf32<R: Runtime>(client: ComputeClient<R::Server, R::Channel>
         a: Elem::Float(FloatKind::TF32),
         b: Elem::Float(FloatKind::TF32),
         c: Elem::Float(FloatKind::F32),
+        d: Elem::Float(FloatKind::F32),
         m: 16,
         k: 8,
         n: 16,
+        scope: CmmaScope::Plane,
     }) {
         // We can't execute the test, skip.
         return;
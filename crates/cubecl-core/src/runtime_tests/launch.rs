@@ -15,6 +15,11 @@ pub fn kernel_without_generics(output: &mut Array<f32>) {
     }
 }
 
+#[cube(launch, execution_mode = "unchecked")]
+pub fn kernel_always_unchecked(output: &mut Array<f32>) {
+    output[UNIT_POS] = 5.0;
+}
+
 pub fn test_kernel_with_generics<R: Runtime, F: Float + CubeElement>(
     client: ComputeClient<R::Server, R::Channel>,
 ) {
@@ -49,6 +54,26 @@ pub fn test_kernel_without_generics<R: Runtime>(client: ComputeClient<R::Server,
     assert_eq!(actual[0], 5.0);
 }
 
+pub fn test_kernel_execution_mode_override<R: Runtime>(
+    client: ComputeClient<R::Server, R::Channel>,
+) {
+    let handle = client.create(f32::as_bytes(&[0.0, 1.0, 2.0, 3.0]));
+
+    // The array is shorter than its buffer, so only the units of a checked kernel past its
+    // length are skipped.
+    kernel_always_unchecked::launch::<R>(
+        &client,
+        CubeCount::Static(1, 1, 1),
+        CubeDim::new(4, 1, 1),
+        unsafe { ArrayArg::from_raw_parts::<f32>(&handle, 2, 1) },
+    );
+
+    let actual = client.read(handle.binding());
+    let actual = f32::from_bytes(&actual);
+
+    assert_eq!(actual, &[5.0, 5.0, 5.0, 5.0]);
+}
+
 #[allow(missing_docs)]
 #[macro_export]
 macro_rules! testgen_launch {
@@ -68,5 +93,13 @@ macro_rules! testgen_launch {
             let client = TestRuntime::client(&Default::default());
             cubecl_core::runtime_tests::launch::test_kernel_without_generics::<TestRuntime>(client);
         }
+
+        #[test]
+        fn test_launch_execution_mode_override() {
+            let client = TestRuntime::client(&Default::default());
+            cubecl_core::runtime_tests::launch::test_kernel_execution_mode_override::<TestRuntime>(
+                client,
+            );
+        }
     };
 }
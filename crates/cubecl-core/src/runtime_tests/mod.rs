@@ -1,6 +1,8 @@
 pub mod assign;
+pub mod atomic;
 pub mod binary;
 pub mod branch;
+pub mod cast;
 pub mod cmma;
 pub mod const_match;
 pub mod constants;
@@ -96,6 +98,8 @@ macro_rules! testgen_uint {
 #[macro_export]
 macro_rules! testgen_untyped {
     () => {
+        cubecl_core::testgen_atomic!();
+        cubecl_core::testgen_cast!();
         cubecl_core::testgen_cmma!();
         cubecl_core::testgen_metadata!();
         cubecl_core::testgen_topology!();
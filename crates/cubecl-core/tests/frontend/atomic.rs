@@ -0,0 +1,21 @@
+use cubecl_core as cubecl;
+use cubecl_core::prelude::*;
+
+#[cube]
+pub fn atomic_max_kernel(output: &Array<AtomicF32>) {
+    AtomicF32::max(&output[0], 1.0);
+}
+
+mod tests {
+    use super::*;
+    use cubecl_core::ir::Item;
+
+    #[test]
+    #[should_panic(expected = "Atomic max isn't supported on F32 atomics")]
+    fn float_atomic_max_is_rejected() {
+        let mut context = CubeContext::default();
+        let output = context.input(0, Item::new(AtomicF32::as_elem()));
+
+        atomic_max_kernel::expand(&mut context, output.into());
+    }
+}
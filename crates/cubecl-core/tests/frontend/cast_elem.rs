@@ -235,6 +235,7 @@ mod tests {
 
         match from_item.elem() {
             Elem::Float(_) => cpa!(scope, x = x + 2f32),
+            Elem::AtomicFloat(_) => cpa!(scope, x = x + 2f32),
             Elem::Int(_) => cpa!(scope, x = x + 2i32),
             Elem::AtomicInt(_) => cpa!(scope, x = x + 2i32),
             Elem::UInt(_) => cpa!(scope, x = x + 2u32),
@@ -246,6 +247,7 @@ mod tests {
 
         match to_item.elem() {
             Elem::Float(_) => cpa!(scope, y = y + 34f32),
+            Elem::AtomicFloat(_) => cpa!(scope, y = y + 34f32),
             Elem::Int(_) => cpa!(scope, y = y + 34i32),
             Elem::AtomicInt(_) => cpa!(scope, y = y + 34i32),
             Elem::UInt(_) => cpa!(scope, y = y + 34u32),
@@ -1,4 +1,5 @@
 mod array;
+mod atomic;
 mod assign;
 mod cast_elem;
 mod cast_kind;
@@ -646,7 +646,9 @@ impl<D: Dialect> CppCompiler<D> {
                     gpu::Elem::Int(kind) => ConstantScalarValue::Int(1, kind),
                     gpu::Elem::UInt(kind) => ConstantScalarValue::UInt(1, kind),
                     gpu::Elem::Bool => ConstantScalarValue::Bool(true),
-                    gpu::Elem::AtomicInt(_) | gpu::Elem::AtomicUInt(_) => {
+                    gpu::Elem::AtomicFloat(_)
+                    | gpu::Elem::AtomicInt(_)
+                    | gpu::Elem::AtomicUInt(_) => {
                         panic!("Cannot use recip with atomics")
                     }
                 };
@@ -934,6 +936,7 @@ impl<D: Dialect> CppCompiler<D> {
                 gpu::FloatKind::F32 => super::Elem::F32,
                 gpu::FloatKind::F64 => super::Elem::F64,
             },
+            gpu::Elem::AtomicFloat(kind) => panic!("atomic<{kind:?}> isn't supported yet"),
             gpu::Elem::Int(kind) => match kind {
                 gpu::IntKind::I8 => super::Elem::I8,
                 gpu::IntKind::I16 => super::Elem::I16,
@@ -1,5 +1,8 @@
-use cubecl_runtime::storage::{ComputeStorage, StorageHandle, StorageId, StorageUtilization};
-use cudarc::driver::sys::CUstream;
+use cubecl_runtime::{
+    memory_management::MemoryError,
+    storage::{ComputeStorage, StorageHandle, StorageId, StorageUtilization},
+};
+use cudarc::driver::sys::{CUresult, CUstream};
 use std::collections::HashMap;
 
 use super::uninit_vec;
@@ -128,12 +131,23 @@ impl ComputeStorage for CudaStorage {
         )
     }
 
-    fn alloc(&mut self, size: u64) -> StorageHandle {
+    fn alloc(&mut self, size: u64) -> Result<StorageHandle, MemoryError> {
         let id = StorageId::new();
-        let ptr =
-            unsafe { cudarc::driver::result::malloc_async(self.stream, size as usize).unwrap() };
+        let result = unsafe { cudarc::driver::result::malloc_async(self.stream, size as usize) };
+        let ptr = match result {
+            Err(err) if err.0 == CUresult::CUDA_ERROR_OUT_OF_MEMORY => {
+                return Err(MemoryError::OutOfMemory {
+                    size,
+                    reason: err.to_string(),
+                })
+            }
+            result => result.unwrap(),
+        };
         self.memory.insert(id, ptr);
-        StorageHandle::new(id, StorageUtilization { offset: 0, size })
+        Ok(StorageHandle::new(
+            id,
+            StorageUtilization { offset: 0, size },
+        ))
     }
 
     fn dealloc(&mut self, id: StorageId) {
@@ -2,14 +2,14 @@ use std::mem::MaybeUninit;
 
 use cubecl_core::{
     ir::{Elem, FloatKind},
-    Feature, MemoryConfiguration, Runtime,
+    CmmaScope, Feature, MemoryConfiguration, Runtime,
 };
 use cubecl_runtime::{
     channel::MutexComputeChannel,
     client::ComputeClient,
     memory_management::{HardwareProperties, MemoryDeviceProperties, MemoryManagement},
     storage::ComputeStorage,
-    ComputeRuntime, DeviceProperties,
+    ComputeRuntime, DeviceProperties, FeatureOverride,
 };
 
 use crate::{
@@ -23,6 +23,8 @@ use cubecl_cpp::{register_supported_types, CudaCompiler};
 pub struct RuntimeOptions {
     /// Configures the memory management.
     pub memory_config: MemoryConfiguration,
+    /// Changes applied to the detected features when the client is created.
+    pub feature_overrides: Vec<FeatureOverride<Feature>>,
 }
 
 #[derive(Debug)]
@@ -70,6 +72,7 @@ fn create_client(device: &CudaDevice, options: RuntimeOptions) -> ComputeClient<
     let mem_properties = MemoryDeviceProperties {
         max_page_size: max_memory / 4,
         alignment: CudaStorage::ALIGNMENT,
+        total_memory: Some(max_memory),
     };
 
     let warp_size = unsafe {
@@ -79,12 +82,30 @@ fn create_client(device: &CudaDevice, options: RuntimeOptions) -> ComputeClient<
         )
         .unwrap()
     };
+    let max_shared_memory = unsafe {
+        cudarc::driver::result::device::get_attribute(
+            device_ptr,
+            cudarc::driver::sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MAX_SHARED_MEMORY_PER_BLOCK,
+        )
+        .unwrap()
+    };
+    let max_cube_count = [
+        cudarc::driver::sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MAX_GRID_DIM_X,
+        cudarc::driver::sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MAX_GRID_DIM_Y,
+        cudarc::driver::sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MAX_GRID_DIM_Z,
+    ]
+    .map(|attribute| unsafe {
+        cudarc::driver::result::device::get_attribute(device_ptr, attribute).unwrap() as u32
+    });
     let hardware_props = HardwareProperties {
         plane_size_min: warp_size as u32,
         plane_size_max: warp_size as u32,
+        plane_size_actual: Some(warp_size as u32),
         // This is a guess - not clear if CUDA has a limit on the number of bindings,
         // but it's dubious it's more than this.
         max_bindings: 1024,
+        max_shared_memory_bytes: max_shared_memory as u32,
+        max_cube_count: max_cube_count.into(),
     };
 
     let memory_management = MemoryManagement::from_configuration(
@@ -99,6 +120,7 @@ fn create_client(device: &CudaDevice, options: RuntimeOptions) -> ComputeClient<
     register_supported_types(&mut device_props);
     device_props.register_feature(Feature::Type(Elem::Float(FloatKind::TF32)));
     register_wmma_features(&mut device_props, server.arch_version());
+    device_props.apply_overrides(&options.feature_overrides);
 
     ComputeClient::new(MutexComputeChannel::new(server), device_props)
 }
@@ -161,34 +183,42 @@ fn register_wmma_features(properties: &mut DeviceProperties<Feature>, arch: u32)
                 a,
                 b,
                 c,
+                d: c,
                 m: 16,
                 k: 16,
                 n: 16,
+                scope: CmmaScope::Plane,
             });
             properties.register_feature(Feature::Cmma {
                 a,
                 b,
                 c,
+                d: c,
                 m: 32,
                 k: 16,
                 n: 8,
+                scope: CmmaScope::Plane,
             });
             properties.register_feature(Feature::Cmma {
                 a,
                 b,
                 c,
+                d: c,
                 m: 8,
                 k: 16,
                 n: 32,
+                scope: CmmaScope::Plane,
             });
         }
         properties.register_feature(Feature::Cmma {
             a: Elem::Float(FloatKind::TF32),
             b: Elem::Float(FloatKind::TF32),
             c: Elem::Float(FloatKind::F32),
+            d: Elem::Float(FloatKind::F32),
             m: 16,
             k: 8,
             n: 16,
+            scope: CmmaScope::Plane,
         });
     }
 }
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use cubecl_core::{
     ir::{Elem, FloatKind},
-    Feature,
+    CmmaScope, Feature,
 };
 use cubecl_runtime::DeviceProperties;
 
@@ -316,9 +316,11 @@ impl AMDArchitecture {
                     a: i,
                     b: o,
                     c,
+                    d: c,
                     m,
                     n,
                     k,
+                    scope: CmmaScope::Plane,
                 });
             }
         }
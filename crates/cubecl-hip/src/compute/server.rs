@@ -8,7 +8,9 @@ use cubecl_core::Feature;
 use cubecl_core::{prelude::*, KernelId};
 use cubecl_hip_sys::{hiprtcResult_HIPRTC_SUCCESS, HIP_SUCCESS};
 use cubecl_runtime::debug::{DebugLogger, ProfileLevel};
-use cubecl_runtime::memory_management::MemoryUsage;
+use cubecl_runtime::memory_management::{
+    AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError, MemoryUsage,
+};
 use cubecl_runtime::storage::BindingResource;
 use cubecl_runtime::{
     memory_management::MemoryManagement,
@@ -108,8 +110,12 @@ impl ComputeServer for HipServer {
         self.ctx.memory_usage()
     }
 
-    fn create(&mut self, data: &[u8]) -> server::Handle {
-        let handle = self.empty(data.len());
+    fn uses_sub_slices(&self) -> bool {
+        self.ctx.memory_management.uses_sub_slices()
+    }
+
+    fn create(&mut self, data: &[u8]) -> Result<server::Handle, MemoryError> {
+        let handle = self.empty(data.len())?;
         let ctx = self.get_context();
 
         let binding = handle.clone().binding();
@@ -128,13 +134,94 @@ impl ComputeServer for HipServer {
             );
             assert_eq!(status, HIP_SUCCESS, "Should send data to device");
         }
-        handle
+        Ok(handle)
+    }
+
+    fn empty(&mut self, size: usize) -> Result<server::Handle, MemoryError> {
+        let ctx = self.get_context();
+        let handle = ctx.memory_management.reserve(size as u64, None)?;
+        Ok(server::Handle::new(handle, None, None, size as u64))
+    }
+
+    fn memset(&mut self, binding: server::Binding, value: u8) {
+        let ctx = self.get_context();
+        let resource = ctx.memory_management.get_resource(
+            binding.memory,
+            binding.offset_start,
+            binding.offset_end,
+        );
+
+        unsafe {
+            let status = cubecl_hip_sys::hipMemsetD8Async(
+                resource.ptr,
+                value,
+                resource.size as usize,
+                ctx.stream,
+            );
+            assert_eq!(status, HIP_SUCCESS, "Should set device memory");
+        }
     }
 
-    fn empty(&mut self, size: usize) -> server::Handle {
+    fn copy(&mut self, src: server::Binding, dst: server::Binding) {
         let ctx = self.get_context();
-        let handle = ctx.memory_management.reserve(size as u64, None);
-        server::Handle::new(handle, None, None, size as u64)
+        let src = ctx
+            .memory_management
+            .get_resource(src.memory, src.offset_start, src.offset_end);
+        let dst = ctx
+            .memory_management
+            .get_resource(dst.memory, dst.offset_start, dst.offset_end);
+
+        unsafe {
+            let status =
+                cubecl_hip_sys::hipMemcpyDtoDAsync(dst.ptr, src.ptr, src.size as usize, ctx.stream);
+            assert_eq!(status, HIP_SUCCESS, "Should copy data on the device");
+        }
+    }
+
+    fn preallocate(&mut self, size: u64) -> Result<(), MemoryError> {
+        self.get_context().memory_management.preallocate(size)
+    }
+
+    fn set_residency_budget(&mut self, budget: Option<u64>) {
+        self.get_context()
+            .memory_management
+            .set_residency_budget(budget);
+    }
+
+    fn bypass_allocation_throttle(&mut self, bypass: bool) {
+        self.get_context().memory_management.bypass_throttle(bypass);
+    }
+
+    fn reconfigure_memory(
+        &mut self,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError> {
+        let ctx = self.get_context();
+        ctx.sync();
+        ctx.memory_management.reconfigure(&properties, config)
+    }
+
+    fn start_allocation_trace(&mut self) {
+        self.get_context().memory_management.start_trace();
+    }
+
+    fn stop_allocation_trace(&mut self) -> Option<AllocationTrace> {
+        self.get_context().memory_management.stop_trace()
+    }
+
+    fn precompile(&mut self, kernels: Vec<Self::Kernel>, mode: ExecutionMode) {
+        let (ctx, logger) = self.get_context_with_logger();
+
+        for kernel in kernels {
+            let mode = kernel.execution_mode().unwrap_or(mode);
+            let mut kernel_id = kernel.id();
+            kernel_id.mode(mode);
+
+            if !ctx.module_names.contains_key(&kernel_id) {
+                ctx.compile_kernel(&kernel_id, kernel, logger, mode);
+            }
+        }
     }
 
     unsafe fn execute(
@@ -144,6 +231,7 @@ impl ComputeServer for HipServer {
         bindings: Vec<server::Binding>,
         mode: ExecutionMode,
     ) {
+        let mode = kernel.execution_mode().unwrap_or(mode);
         let mut kernel_id = kernel.id();
         kernel_id.mode(mode);
 
@@ -1,5 +1,8 @@
 use cubecl_hip_sys::HIP_SUCCESS;
-use cubecl_runtime::storage::{ComputeStorage, StorageHandle, StorageId, StorageUtilization};
+use cubecl_runtime::{
+    memory_management::MemoryError,
+    storage::{ComputeStorage, StorageHandle, StorageId, StorageUtilization},
+};
 use std::collections::HashMap;
 
 /// Buffer storage for HIP.
@@ -93,15 +96,24 @@ impl ComputeStorage for HipStorage {
         )
     }
 
-    fn alloc(&mut self, size: u64) -> StorageHandle {
+    fn alloc(&mut self, size: u64) -> Result<StorageHandle, MemoryError> {
         let id = StorageId::new();
         unsafe {
             let mut dptr: *mut ::std::os::raw::c_void = std::ptr::null_mut();
             let status = cubecl_hip_sys::hipMallocAsync(&mut dptr, size as usize, self.stream);
+            if status == cubecl_hip_sys::hipError_t_hipErrorOutOfMemory {
+                return Err(MemoryError::OutOfMemory {
+                    size,
+                    reason: "hipErrorOutOfMemory".into(),
+                });
+            }
             assert_eq!(status, HIP_SUCCESS, "Should allocate memory");
             self.memory.insert(id, dptr);
         };
-        StorageHandle::new(id, StorageUtilization { offset: 0, size })
+        Ok(StorageHandle::new(
+            id,
+            StorageUtilization { offset: 0, size },
+        ))
     }
 
     fn dealloc(&mut self, id: StorageId) {
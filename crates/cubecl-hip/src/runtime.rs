@@ -8,7 +8,7 @@ use cubecl_runtime::{
     channel::MutexComputeChannel,
     client::ComputeClient,
     memory_management::{HardwareProperties, MemoryDeviceProperties, MemoryManagement},
-    ComputeRuntime, DeviceProperties,
+    ComputeRuntime, DeviceProperties, FeatureOverride,
 };
 
 use crate::{
@@ -22,6 +22,8 @@ use crate::{
 pub struct RuntimeOptions {
     /// Configures the memory management.
     pub memory_config: MemoryConfiguration,
+    /// Changes applied to the detected features when the client is created.
+    pub feature_overrides: Vec<FeatureOverride<Feature>>,
 }
 
 #[derive(Debug)]
@@ -42,6 +44,10 @@ fn create_client(device: &HipDevice, options: RuntimeOptions) -> ComputeClient<S
     let mut prop_warp_size = 0;
     #[allow(unused_assignments)]
     let mut prop_arch_name = "";
+    #[allow(unused_assignments)]
+    let mut prop_shared_mem = 0;
+    #[allow(unused_assignments)]
+    let mut prop_max_grid_size = [0; 3];
     unsafe {
         let mut ll_device_props = MaybeUninit::uninit();
         let status = cubecl_hip_sys::hipGetDevicePropertiesR0600(
@@ -51,6 +57,8 @@ fn create_client(device: &HipDevice, options: RuntimeOptions) -> ComputeClient<S
         assert_eq!(status, HIP_SUCCESS, "Should get device properties");
         let ll_device_props = ll_device_props.assume_init();
         prop_warp_size = ll_device_props.warpSize;
+        prop_shared_mem = ll_device_props.sharedMemPerBlock;
+        prop_max_grid_size = ll_device_props.maxGridSize;
         prop_arch_name = CStr::from_ptr(ll_device_props.gcnArchName.as_ptr())
             .to_str()
             .unwrap();
@@ -88,13 +96,17 @@ fn create_client(device: &HipDevice, options: RuntimeOptions) -> ComputeClient<S
     let mem_properties = MemoryDeviceProperties {
         max_page_size: max_memory as u64 / 4,
         alignment: MEMORY_OFFSET_ALIGNMENT,
+        total_memory: Some(max_memory as u64),
     };
     let topology = HardwareProperties {
         plane_size_min: prop_warp_size as u32,
         plane_size_max: prop_warp_size as u32,
+        plane_size_actual: Some(prop_warp_size as u32),
         // This is a guess - not clear if ROCM has a limit on the number of bindings,
         // but it's dubious it's more than this.
         max_bindings: 1024,
+        max_shared_memory_bytes: prop_shared_mem as u32,
+        max_cube_count: prop_max_grid_size.map(|size| size as u32).into(),
     };
     let memory_management = MemoryManagement::from_configuration(
         storage,
@@ -106,6 +118,7 @@ fn create_client(device: &HipDevice, options: RuntimeOptions) -> ComputeClient<S
     let mut device_props = DeviceProperties::new(&[Feature::Plane], mem_properties, topology);
     register_supported_types(&mut device_props);
     arch.register_wmma_features(&mut device_props);
+    device_props.apply_overrides(&options.feature_overrides);
 
     ComputeClient::new(MutexComputeChannel::new(server), device_props)
 }
@@ -10,6 +10,7 @@ use super::kernels::{
     cmma_old::{self, config::PredefinedCmmaConfig, is_available, CmmaConfig},
     matmul,
     tiling2d::{self, Tiling2dConfig},
+    MatmulLaunchError,
 };
 
 #[derive(Debug)]
@@ -26,13 +27,23 @@ pub fn launch<R: Runtime, EG: Float>(
     lhs: TensorHandle<R, EG>,
     rhs: TensorHandle<R, EG>,
     out: TensorHandle<R, EG>,
-) {
+) -> Result<(), MatmulLaunchError> {
     match strategy {
-        Strategy::Accelerated => matmul::launch(client, lhs, rhs, out, false),
-        Strategy::PlaneMma => matmul::launch(client, lhs, rhs, out, true),
-        Strategy::CmmaOld(config) => cmma_old::launch(client, lhs, rhs, out, config.clone()),
-        Strategy::Tiling2D(config) => tiling2d::launch(client, lhs, rhs, out, config.clone()),
+        Strategy::Accelerated => {
+            matmul::launch(client, lhs, rhs, out, false)?;
+        }
+        Strategy::PlaneMma => {
+            matmul::launch(client, lhs, rhs, out, true)?;
+        }
+        Strategy::CmmaOld(config) => {
+            cmma_old::launch(client, lhs, rhs, out, config.clone());
+        }
+        Strategy::Tiling2D(config) => {
+            tiling2d::launch(client, lhs, rhs, out, config.clone());
+        }
     };
+
+    Ok(())
 }
 
 pub fn launch_ref<R: Runtime, EG: Float>(
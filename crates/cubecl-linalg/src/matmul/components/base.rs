@@ -1,6 +1,7 @@
 use cubecl_core::prelude::*;
 
 use crate::matmul::kernels::matmul::AdvancedConfig;
+use crate::matmul::kernels::MatmulAvailabilityError;
 
 use super::{config::MatmulConfig, MatmulProblem};
 
@@ -15,7 +16,7 @@ pub trait MatmulKernel<I: Numeric, O: Numeric> {
     /// Checks if the client can handle the features used in this computation
     fn check_availability<R: Runtime>(
         client: &ComputeClient<R::Server, R::Channel>,
-    ) -> Result<(), &str>;
+    ) -> Result<(), MatmulAvailabilityError>;
 
     fn make_config(
         problem: &MatmulProblem,
@@ -8,6 +8,7 @@ use crate::matmul::components::StageDim;
 use crate::matmul::components::{global, MatmulProblem};
 use crate::matmul::components::{Ident, MatrixLayout};
 use crate::matmul::kernels::matmul::AdvancedConfig;
+use crate::matmul::kernels::MatmulAvailabilityError;
 
 use cubecl_core as cubecl;
 use cubecl_core::prelude::*;
@@ -104,7 +105,7 @@ where
 
     fn check_availability<R: Runtime>(
         client: &ComputeClient<R::Server, R::Channel>,
-    ) -> Result<(), &str> {
+    ) -> Result<(), MatmulAvailabilityError> {
         SMM::check_availability::<R>(client)
     }
 
@@ -0,0 +1,228 @@
+use cubecl_core::Runtime;
+
+use crate::matmul::kernels::MatmulInvalidProblem;
+
+use super::{global, Ident, MatrixLayout};
+
+/// Returns the largest supported line size dividing `dim`, or 1 if none does.
+pub fn best_line_size(dim: u32, supported: &[u8]) -> u8 {
+    supported
+        .iter()
+        .copied()
+        .filter(|line_size| *line_size > 0 && dim % *line_size as u32 == 0)
+        .max()
+        .unwrap_or(1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Line sizes of the three operands of a matmul
+pub struct MatmulLineSizes {
+    pub lhs: u8,
+    pub rhs: u8,
+    pub out: u8,
+}
+
+impl MatmulLineSizes {
+    /// Picks the largest supported line size for each operand, so that it divides the dimension
+    /// in which the operand is aligned.
+    pub fn new(
+        m: u32,
+        n: u32,
+        k: u32,
+        lhs_layout: MatrixLayout,
+        rhs_layout: MatrixLayout,
+        supported: &[u8],
+    ) -> Self {
+        let lhs_dim = match lhs_layout {
+            MatrixLayout::RowMajor => k,
+            MatrixLayout::ColMajor => m,
+        };
+        let rhs_dim = match rhs_layout {
+            MatrixLayout::RowMajor => n,
+            MatrixLayout::ColMajor => k,
+        };
+
+        Self {
+            lhs: best_line_size(lhs_dim, supported),
+            rhs: best_line_size(rhs_dim, supported),
+            out: best_line_size(n, supported),
+        }
+    }
+
+    /// Same as [new](MatmulLineSizes::new), using the line sizes supported by the runtime.
+    pub fn for_runtime<R: Runtime>(
+        m: u32,
+        n: u32,
+        k: u32,
+        lhs_layout: MatrixLayout,
+        rhs_layout: MatrixLayout,
+    ) -> Self {
+        Self::new(m, n, k, lhs_layout, rhs_layout, R::supported_line_sizes())
+    }
+}
+
+/// Checks the line sizes of a [global config](global::Config) before any tensor is involved.
+///
+/// The global and stage line sizes of every operand must be supported by the runtime, and must
+/// divide the dimension of the stage in which the operand is aligned. Every violation is
+/// returned, in the order lhs, rhs, out.
+pub fn validate_config<R: Runtime, G: global::Config>(
+    config: &G,
+) -> Result<(), Vec<MatmulInvalidProblem>> {
+    validate_config_line_sizes(config, R::supported_line_sizes())
+}
+
+fn validate_config_line_sizes<G: global::Config>(
+    config: &G,
+    supported: &[u8],
+) -> Result<(), Vec<MatmulInvalidProblem>> {
+    let mut errors = Vec::new();
+
+    for ident in [Ident::Lhs, Ident::Rhs, Ident::Out] {
+        let stage_dim = config.stage_dim(ident);
+        let size = match config.layout(ident) {
+            MatrixLayout::RowMajor => stage_dim.num_elements_y_dim(),
+            MatrixLayout::ColMajor => stage_dim.num_elements_x_dim(),
+        };
+
+        let mut line_sizes = vec![
+            config.global_line_size(ident),
+            config.stage_line_size(ident),
+        ];
+        line_sizes.dedup();
+
+        for line_size in line_sizes.into_iter().map(|line_size| line_size as u8) {
+            if !supported.contains(&line_size) {
+                errors.push(MatmulInvalidProblem::UnsupportedLineSize {
+                    ident,
+                    line_size,
+                    supported: supported.to_vec(),
+                });
+            }
+
+            if line_size == 0 || size.next_multiple_of(line_size as u32) != size {
+                errors.push(match ident {
+                    Ident::Lhs => MatmulInvalidProblem::InvalidLineSizeLhs { size, line_size },
+                    Ident::Rhs => MatmulInvalidProblem::InvalidLineSizeRhs { size, line_size },
+                    Ident::Out => MatmulInvalidProblem::InvalidLineSizeOut { size, line_size },
+                });
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matmul::components::{
+        global::homogeneous, stage::row_accumulate, stage::TilingOrderConfig, tile::plane, StageDim,
+    };
+
+    fn config(
+        lhs_line_size: u32,
+        rhs_line_size: u32,
+        out_line_size: u32,
+    ) -> homogeneous::Config<row_accumulate::Config<plane::Config>> {
+        let stage_dim = StageDim {
+            tile_size_x: 16,
+            tile_size_y: 16,
+            num_tiles_x: 2,
+            num_tiles_y: 2,
+        };
+        let tmm_config =
+            plane::Config::new(32, MatrixLayout::RowMajor, MatrixLayout::RowMajor, 4, 4, 4);
+        let smm_config = row_accumulate::Config::new(
+            tmm_config,
+            stage_dim,
+            stage_dim,
+            stage_dim,
+            2,
+            TilingOrderConfig::XMajor,
+        );
+
+        homogeneous::Config::new(
+            smm_config,
+            false,
+            false,
+            MatrixLayout::RowMajor,
+            MatrixLayout::RowMajor,
+            lhs_line_size,
+            rhs_line_size,
+            out_line_size,
+        )
+    }
+
+    #[test]
+    fn supported_line_sizes_pass_validation() {
+        assert!(validate_config_line_sizes(&config(4, 2, 4), &[1, 2, 4]).is_ok());
+    }
+
+    #[test]
+    fn every_invalid_config_line_size_is_reported() {
+        let errors = validate_config_line_sizes(&config(8, 3, 4), &[1, 2, 4]).unwrap_err();
+
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(
+            errors[0],
+            MatmulInvalidProblem::UnsupportedLineSize {
+                ident: Ident::Lhs,
+                line_size: 8,
+                ..
+            }
+        ));
+        assert!(matches!(
+            errors[1],
+            MatmulInvalidProblem::UnsupportedLineSize {
+                ident: Ident::Rhs,
+                line_size: 3,
+                ..
+            }
+        ));
+        assert!(matches!(
+            errors[2],
+            MatmulInvalidProblem::InvalidLineSizeRhs {
+                size: 32,
+                line_size: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn best_line_size_picks_largest_divisor() {
+        assert_eq!(best_line_size(12, &[1, 2, 4, 8]), 4);
+        assert_eq!(best_line_size(64, &[4, 2]), 4);
+    }
+
+    #[test]
+    fn best_line_size_falls_back_to_one() {
+        assert_eq!(best_line_size(7, &[2, 4]), 1);
+        assert_eq!(best_line_size(8, &[]), 1);
+    }
+
+    #[test]
+    fn line_sizes_follow_operand_layouts() {
+        let line_sizes = MatmulLineSizes::new(
+            6,
+            8,
+            12,
+            MatrixLayout::ColMajor,
+            MatrixLayout::RowMajor,
+            &[1, 2, 4, 8],
+        );
+
+        assert_eq!(
+            line_sizes,
+            MatmulLineSizes {
+                lhs: 2,
+                rhs: 8,
+                out: 8
+            }
+        );
+    }
+}
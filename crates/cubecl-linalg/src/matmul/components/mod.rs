@@ -5,8 +5,10 @@ pub mod tile;
 
 mod base;
 mod config;
+mod line_size;
 mod problem;
 
 pub use base::*;
-pub use config::{as_cmma_layout, Ident, MatrixLayout, PlaneMapper, StageDim};
-pub use problem::MatmulProblem;
+pub use config::{as_cmma_layout, Ident, MatrixLayout, PlaneMapper, StageDim, StageDims};
+pub use line_size::{best_line_size, validate_config, MatmulLineSizes};
+pub use problem::{validate_line_sizes, MatmulProblem};
@@ -1,3 +1,7 @@
+use std::fmt::Display;
+
+use crate::matmul::kernels::MatmulInvalidProblem;
+
 use super::{batch, MatrixLayout};
 
 #[derive(Clone)]
@@ -14,48 +18,192 @@ pub struct MatmulProblem {
     pub out_line_size: u8,
 }
 
+/// A one-line summary of the problem, such as
+/// `matmul m=1024 n=1024 k=512 batches=[2] lhs=row(4) rhs=col(4) out=row(4)`, with the layout
+/// and line size of every operand.
+impl Display for MatmulProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let layout = |layout: MatrixLayout| match layout {
+            MatrixLayout::RowMajor => "row",
+            MatrixLayout::ColMajor => "col",
+        };
+
+        write!(f, "matmul m={} n={} k={}", self.m, self.n, self.k)?;
+        if !self.batches.is_empty() {
+            write!(f, " batches={:?}", self.batches)?;
+        }
+        write!(
+            f,
+            " lhs={}({}) rhs={}({}) out=row({})",
+            layout(self.lhs_layout),
+            self.lhs_line_size,
+            layout(self.rhs_layout),
+            self.rhs_line_size,
+            self.out_line_size
+        )
+    }
+}
+
 impl MatmulProblem {
     /// Returns the total number of batches
     pub(crate) fn num_batches(&self) -> usize {
         self.batches.iter().copied().product()
     }
 
-    /// Asserts that the problem can be solved with the given batch matmul configs
+    /// Checks that the problem can be solved with the given batch matmul configs
     ///
-    /// # Panics:
+    /// # Errors:
     ///
     ///  - If dimensions of the problem are larger than allowed by the config
     ///  - If line sizes do not divide well the dimension in which they are aligned
-    pub(crate) fn check_config<B: batch::Config>(&self, config: &B) {
-        assert!(
-            self.m <= config.max_m() as usize,
-            "Problem has m={} but these configs can only have m<={}",
-            self.m,
-            config.max_m()
-        );
-        assert!(
-            self.n <= config.max_n() as usize,
-            "Problem has n={} but these configs can only have n<={}",
-            self.n,
-            config.max_n()
-        );
-        assert!(
-            self.num_batches() <= config.max_batches() as usize,
-            "Problem has {} batches but these configs can only have batches<={}",
-            self.num_batches(),
-            config.max_batches()
-        );
+    pub(crate) fn check_config<B: batch::Config>(
+        &self,
+        config: &B,
+    ) -> Result<(), MatmulInvalidProblem> {
+        if self.m > config.max_m() as usize {
+            return Err(MatmulInvalidProblem::ExceededMSize {
+                m: self.m as u32,
+                max_m: config.max_m(),
+            });
+        }
+
+        if self.n > config.max_n() as usize {
+            return Err(MatmulInvalidProblem::ExceededNSize {
+                n: self.n as u32,
+                max_n: config.max_n(),
+            });
+        }
+
+        if self.num_batches() > config.max_batches() as usize {
+            return Err(MatmulInvalidProblem::ExceededBatchSize {
+                b: self.num_batches() as u32,
+                max_b: config.max_batches(),
+            });
+        }
+
+        if let Err(mut errors) = validate_line_sizes(
+            self,
+            self.lhs_line_size,
+            self.rhs_line_size,
+            self.out_line_size,
+        ) {
+            return Err(errors.remove(0));
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that the line sizes divide the dimension in which each operand is aligned.
+///
+/// Unlike [check_config](MatmulProblem::check_config), which stops at the first invalid line
+/// size, every violation is returned, in the order lhs, rhs, out.
+pub fn validate_line_sizes(
+    problem: &MatmulProblem,
+    lhs_line_size: u8,
+    rhs_line_size: u8,
+    out_line_size: u8,
+) -> Result<(), Vec<MatmulInvalidProblem>> {
+    let mut errors = Vec::new();
+
+    let lhs_size = match problem.lhs_layout {
+        MatrixLayout::RowMajor => problem.k,
+        MatrixLayout::ColMajor => problem.m,
+    };
+    if lhs_size % lhs_line_size as usize != 0 {
+        errors.push(MatmulInvalidProblem::InvalidLineSizeLhs {
+            size: lhs_size as u32,
+            line_size: lhs_line_size,
+        });
+    }
 
-        assert!(match self.lhs_layout {
-            MatrixLayout::RowMajor => self.k % self.lhs_line_size as usize == 0,
-            MatrixLayout::ColMajor => self.m % self.lhs_line_size as usize == 0,
+    let rhs_size = match problem.rhs_layout {
+        MatrixLayout::RowMajor => problem.n,
+        MatrixLayout::ColMajor => problem.k,
+    };
+    if rhs_size % rhs_line_size as usize != 0 {
+        errors.push(MatmulInvalidProblem::InvalidLineSizeRhs {
+            size: rhs_size as u32,
+            line_size: rhs_line_size,
         });
+    }
 
-        assert!(match self.rhs_layout {
-            MatrixLayout::RowMajor => self.n % self.rhs_line_size as usize == 0,
-            MatrixLayout::ColMajor => self.k % self.rhs_line_size as usize == 0,
+    if problem.n % out_line_size as usize != 0 {
+        errors.push(MatmulInvalidProblem::InvalidLineSizeOut {
+            size: problem.n as u32,
+            line_size: out_line_size,
         });
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(lhs_layout: MatrixLayout, rhs_layout: MatrixLayout) -> MatmulProblem {
+        MatmulProblem {
+            m: 6,
+            n: 10,
+            k: 12,
+            batches: vec![1],
+            lhs_layout,
+            rhs_layout,
+            lhs_line_size: 1,
+            rhs_line_size: 1,
+            out_line_size: 1,
+        }
+    }
+
+    #[test]
+    fn problem_summary_fits_on_one_line() {
+        let problem = problem(MatrixLayout::RowMajor, MatrixLayout::ColMajor);
+
+        assert_eq!(
+            problem.to_string(),
+            "matmul m=6 n=10 k=12 batches=[1] lhs=row(1) rhs=col(1) out=row(1)"
+        );
+    }
+
+    #[test]
+    fn valid_line_sizes_pass() {
+        let problem = problem(MatrixLayout::RowMajor, MatrixLayout::RowMajor);
+
+        assert!(validate_line_sizes(&problem, 4, 2, 2).is_ok());
+    }
+
+    #[test]
+    fn every_invalid_line_size_is_reported() {
+        let problem = problem(MatrixLayout::ColMajor, MatrixLayout::ColMajor);
+
+        let errors = validate_line_sizes(&problem, 4, 8, 4).unwrap_err();
 
-        assert!(self.n % self.out_line_size as usize == 0);
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(
+            errors[0],
+            MatmulInvalidProblem::InvalidLineSizeLhs {
+                size: 6,
+                line_size: 4
+            }
+        ));
+        assert!(matches!(
+            errors[1],
+            MatmulInvalidProblem::InvalidLineSizeRhs {
+                size: 12,
+                line_size: 8
+            }
+        ));
+        assert!(matches!(
+            errors[2],
+            MatmulInvalidProblem::InvalidLineSizeOut {
+                size: 10,
+                line_size: 4
+            }
+        ));
     }
 }
@@ -11,7 +11,10 @@ use crate::matmul::{
         stage::{self, Config as _, StageReader, StageWriter},
         tile, Ident, MatmulKernel, MatmulProblem, MatrixLayout, PlaneMapper, StageDim,
     },
-    kernels::matmul::{create_stage_dim, AdvancedConfig},
+    kernels::{
+        matmul::{create_stage_dim, AdvancedConfig},
+        MatmulAvailabilityError,
+    },
 };
 
 use super::reader::{LhsReader, RhsReader};
@@ -159,7 +162,7 @@ where
 
     fn check_availability<R: Runtime>(
         client: &ComputeClient<R::Server, R::Channel>,
-    ) -> Result<(), &str> {
+    ) -> Result<(), MatmulAvailabilityError> {
         TMM::check_availability::<R>(client)
     }
 
@@ -5,7 +5,8 @@ use crate::matmul::components::{
     as_cmma_layout, tile, Ident, MatmulKernel, MatmulProblem, MatrixLayout,
 };
 use crate::matmul::kernels::matmul::AdvancedConfig;
-use cubecl_core::{self as cubecl, Feature};
+use crate::matmul::kernels::MatmulAvailabilityError;
+use cubecl_core::{self as cubecl, CmmaScope, Feature};
 use cubecl_core::{cmma, prelude::*};
 use half::{bf16, f16};
 use std::marker::PhantomData;
@@ -98,7 +99,7 @@ macro_rules! instruction {
 
             fn check_availability<R: Runtime>(
                 client: &ComputeClient<R::Server, R::Channel>,
-            ) -> Result<(), &str> {
+            ) -> Result<(), MatmulAvailabilityError> {
                 check_availability::<I, O, R>(Self::M, Self::N, Self::K, client)
             }
 
@@ -198,31 +199,62 @@ fn check_availability<I: Numeric, O: Numeric, R: Runtime>(
     n: u32,
     k: u32,
     client: &ComputeClient<R::Server, R::Channel>,
-) -> Result<(), &str> {
+) -> Result<(), MatmulAvailabilityError> {
+    let i_elem = I::as_elem();
+    let o_elem = O::as_elem();
+
+    // Check the types first, so that unsupported types aren't reported as a missing cmma shape.
+    if !client
+        .properties()
+        .missing_features(&[Feature::Type(i_elem), Feature::Type(o_elem)])
+        .is_empty()
+    {
+        return Err(MatmulAvailabilityError::TypesUnavailable {
+            input: i_elem,
+            output: o_elem,
+        });
+    }
+
     if !client.properties().feature_enabled(Feature::Cmma {
-        a: I::as_elem(),
-        b: I::as_elem(),
-        c: O::as_elem(),
+        a: i_elem,
+        b: i_elem,
+        c: o_elem,
+        d: o_elem,
         m: m as u8,
         k: k as u8,
         n: n as u8,
+        scope: CmmaScope::Plane,
     }) {
-        return Err("Cmma not supported.");
-    }
-
-    if !(client
-        .properties()
-        .feature_enabled(Feature::Type(I::as_elem()))
-        && client
-            .properties()
-            .feature_enabled(Feature::Type(O::as_elem())))
-    {
-        return Err("Types not supported.");
+        return Err(MatmulAvailabilityError::CmmaInstructionUnavailable {
+            input: i_elem,
+            output: o_elem,
+            m,
+            n,
+            k,
+            alternatives: Some(closest_cmma_shapes(
+                client.properties().supported_cmma_shapes(i_elem, o_elem),
+                (m, n, k),
+            )),
+        });
     }
 
     Ok(())
 }
 
+/// The supported shapes closest to the requested one, at most five of them.
+fn closest_cmma_shapes(
+    supported: Vec<(u8, u8, u8)>,
+    shape: (u32, u32, u32),
+) -> Vec<(u32, u32, u32)> {
+    let mut shapes: Vec<_> = supported
+        .into_iter()
+        .map(|(m, n, k)| (m as u32, n as u32, k as u32))
+        .collect();
+    shapes.sort_by_key(|(m, n, k)| m.abs_diff(shape.0) + n.abs_diff(shape.1) + k.abs_diff(shape.2));
+    shapes.truncate(5);
+    shapes
+}
+
 fn check_plane_dim(actual_plane_dim: u32) {
     assert_eq!(32, actual_plane_dim, "Error: Expected plane dimension to be 32, but found {}. Please ensure that cube dimension x is set correctly.",
         actual_plane_dim
@@ -3,6 +3,7 @@ use crate::matmul::components::tile::Config as TileConfig;
 use crate::matmul::components::MatmulProblem;
 use crate::matmul::components::{config::PlaneMapper, tile, Ident, MatmulKernel, MatrixLayout};
 use crate::matmul::kernels::matmul::AdvancedConfig;
+use crate::matmul::kernels::MatmulAvailabilityError;
 use cubecl_core::prelude::*;
 use cubecl_core::{self as cubecl, Feature};
 use std::marker::PhantomData;
@@ -347,19 +348,23 @@ impl<I: Numeric, O: Numeric, const M: u32, const N: u32, const K: u32> MatmulKer
 
     fn check_availability<R: Runtime>(
         client: &ComputeClient<R::Server, R::Channel>,
-    ) -> Result<(), &str> {
+    ) -> Result<(), MatmulAvailabilityError> {
         if !client.properties().feature_enabled(Feature::Plane) {
-            return Err("Planes not supported.");
+            return Err(MatmulAvailabilityError::PlaneOperationsUnavailable);
         }
 
-        if !(client
+        let i_elem = I::as_elem();
+        let o_elem = O::as_elem();
+
+        if !client
             .properties()
-            .feature_enabled(Feature::Type(I::as_elem()))
-            && client
-                .properties()
-                .feature_enabled(Feature::Type(O::as_elem())))
+            .missing_features(&[Feature::Type(i_elem), Feature::Type(o_elem)])
+            .is_empty()
         {
-            return Err("Types not supported.");
+            return Err(MatmulAvailabilityError::TypesUnavailable {
+                input: i_elem,
+                output: o_elem,
+            });
         }
 
         Ok(())
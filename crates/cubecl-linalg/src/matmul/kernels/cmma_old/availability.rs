@@ -2,7 +2,7 @@ use cubecl_core::{
     client::ComputeClient,
     ir::{Elem, FloatKind},
     prelude::CubePrimitive,
-    Feature, Runtime,
+    CmmaScope, Feature, Runtime,
 };
 
 use crate::matmul::kernels::cmma_old::config::CmmaConfig;
@@ -27,9 +27,11 @@ pub fn check_cmma_availability<R: Runtime, F: CubePrimitive>(
         a: Elem::Float(FloatKind::F16),
         b: Elem::Float(FloatKind::F16),
         c: F::as_elem(),
+        d: F::as_elem(),
         m: tile_dim.m as u8,
         k: tile_dim.k as u8,
         n: tile_dim.n as u8,
+        scope: CmmaScope::Plane,
     }) {
         return Err(UnavailabilityReason::CmmaInstructionsUnsupported);
     }
@@ -0,0 +1,477 @@
+use cubecl_core::ir::Elem;
+
+use crate::matmul::components::{Ident, MatmulProblem};
+use std::fmt::{Debug, Display};
+
+/// Reasons why a matmul can't be launched.
+pub enum MatmulLaunchError {
+    /// A feature required by the selected algorithm isn't available on the device.
+    Unavailable(MatmulAvailabilityError),
+    /// The problem can't be solved by the selected algorithm.
+    InvalidProblem(MatmulInvalidProblem),
+}
+
+/// Features required by a matmul that can be missing on a device.
+pub enum MatmulAvailabilityError {
+    /// Plane operations are not supported.
+    PlaneOperationsUnavailable,
+    /// Plane operations are supported, but not with the plane size the tiling requires.
+    IncompatiblePlaneSize {
+        /// The plane size required by the selected tile.
+        required: u32,
+        /// The smallest plane size used by the device.
+        min: u32,
+        /// The biggest plane size used by the device.
+        max: u32,
+    },
+    /// The input or output element types are not supported.
+    TypesUnavailable { input: Elem, output: Elem },
+    /// The cmma instruction with the given shape and element types is not supported.
+    CmmaInstructionUnavailable {
+        input: Elem,
+        output: Elem,
+        m: u32,
+        n: u32,
+        k: u32,
+        /// The closest `(m, n, k)` shapes supported for the same element types, if known.
+        alternatives: Option<Vec<(u32, u32, u32)>>,
+    },
+    /// The device couldn't create the kernel, even with the smallest stage size, because it
+    /// needs more resources, such as registers, than the device has.
+    OutOfResources {
+        /// The error reported by the runtime for the last attempt.
+        reason: String,
+    },
+}
+
+/// Ways in which a matmul problem can be incompatible with the selected algorithm.
+pub enum MatmulInvalidProblem {
+    /// The m dimension is larger than what the configuration can handle.
+    ExceededMSize { m: u32, max_m: u32 },
+    /// The n dimension is larger than what the configuration can handle.
+    ExceededNSize { n: u32, max_n: u32 },
+    /// There are more batches than what the configuration can handle.
+    ExceededBatchSize { b: u32, max_b: u32 },
+    /// The line size of lhs doesn't divide the dimension it is aligned with.
+    InvalidLineSizeLhs { size: u32, line_size: u8 },
+    /// The line size of rhs doesn't divide the dimension it is aligned with.
+    InvalidLineSizeRhs { size: u32, line_size: u8 },
+    /// The line size of out doesn't divide the dimension it is aligned with.
+    InvalidLineSizeOut { size: u32, line_size: u8 },
+    /// The line size of an operand isn't one of the line sizes supported by the runtime.
+    UnsupportedLineSize {
+        ident: Ident,
+        line_size: u8,
+        supported: Vec<u8>,
+    },
+    /// An operand has fewer than two dimensions, so it isn't a matrix.
+    RankTooLow { ident: Ident, rank: u32 },
+    /// The k dimension of lhs doesn't match the k dimension of rhs.
+    MismatchedContractionDim { lhs_k: u32, rhs_k: u32 },
+    /// The batch dimensions of lhs and rhs can't be broadcast together.
+    IncompatibleBatchShapes { lhs: Vec<u32>, rhs: Vec<u32> },
+    /// The output isn't row-major with contiguous rows, so the kernel can't write it.
+    UnsupportedOutLayout {
+        strides: Vec<u32>,
+        /// The strides of a contiguous output with the same shape.
+        expected: Vec<u32>,
+    },
+}
+
+impl From<MatmulAvailabilityError> for MatmulLaunchError {
+    fn from(value: MatmulAvailabilityError) -> Self {
+        Self::Unavailable(value)
+    }
+}
+
+impl From<MatmulInvalidProblem> for MatmulLaunchError {
+    fn from(value: MatmulInvalidProblem) -> Self {
+        Self::InvalidProblem(value)
+    }
+}
+
+impl Debug for MatmulLaunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatmulLaunchError::Unavailable(err) => write!(
+                f,
+                "Unable to launch matmul because a required feature is unavailable: {err:?}"
+            ),
+            MatmulLaunchError::InvalidProblem(err) => write!(
+                f,
+                "Unable to launch matmul because the problem is invalid: {err:?}"
+            ),
+        }
+    }
+}
+
+impl Debug for MatmulAvailabilityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatmulAvailabilityError::PlaneOperationsUnavailable => {
+                write!(f, "Plane operations not supported.")
+            }
+            MatmulAvailabilityError::IncompatiblePlaneSize { required, min, max } => write!(
+                f,
+                "The selected tile requires a plane size of {required}, but the device uses plane sizes between {min} and {max}. Pick a tile whose dimensions are divisible by {min}."
+            ),
+            MatmulAvailabilityError::TypesUnavailable { input, output } => write!(
+                f,
+                "Types input={input:?} and/or output={output:?} not supported."
+            ),
+            MatmulAvailabilityError::CmmaInstructionUnavailable {
+                input,
+                output,
+                m,
+                n,
+                k,
+                alternatives,
+            } => {
+                write!(
+                    f,
+                    "Cmma on inputs {input:?} and outputs {output:?} with shape m={m:?}, n={n:?}, k={k:?} not supported."
+                )?;
+
+                match alternatives {
+                    Some(alternatives) if !alternatives.is_empty() => {
+                        write!(f, " Supported shapes (m, n, k) are:")?;
+                        for (m, n, k) in alternatives {
+                            write!(f, " ({m}, {n}, {k})")?;
+                        }
+                        Ok(())
+                    }
+                    Some(_) => write!(f, " No cmma shape is supported for these types."),
+                    None => Ok(()),
+                }
+            }
+            MatmulAvailabilityError::OutOfResources { reason } => write!(
+                f,
+                "The kernel needs more resources than the device has, even with the smallest stage size: {reason}"
+            ),
+        }
+    }
+}
+
+impl Debug for MatmulInvalidProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatmulInvalidProblem::ExceededMSize { m, max_m } => write!(
+                f,
+                "Problem has m={m} but these configs can only have m<={max_m}"
+            ),
+            MatmulInvalidProblem::ExceededNSize { n, max_n } => write!(
+                f,
+                "Problem has n={n} but these configs can only have n<={max_n}"
+            ),
+            MatmulInvalidProblem::ExceededBatchSize { b, max_b } => write!(
+                f,
+                "Problem has {b} batches but these configs can only have batches<={max_b}"
+            ),
+            MatmulInvalidProblem::InvalidLineSizeLhs { size, line_size } => write!(
+                f,
+                "The lhs line size {line_size} does not divide the aligned dimension of size {size}"
+            ),
+            MatmulInvalidProblem::InvalidLineSizeRhs { size, line_size } => write!(
+                f,
+                "The rhs line size {line_size} does not divide the aligned dimension of size {size}"
+            ),
+            MatmulInvalidProblem::InvalidLineSizeOut { size, line_size } => write!(
+                f,
+                "The out line size {line_size} does not divide the aligned dimension of size {size}"
+            ),
+            MatmulInvalidProblem::UnsupportedLineSize {
+                ident,
+                line_size,
+                supported,
+            } => write!(
+                f,
+                "The {ident:?} line size {line_size} isn't supported by the runtime, which supports {supported:?}"
+            ),
+            MatmulInvalidProblem::RankTooLow { ident, rank } => write!(
+                f,
+                "The {ident:?} tensor has rank {rank} but matmul operands must have at least 2 dimensions"
+            ),
+            MatmulInvalidProblem::MismatchedContractionDim { lhs_k, rhs_k } => write!(
+                f,
+                "Lhs has k={lhs_k} (its last dimension) but rhs has k={rhs_k} (its second to last dimension), they must be equal"
+            ),
+            MatmulInvalidProblem::IncompatibleBatchShapes { lhs, rhs } => {
+                write!(
+                    f,
+                    "Lhs batch shape {lhs:?} and rhs batch shape {rhs:?} can't be broadcast together"
+                )?;
+
+                match incompatible_batch_dim(lhs, rhs) {
+                    Some((dim, lhs_size, rhs_size)) => write!(
+                        f,
+                        ": batch dimension -{dim} has sizes {lhs_size} and {rhs_size}, they must be equal or one of them must be 1"
+                    ),
+                    None => Ok(()),
+                }
+            }
+            MatmulInvalidProblem::UnsupportedOutLayout { strides, expected } => write!(
+                f,
+                "The output has strides {strides:?} but must be row-major with contiguous rows, make it contiguous with strides {expected:?}"
+            ),
+        }
+    }
+}
+
+/// Finds the first pair of batch dimensions, starting from the last one, that can't be broadcast
+/// together following NumPy rules.
+///
+/// Returns the position of the dimension counted from the end (starting at 1) along with both
+/// sizes, or `None` if the shapes are compatible. Missing leading dimensions are broadcast.
+pub(crate) fn incompatible_batch_dim(lhs: &[u32], rhs: &[u32]) -> Option<(usize, u32, u32)> {
+    lhs.iter()
+        .rev()
+        .zip(rhs.iter().rev())
+        .enumerate()
+        .find(|(_, (lhs, rhs))| lhs != rhs && **lhs != 1 && **rhs != 1)
+        .map(|(i, (lhs, rhs))| (i + 1, *lhs, *rhs))
+}
+
+impl MatmulLaunchError {
+    /// Describe the error along with the summary of the [problem](MatmulProblem) that caused it,
+    /// so a failure log shows the full problem shape.
+    pub fn describe(&self, problem: &MatmulProblem) -> String {
+        format!("{self} ({problem})")
+    }
+
+    /// The stable numeric code of the error, to identify it without parsing its message.
+    ///
+    /// Codes are grouped by kind: `1xx` for [unavailable features](MatmulAvailabilityError) and
+    /// `2xx` for [invalid problems](MatmulInvalidProblem). A code is never reused or reassigned
+    /// once released, new variants get the next free code of their group.
+    pub fn code(&self) -> u32 {
+        match self {
+            MatmulLaunchError::Unavailable(err) => err.code(),
+            MatmulLaunchError::InvalidProblem(err) => err.code(),
+        }
+    }
+
+    /// A short description of the error with the given [code](Self::code), or `None` if the code
+    /// isn't assigned.
+    pub fn code_description(code: u32) -> Option<&'static str> {
+        let description = match code {
+            100 => "Plane operations are not supported",
+            101 => "The input or output element types are not supported",
+            102 => "The cmma instruction is not supported for the shape and element types",
+            103 => "The device doesn't use the plane size required by the tiling",
+            104 => "The device doesn't have the resources to create the kernel with any stage size",
+            200 => "The m dimension exceeds what the configuration can handle",
+            201 => "The n dimension exceeds what the configuration can handle",
+            202 => "The number of batches exceeds what the configuration can handle",
+            203 => "The line size of lhs doesn't divide its aligned dimension",
+            204 => "The line size of rhs doesn't divide its aligned dimension",
+            205 => "The line size of out doesn't divide its aligned dimension",
+            206 => "The k dimensions of lhs and rhs don't match",
+            207 => "The batch shapes of lhs and rhs can't be broadcast together",
+            208 => "The output isn't row-major with contiguous rows",
+            209 => "The line size of an operand isn't supported by the runtime",
+            210 => "An operand has fewer than two dimensions",
+            _ => return None,
+        };
+
+        Some(description)
+    }
+}
+
+impl MatmulAvailabilityError {
+    /// The stable numeric code of the error, in the `1xx` range.
+    pub fn code(&self) -> u32 {
+        match self {
+            MatmulAvailabilityError::PlaneOperationsUnavailable => 100,
+            MatmulAvailabilityError::TypesUnavailable { .. } => 101,
+            MatmulAvailabilityError::CmmaInstructionUnavailable { .. } => 102,
+            MatmulAvailabilityError::IncompatiblePlaneSize { .. } => 103,
+            MatmulAvailabilityError::OutOfResources { .. } => 104,
+        }
+    }
+}
+
+impl MatmulInvalidProblem {
+    /// The stable numeric code of the error, in the `2xx` range.
+    pub fn code(&self) -> u32 {
+        match self {
+            MatmulInvalidProblem::ExceededMSize { .. } => 200,
+            MatmulInvalidProblem::ExceededNSize { .. } => 201,
+            MatmulInvalidProblem::ExceededBatchSize { .. } => 202,
+            MatmulInvalidProblem::InvalidLineSizeLhs { .. } => 203,
+            MatmulInvalidProblem::InvalidLineSizeRhs { .. } => 204,
+            MatmulInvalidProblem::InvalidLineSizeOut { .. } => 205,
+            MatmulInvalidProblem::MismatchedContractionDim { .. } => 206,
+            MatmulInvalidProblem::IncompatibleBatchShapes { .. } => 207,
+            MatmulInvalidProblem::UnsupportedOutLayout { .. } => 208,
+            MatmulInvalidProblem::UnsupportedLineSize { .. } => 209,
+            MatmulInvalidProblem::RankTooLow { .. } => 210,
+        }
+    }
+}
+
+impl Display for MatmulLaunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl Display for MatmulAvailabilityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl Display for MatmulInvalidProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for MatmulLaunchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MatmulLaunchError::Unavailable(err) => Some(err),
+            MatmulLaunchError::InvalidProblem(err) => Some(err),
+        }
+    }
+}
+
+impl std::error::Error for MatmulAvailabilityError {}
+
+impl std::error::Error for MatmulInvalidProblem {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn launch_error_chains_to_availability_error() {
+        let err: MatmulLaunchError = MatmulAvailabilityError::PlaneOperationsUnavailable.into();
+
+        assert_eq!(
+            err.source().unwrap().to_string(),
+            "Plane operations not supported."
+        );
+    }
+
+    #[test]
+    fn launch_error_converts_to_boxed_error() {
+        fn launch() -> Result<(), Box<dyn Error>> {
+            Err(MatmulLaunchError::from(
+                MatmulInvalidProblem::ExceededMSize { m: 512, max_m: 256 },
+            ))?;
+            Ok(())
+        }
+
+        assert_eq!(
+            launch().unwrap_err().to_string(),
+            "Unable to launch matmul because the problem is invalid: \
+             Problem has m=512 but these configs can only have m<=256"
+        );
+    }
+
+    #[test]
+    fn cmma_unavailable_lists_alternatives() {
+        let f16 = Elem::Float(cubecl_core::ir::FloatKind::F16);
+        let err = MatmulAvailabilityError::CmmaInstructionUnavailable {
+            input: f16,
+            output: f16,
+            m: 8,
+            n: 8,
+            k: 8,
+            alternatives: Some(vec![(16, 16, 16), (32, 8, 16)]),
+        };
+
+        assert!(format!("{err:?}")
+            .ends_with("not supported. Supported shapes (m, n, k) are: (16, 16, 16) (32, 8, 16)"));
+    }
+
+    #[test]
+    fn error_codes_are_stable() {
+        let err: MatmulLaunchError = MatmulInvalidProblem::MismatchedContractionDim {
+            lhs_k: 16,
+            rhs_k: 32,
+        }
+        .into();
+
+        assert_eq!(err.code(), 206);
+        assert_eq!(
+            MatmulLaunchError::code_description(err.code()),
+            Some("The k dimensions of lhs and rhs don't match")
+        );
+        assert_eq!(MatmulLaunchError::code_description(300), None);
+    }
+
+    #[test]
+    fn rank_too_low_names_the_operand() {
+        let err = MatmulInvalidProblem::RankTooLow {
+            ident: Ident::Rhs,
+            rank: 1,
+        };
+
+        assert_eq!(err.code(), 210);
+        assert_eq!(
+            format!("{err:?}"),
+            "The Rhs tensor has rank 1 but matmul operands must have at least 2 dimensions"
+        );
+    }
+
+    #[test]
+    fn incompatible_plane_size_suggests_min_plane_size() {
+        let err = MatmulAvailabilityError::IncompatiblePlaneSize {
+            required: 32,
+            min: 8,
+            max: 16,
+        };
+
+        assert!(format!("{err:?}").ends_with("dimensions are divisible by 8."));
+    }
+
+    #[test]
+    fn batch_shapes_broadcast_like_numpy() {
+        assert_eq!(incompatible_batch_dim(&[4, 1, 3], &[2, 3]), None);
+        assert_eq!(incompatible_batch_dim(&[], &[2, 3]), None);
+        assert_eq!(incompatible_batch_dim(&[2, 3, 5], &[3, 4]), Some((1, 5, 4)));
+    }
+
+    #[test]
+    fn unsupported_out_layout_reports_expected_strides() {
+        let err = MatmulInvalidProblem::UnsupportedOutLayout {
+            strides: vec![1, 4],
+            expected: vec![8, 1],
+        };
+
+        assert_eq!(
+            err.to_string(),
+            "The output has strides [1, 4] but must be row-major with contiguous rows, make it \
+             contiguous with strides [8, 1]"
+        );
+    }
+
+    #[test]
+    fn incompatible_batch_shapes_reports_dimension() {
+        let err = MatmulInvalidProblem::IncompatibleBatchShapes {
+            lhs: vec![2, 3],
+            rhs: vec![4, 3],
+        };
+
+        assert_eq!(
+            format!("{err:?}"),
+            "Lhs batch shape [2, 3] and rhs batch shape [4, 3] can't be broadcast together: \
+             batch dimension -2 has sizes 2 and 4, they must be equal or one of them must be 1"
+        );
+    }
+
+    #[test]
+    fn mismatched_contraction_dim_reports_both_sizes() {
+        let err = MatmulInvalidProblem::MismatchedContractionDim {
+            lhs_k: 64,
+            rhs_k: 32,
+        };
+
+        let message = format!("{err:?}");
+        assert!(message.contains("k=64"));
+        assert!(message.contains("k=32"));
+    }
+}
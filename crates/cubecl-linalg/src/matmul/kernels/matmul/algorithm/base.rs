@@ -1,14 +1,20 @@
 use cubecl_core::prelude::*;
+use cubecl_runtime::memory_management::HardwareProperties;
 
 use crate::matmul::components::stage::{self, StageSize};
 use crate::matmul::components::{batch, global, tile};
 use crate::matmul::components::{MatmulKernel, MatmulProblem};
 use crate::matmul::kernels::matmul::AdvancedConfig;
+use crate::matmul::kernels::{MatmulAvailabilityError, MatmulInvalidProblem};
 
 type LhsStageReader<GMM, EG, ES> =
     <<GMM as global::Matmul<EG, ES>>::Lhs as global::Loader<EG, ES>>::StageReader;
 type RhsStageReader<GMM, EG, ES> =
     <<GMM as global::Matmul<EG, ES>>::Rhs as global::Loader<EG, ES>>::StageReader;
+type BatchConfig<A, EG> = <<A as Algorithm<EG>>::BatchMatmul as MatmulKernel<
+    <A as Algorithm<EG>>::EG,
+    <A as Algorithm<EG>>::EG,
+>>::Config;
 
 /// Specifications for a matmul algorithm
 pub trait Algorithm<EG: Numeric> {
@@ -40,15 +46,36 @@ pub trait Algorithm<EG: Numeric> {
         cube_dim: &CubeDim,
         cube_count: &CubeCount,
         advanced_config: &AdvancedConfig,
-    ) -> <Self::BatchMatmul as MatmulKernel<Self::EG, Self::EG>>::Config {
+    ) -> Result<BatchConfig<Self, EG>, MatmulInvalidProblem> {
         let config = Self::BatchMatmul::make_config(problem, cube_dim, cube_count, advanced_config);
-        problem.check_config(&config);
-        config
+        problem.check_config(&config)?;
+        Ok(config)
     }
 
     fn check_availability<R: Runtime>(
         client: &ComputeClient<R::Server, R::Channel>,
-    ) -> Result<(), &str> {
-        Self::BatchMatmul::check_availability::<R>(client)
+    ) -> Result<(), MatmulAvailabilityError> {
+        Self::BatchMatmul::check_availability::<R>(client)?;
+        check_plane_size(Self::PLANE_DIM, client.properties().hardware_properties())
     }
 }
+
+/// Check that the device runs planes of the size the algorithm was tiled for.
+///
+/// When the plane size was measured, it is the only one the device uses, otherwise every size in
+/// the range reported by the device is possible.
+fn check_plane_size(
+    required: u32,
+    hardware: &HardwareProperties,
+) -> Result<(), MatmulAvailabilityError> {
+    let (min, max) = match hardware.plane_size_actual {
+        Some(actual) => (actual, actual),
+        None => (hardware.plane_size_min, hardware.plane_size_max),
+    };
+
+    if required < min || required > max {
+        return Err(MatmulAvailabilityError::IncompatiblePlaneSize { required, min, max });
+    }
+
+    Ok(())
+}
@@ -3,26 +3,34 @@ use std::marker::PhantomData;
 use cubecl_core::prelude::*;
 
 use crate::matmul::components::stage::{self, S4x4x2, StageSize};
+use crate::matmul::components::tile;
 use crate::matmul::components::tile::accelerated::Accelerated16x16x16;
-use crate::matmul::components::tile::Matmul;
-use crate::matmul::components::MatmulProblem;
 use crate::matmul::components::{batch, global};
+use crate::matmul::components::{MatmulKernel, MatmulProblem};
 
 use super::base;
 
-pub struct Cmma<EG: Numeric> {
+/// Matmul using the accelerated cmma instructions, computing tiles of the shape of `TMM`, with
+/// stages of `SS` tiles.
+pub struct Cmma<EG: Numeric, TMM = Accelerated16x16x16<half::f16, f32>, SS = S4x4x2> {
     pub _eg: PhantomData<EG>,
+    pub _tmm: PhantomData<TMM>,
+    pub _ss: PhantomData<SS>,
 }
 
-impl<EG: Numeric> base::Algorithm<EG> for Cmma<EG> {
+impl<EG: Numeric, TMM, SS> base::Algorithm<EG> for Cmma<EG, TMM, SS>
+where
+    TMM: tile::Matmul<half::f16, f32> + MatmulKernel<half::f16, f32>,
+    SS: StageSize,
+{
     const PLANE_DIM: u32 = 32;
     type EG = EG;
     type ES = half::f16;
     type EA = f32;
 
-    type TileMatmul = Accelerated16x16x16<Self::ES, Self::EA>;
+    type TileMatmul = TMM;
 
-    type StageSize = S4x4x2;
+    type StageSize = SS;
     type StageMatmul = stage::row_accumulate::Matmul<
         Self::ES,
         Self::EG,
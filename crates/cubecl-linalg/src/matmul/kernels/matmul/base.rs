@@ -1,3 +1,7 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+use cubecl_core::ir::Elem;
 use cubecl_core::prelude::*;
 
 use cubecl_core::{
@@ -7,28 +11,412 @@ use cubecl_core::{
 };
 
 use crate::matmul;
-use crate::matmul::components::{MatmulLaunch, MatmulProblem};
+use crate::matmul::components::batch::Config as _;
+use crate::matmul::components::stage::{S1x1x1, S2x2x2, S4x4x2};
+use crate::matmul::components::tile::accelerated::{
+    Accelerated16x16x16, Accelerated32x8x16, Accelerated8x32x16,
+};
+use crate::matmul::components::tile::{self, Matmul as _};
+use crate::matmul::components::{
+    Ident, MatmulKernel, MatmulLaunch, MatmulLineSizes, MatmulProblem, StageDims,
+};
+use crate::matmul::kernels::{
+    error::incompatible_batch_dim, MatmulAvailabilityError, MatmulInvalidProblem, MatmulLaunchError,
+};
 use crate::tensor::{into_contiguous, matrix_layout, MatrixLayout, TensorHandle};
 
 use super::config::AdvancedConfig;
+use super::fallback::{first_stage_level, remember_stage_level, ProblemClass, CMMA_STAGE_LEVELS};
 use super::{cmma::Cmma, plane_mma::PlaneMma, Algorithm};
 
 /// Launch a matrix multiplication kernel.
 ///
 /// Cmma will be used if available and enabled,
-/// otherwise it will fall back on a non-cmma implementation
+/// otherwise it will fall back on a non-cmma implementation.
+///
+/// When the device can't create the cmma kernel because it needs too many registers, it's
+/// launched again with smaller stages, and the stage size that worked is used for the next
+/// launches of the same element type on the device.
 pub fn launch_ref<R: Runtime, EG: Numeric>(
     client: &ComputeClient<R::Server, R::Channel>,
     lhs: TensorHandleRef<'_, R>,
     rhs: TensorHandleRef<'_, R>,
     out: TensorHandleRef<'_, R>,
     disable_cmma: bool,
-) {
-    if !disable_cmma && Cmma::<EG>::check_availability::<R>(client).is_ok() {
-        matmul_cmma_ref::<R, EG, Cmma<EG>>(client, lhs, rhs, out);
-    } else {
-        matmul_cmma_ref::<R, EG, PlaneMma<EG>>(client, lhs, rhs, out);
+) -> Result<(), MatmulLaunchError> {
+    launch_ref_with_config::<R, EG>(client, lhs, rhs, out, disable_cmma, Default::default())
+}
+
+/// Like [launch_ref], with an [advanced config](AdvancedConfig).
+pub fn launch_ref_with_config<R: Runtime, EG: Numeric>(
+    client: &ComputeClient<R::Server, R::Channel>,
+    lhs: TensorHandleRef<'_, R>,
+    rhs: TensorHandleRef<'_, R>,
+    out: TensorHandleRef<'_, R>,
+    disable_cmma: bool,
+    advanced_config: AdvancedConfig,
+) -> Result<(), MatmulLaunchError> {
+    check_contraction_dim(&lhs, &rhs)?;
+    check_batch_shapes(&lhs, &rhs)?;
+    check_rank(&out, Ident::Out)?;
+    check_out_layout(&out)?;
+
+    let rank = out.shape.len();
+    let (m, n) = (out.shape[rank - 2], out.shape[rank - 1]);
+    let fallback = advanced_config.cmma_shape_fallback;
+
+    match select_algorithm::<R, EG>(client, disable_cmma, fallback, (m, n))? {
+        SelectedAlgorithm::Cmma16x16x16 => {
+            launch_cmma::<R, EG, Cmma16x16x16>(client, lhs, rhs, out, advanced_config)
+        }
+        SelectedAlgorithm::Cmma32x8x16 => {
+            launch_cmma::<R, EG, Cmma32x8x16>(client, lhs, rhs, out, advanced_config)
+        }
+        SelectedAlgorithm::Cmma8x32x16 => {
+            launch_cmma::<R, EG, Cmma8x32x16>(client, lhs, rhs, out, advanced_config)
+        }
+        SelectedAlgorithm::PlaneMma => {
+            matmul_cmma_ref::<R, EG, PlaneMma<EG>>(client, lhs, rhs, out, advanced_config)
+        }
+    }
+}
+
+/// Launch cmma with the largest stage that the device can create, starting from the one that
+/// last worked for the same [class of problems](ProblemClass).
+///
+/// Some drivers only notice that a kernel needs more registers than the device has when creating
+/// it, in which case it isn't executed. Smaller stages keep fewer accumulators in every plane, so
+/// the kernel is launched again with the next one, until the smallest also fails.
+fn launch_cmma<R: Runtime, EG: Numeric, TMM>(
+    client: &ComputeClient<R::Server, R::Channel>,
+    lhs: TensorHandleRef<'_, R>,
+    rhs: TensorHandleRef<'_, R>,
+    out: TensorHandleRef<'_, R>,
+    advanced_config: AdvancedConfig,
+) -> Result<(), MatmulLaunchError>
+where
+    TMM: tile::Matmul<half::f16, f32> + MatmulKernel<half::f16, f32>,
+{
+    let class = ProblemClass::new::<R, EG>(client, (TMM::M, TMM::N, TMM::K));
+    let mut level = first_stage_level(&class);
+
+    loop {
+        let (lhs, rhs, out) = (reborrow(&lhs), reborrow(&rhs), reborrow(&out));
+        let config = advanced_config.clone();
+        match level {
+            0 => matmul_cmma_ref::<R, EG, Cmma<EG, TMM, S4x4x2>>(client, lhs, rhs, out, config),
+            1 => matmul_cmma_ref::<R, EG, Cmma<EG, TMM, S2x2x2>>(client, lhs, rhs, out, config),
+            _ => matmul_cmma_ref::<R, EG, Cmma<EG, TMM, S1x1x1>>(client, lhs, rhs, out, config),
+        }?;
+
+        match client.take_kernel_error() {
+            None => {
+                remember_stage_level(class, level);
+                return Ok(());
+            }
+            Some(_) if level + 1 < CMMA_STAGE_LEVELS => level += 1,
+            Some(err) => {
+                return Err(MatmulAvailabilityError::OutOfResources {
+                    reason: err.to_string(),
+                }
+                .into())
+            }
+        }
+    }
+}
+
+/// Copy the references of a tensor handle, to launch it again.
+fn reborrow<'a, R: Runtime>(tensor: &TensorHandleRef<'a, R>) -> TensorHandleRef<'a, R> {
+    TensorHandleRef {
+        handle: tensor.handle,
+        strides: tensor.strides,
+        shape: tensor.shape,
+        elem_size: tensor.elem_size,
+        runtime: PhantomData,
+    }
+}
+
+/// The operands of [matmul_transposed] to transpose over their last two dimensions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransposeSpec {
+    /// Multiply by the transpose of lhs.
+    pub lhs: bool,
+    /// Multiply by the transpose of rhs.
+    pub rhs: bool,
+}
+
+/// Launch a matrix multiplication of the operands transposed according to `transpose`, such as
+/// `lhs @ rhs^T` for attention scores.
+///
+/// The transpose is fused into the reads of the kernel: swapping the last two dimensions only
+/// swaps the shape and strides of the operand, which is then read in column-major order, as a
+/// transposed tensor would be by [launch_ref]. Every combination is fused for operands with
+/// contiguous rows or columns, whose batch dimensions aren't interleaved with them. Other
+/// operands, such as broadcast or highly permuted ones, are copied in a separate pass first.
+///
+/// The problem is validated on the transposed operands, so a mismatched contraction dimension is
+/// reported with the shapes after the transpose.
+pub fn matmul_transposed<R: Runtime, EG: Numeric>(
+    client: &ComputeClient<R::Server, R::Channel>,
+    lhs: TensorHandleRef<'_, R>,
+    rhs: TensorHandleRef<'_, R>,
+    out: TensorHandleRef<'_, R>,
+    transpose: TransposeSpec,
+    disable_cmma: bool,
+) -> Result<(), MatmulLaunchError> {
+    let lhs_shape = transpose_last_dims(lhs.shape, transpose.lhs);
+    let lhs_strides = transpose_last_dims(lhs.strides, transpose.lhs);
+    let rhs_shape = transpose_last_dims(rhs.shape, transpose.rhs);
+    let rhs_strides = transpose_last_dims(rhs.strides, transpose.rhs);
+
+    let lhs = TensorHandleRef {
+        shape: &lhs_shape,
+        strides: &lhs_strides,
+        ..lhs
+    };
+    let rhs = TensorHandleRef {
+        shape: &rhs_shape,
+        strides: &rhs_strides,
+        ..rhs
+    };
+
+    launch_ref::<R, EG>(client, lhs, rhs, out, disable_cmma)
+}
+
+/// Swap the last two dimensions of a shape or strides when `transpose` is set.
+fn transpose_last_dims(dims: &[usize], transpose: bool) -> Vec<usize> {
+    let mut dims = dims.to_vec();
+    let rank = dims.len();
+    if transpose && rank >= 2 {
+        dims.swap(rank - 2, rank - 1);
+    }
+    dims
+}
+
+type Cmma16x16x16 = Accelerated16x16x16<half::f16, f32>;
+type Cmma32x8x16 = Accelerated32x8x16<half::f16, f32>;
+type Cmma8x32x16 = Accelerated8x32x16<half::f16, f32>;
+
+/// An algorithm chosen by [select_algorithm], with the shape of its cmma instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelectedAlgorithm {
+    Cmma16x16x16,
+    Cmma32x8x16,
+    Cmma8x32x16,
+    PlaneMma,
+}
+
+/// The algorithms [launch_ref] selects from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatmulAlgorithmKind {
+    /// Uses the accelerated cmma instructions of the device.
+    Cmma,
+    /// Uses plane operations, when cmma isn't available or disabled.
+    PlaneMma,
+}
+
+impl Display for MatmulAlgorithmKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatmulAlgorithmKind::Cmma => f.write_str("cmma"),
+            MatmulAlgorithmKind::PlaneMma => f.write_str("plane_mma"),
+        }
+    }
+}
+
+/// How a matmul problem is solved on a device, as selected by [launch_ref].
+#[derive(Debug, Clone)]
+pub struct MatmulPlan {
+    /// The selected algorithm.
+    pub algorithm: MatmulAlgorithmKind,
+    /// The element type of the operands in global memory.
+    pub global_elem: Elem,
+    /// The element type of the operands given to the tile matmul.
+    pub stage_elem: Elem,
+    /// The element type the tile matmul accumulates in.
+    pub accumulator_elem: Elem,
+    /// The `(m, n, k)` shape of the tiles computed by the tile matmul.
+    pub tile_shape: (u32, u32, u32),
+    /// The tiles of every operand loaded in a stage.
+    pub stage_dims: StageDims,
+    /// The line sizes of the operands.
+    pub line_sizes: MatmulLineSizes,
+    /// The layout lhs is read with.
+    ///
+    /// Transposed operands are read in column-major order without a copy. Operands in any other
+    /// layout, such as permuted or broadcast matrices, are first made contiguous by an extra
+    /// kernel, so the plan of their problem is the one of a row-major operand.
+    pub lhs_layout: matmul::components::MatrixLayout,
+    /// The layout rhs is read with, see [lhs_layout](Self::lhs_layout).
+    pub rhs_layout: matmul::components::MatrixLayout,
+    /// The number of units of every cube.
+    pub cube_dim: CubeDim,
+    /// The number of cubes launched in every dimension.
+    pub cube_count: (u32, u32, u32),
+}
+
+/// A one-line summary of the kernel, such as `f16->f32 cmma 16x16x16`, with the element types
+/// of the tile matmul. Log it after the [problem](MatmulProblem) for a full description.
+impl Display for MatmulPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (m, n, k) = self.tile_shape;
+        write!(
+            f,
+            "{}->{} {} {m}x{n}x{k}",
+            self.stage_elem, self.accumulator_elem, self.algorithm
+        )
+    }
+}
+
+/// Describe the kernel [launch_ref] would run for the given problem on this device, without
+/// dispatching it.
+///
+/// The algorithm is selected with the same availability checks as a launch, and the problem is
+/// validated against its config, so the plan reflects the capabilities of the device. Cmma is
+/// planned with the stage size a launch would start from.
+///
+/// Column-major operands are described by the layouts of the problem. Operands that are neither
+/// row-major nor column-major are copied to a contiguous tensor before the launch, which the plan
+/// doesn't include.
+pub fn matmul_plan<R: Runtime, EG: Numeric>(
+    client: &ComputeClient<R::Server, R::Channel>,
+    problem: &MatmulProblem,
+    advanced_config: &AdvancedConfig,
+    disable_cmma: bool,
+) -> Result<MatmulPlan, MatmulLaunchError> {
+    let fallback = advanced_config.cmma_shape_fallback;
+    let selected =
+        select_algorithm::<R, EG>(client, disable_cmma, fallback, (problem.m, problem.n))?;
+
+    match selected {
+        SelectedAlgorithm::Cmma16x16x16 => {
+            plan_cmma::<R, EG, Cmma16x16x16>(client, problem, advanced_config)
+        }
+        SelectedAlgorithm::Cmma32x8x16 => {
+            plan_cmma::<R, EG, Cmma32x8x16>(client, problem, advanced_config)
+        }
+        SelectedAlgorithm::Cmma8x32x16 => {
+            plan_cmma::<R, EG, Cmma8x32x16>(client, problem, advanced_config)
+        }
+        SelectedAlgorithm::PlaneMma => {
+            make_plan::<EG, PlaneMma<EG>>(problem, advanced_config, MatmulAlgorithmKind::PlaneMma)
+        }
+    }
+}
+
+/// Plan cmma with the stage [launch_cmma] would start from.
+fn plan_cmma<R: Runtime, EG: Numeric, TMM>(
+    client: &ComputeClient<R::Server, R::Channel>,
+    problem: &MatmulProblem,
+    advanced_config: &AdvancedConfig,
+) -> Result<MatmulPlan, MatmulLaunchError>
+where
+    TMM: tile::Matmul<half::f16, f32> + MatmulKernel<half::f16, f32>,
+{
+    let class = ProblemClass::new::<R, EG>(client, (TMM::M, TMM::N, TMM::K));
+    let cmma = MatmulAlgorithmKind::Cmma;
+
+    match first_stage_level(&class) {
+        0 => make_plan::<EG, Cmma<EG, TMM, S4x4x2>>(problem, advanced_config, cmma),
+        1 => make_plan::<EG, Cmma<EG, TMM, S2x2x2>>(problem, advanced_config, cmma),
+        _ => make_plan::<EG, Cmma<EG, TMM, S1x1x1>>(problem, advanced_config, cmma),
+    }
+}
+
+/// Select cmma when it's available and not disabled, falling back on plane operations.
+///
+/// With `cmma_shape_fallback`, every supported cmma shape is tried before plane operations,
+/// starting with the shapes whose tiles divide `(m, n)`.
+fn select_algorithm<R: Runtime, EG: Numeric>(
+    client: &ComputeClient<R::Server, R::Channel>,
+    disable_cmma: bool,
+    cmma_shape_fallback: bool,
+    (m, n): (usize, usize),
+) -> Result<SelectedAlgorithm, MatmulAvailabilityError> {
+    if !disable_cmma {
+        if Cmma::<EG>::check_availability::<R>(client).is_ok() {
+            return Ok(SelectedAlgorithm::Cmma16x16x16);
+        }
+
+        if cmma_shape_fallback {
+            let candidates = [
+                (
+                    SelectedAlgorithm::Cmma32x8x16,
+                    (Cmma32x8x16::M, Cmma32x8x16::N),
+                    Cmma::<EG, Cmma32x8x16>::check_availability::<R>(client).is_ok(),
+                ),
+                (
+                    SelectedAlgorithm::Cmma8x32x16,
+                    (Cmma8x32x16::M, Cmma8x32x16::N),
+                    Cmma::<EG, Cmma8x32x16>::check_availability::<R>(client).is_ok(),
+                ),
+            ];
+
+            if let Some(selected) = select_fallback_shape(candidates, (m, n)) {
+                return Ok(selected);
+            }
+        }
     }
+
+    PlaneMma::<EG>::check_availability::<R>(client)?;
+    Ok(SelectedAlgorithm::PlaneMma)
+}
+
+/// Select the first available cmma shape among `(algorithm, (tile_m, tile_n), available)`
+/// candidates, preferring the shapes whose tiles divide `(m, n)`.
+fn select_fallback_shape<const N: usize>(
+    mut candidates: [(SelectedAlgorithm, (u32, u32), bool); N],
+    (m, n): (usize, usize),
+) -> Option<SelectedAlgorithm> {
+    // Stable sort, so the given order is kept among shapes that tile the problem.
+    candidates.sort_by_key(|(_, (tile_m, tile_n), _)| {
+        m % *tile_m as usize != 0 || n % *tile_n as usize != 0
+    });
+
+    candidates
+        .iter()
+        .find(|(_, _, available)| *available)
+        .map(|(selected, _, _)| *selected)
+}
+
+fn make_plan<EG: Numeric, D: Algorithm<EG>>(
+    problem: &MatmulProblem,
+    advanced_config: &AdvancedConfig,
+    algorithm: MatmulAlgorithmKind,
+) -> Result<MatmulPlan, MatmulLaunchError> {
+    let cube_dim = D::cube_dim();
+    let cube_count = match D::cube_count(problem) {
+        CubeCount::Static(x, y, z) => (x, y, z),
+        CubeCount::Dynamic(_) => unreachable!("Matmul algorithms use a static cube count"),
+    };
+    let config = D::make_config(
+        problem,
+        &cube_dim,
+        &CubeCount::Static(cube_count.0, cube_count.1, cube_count.2),
+        advanced_config,
+    )?;
+
+    Ok(MatmulPlan {
+        algorithm,
+        global_elem: D::EG::as_elem(),
+        stage_elem: D::ES::as_elem(),
+        accumulator_elem: D::EA::as_elem(),
+        tile_shape: (D::TileMatmul::M, D::TileMatmul::N, D::TileMatmul::K),
+        stage_dims: StageDims {
+            lhs: config.stage_dim(Ident::Lhs),
+            rhs: config.stage_dim(Ident::Rhs),
+            out: config.stage_dim(Ident::Out),
+        },
+        line_sizes: MatmulLineSizes {
+            lhs: problem.lhs_line_size,
+            rhs: problem.rhs_line_size,
+            out: problem.out_line_size,
+        },
+        lhs_layout: problem.lhs_layout,
+        rhs_layout: problem.rhs_layout,
+        cube_dim,
+        cube_count,
+    })
 }
 
 /// Launch a matrix multiplication kernel.
@@ -41,15 +429,123 @@ pub fn launch<R: Runtime, EG: Numeric>(
     rhs: TensorHandle<R, EG>,
     out: TensorHandle<R, EG>,
     disable_cmma: bool,
-) -> TensorHandle<R, EG> {
+) -> Result<TensorHandle<R, EG>, MatmulLaunchError> {
     launch_ref::<R, EG>(
         client,
         lhs.as_ref(),
         rhs.as_ref(),
         out.as_ref(),
         disable_cmma,
-    );
-    out
+    )?;
+    Ok(out)
+}
+
+/// Validate that the given algorithm can solve the problem on this device, without dispatching
+/// any kernel.
+///
+/// Runs the same checks as a launch: the features required by the algorithm, such as element
+/// types and CMMA shapes, and the limits of its config on the problem, such as line sizes and the
+/// maximum m, n and batch sizes.
+pub fn matmul_check<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
+    client: &ComputeClient<R::Server, R::Channel>,
+    problem: &MatmulProblem,
+    advanced_config: &AdvancedConfig,
+) -> Result<(), MatmulLaunchError> {
+    D::check_availability::<R>(client)?;
+
+    let cube_dim = D::cube_dim();
+    let cube_count = D::cube_count(problem);
+    D::make_config(problem, &cube_dim, &cube_count, advanced_config)?;
+
+    Ok(())
+}
+
+/// Checks that a tensor has the two matrix dimensions, before they are indexed.
+fn check_rank<R: Runtime>(
+    tensor: &TensorHandleRef<'_, R>,
+    ident: Ident,
+) -> Result<(), MatmulInvalidProblem> {
+    let rank = tensor.shape.len();
+
+    if rank < 2 {
+        return Err(MatmulInvalidProblem::RankTooLow {
+            ident,
+            rank: rank as u32,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that the k dimension of lhs matches the one of rhs.
+fn check_contraction_dim<R: Runtime>(
+    lhs: &TensorHandleRef<'_, R>,
+    rhs: &TensorHandleRef<'_, R>,
+) -> Result<(), MatmulInvalidProblem> {
+    check_rank(lhs, Ident::Lhs)?;
+    check_rank(rhs, Ident::Rhs)?;
+
+    let lhs_k = lhs.shape[lhs.shape.len() - 1] as u32;
+    let rhs_k = rhs.shape[rhs.shape.len() - 2] as u32;
+
+    if lhs_k != rhs_k {
+        return Err(MatmulInvalidProblem::MismatchedContractionDim { lhs_k, rhs_k });
+    }
+
+    Ok(())
+}
+
+/// Checks that the batch dimensions of lhs and rhs can be broadcast together.
+fn check_batch_shapes<R: Runtime>(
+    lhs: &TensorHandleRef<'_, R>,
+    rhs: &TensorHandleRef<'_, R>,
+) -> Result<(), MatmulInvalidProblem> {
+    check_rank(lhs, Ident::Lhs)?;
+    check_rank(rhs, Ident::Rhs)?;
+
+    let batch_shape = |tensor: &TensorHandleRef<'_, R>| {
+        tensor.shape[..tensor.shape.len() - 2]
+            .iter()
+            .map(|dim| *dim as u32)
+            .collect::<Vec<_>>()
+    };
+    let lhs = batch_shape(lhs);
+    let rhs = batch_shape(rhs);
+
+    if incompatible_batch_dim(&lhs, &rhs).is_some() {
+        return Err(MatmulInvalidProblem::IncompatibleBatchShapes { lhs, rhs });
+    }
+
+    Ok(())
+}
+
+/// Checks that out is row-major with contiguous rows, which is the only layout the kernels can
+/// write.
+fn check_out_layout<R: Runtime>(out: &TensorHandleRef<'_, R>) -> Result<(), MatmulInvalidProblem> {
+    let rank = out.strides.len();
+    let writable = out.strides[rank - 1] == 1
+        && matches!(
+            matrix_layout(out.strides),
+            MatrixLayout::Contiguous
+                | MatrixLayout::MildlyPermuted {
+                    transposed: false,
+                    ..
+                }
+        );
+
+    if !writable {
+        let mut expected = vec![1; rank];
+        for i in (0..rank - 1).rev() {
+            expected[i] = expected[i + 1] * out.shape[i + 1] as u32;
+        }
+
+        return Err(MatmulInvalidProblem::UnsupportedOutLayout {
+            strides: out.strides.iter().map(|stride| *stride as u32).collect(),
+            expected,
+        });
+    }
+
+    Ok(())
 }
 
 fn matmul_cmma_ref<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
@@ -57,7 +553,8 @@ fn matmul_cmma_ref<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
     lhs: TensorHandleRef<'_, R>,
     rhs: TensorHandleRef<'_, R>,
     out: TensorHandleRef<'_, R>,
-) {
+    advanced_config: AdvancedConfig,
+) -> Result<(), MatmulLaunchError> {
     let check_layout = |tensor: &TensorHandleRef<'_, R>| match matrix_layout(tensor.strides) {
         MatrixLayout::Contiguous => (false, false),
         MatrixLayout::MildlyPermuted {
@@ -77,6 +574,7 @@ fn matmul_cmma_ref<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
             rhs,
             out,
             (lhs_transposed, rhs_transposed),
+            advanced_config,
         ),
         (false, true) => matmul_cmma_ref_no_check::<R, EG, D>(
             client,
@@ -84,6 +582,7 @@ fn matmul_cmma_ref<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
             into_contiguous::<R, EG>(client, rhs).as_ref(),
             out,
             (lhs_transposed, rhs_transposed),
+            advanced_config,
         ),
         (true, false) => matmul_cmma_ref_no_check::<R, EG, D>(
             client,
@@ -91,6 +590,7 @@ fn matmul_cmma_ref<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
             rhs,
             out,
             (lhs_transposed, rhs_transposed),
+            advanced_config,
         ),
         (true, true) => matmul_cmma_ref_no_check::<R, EG, D>(
             client,
@@ -98,6 +598,7 @@ fn matmul_cmma_ref<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
             into_contiguous::<R, EG>(client, rhs).as_ref(),
             out,
             (lhs_transposed, rhs_transposed),
+            advanced_config,
         ),
     }
 }
@@ -108,7 +609,8 @@ fn matmul_cmma_ref_no_check<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
     rhs: TensorHandleRef<'_, R>,
     out: TensorHandleRef<'_, R>,
     transposed: (bool, bool),
-) {
+    advanced_config: AdvancedConfig,
+) -> Result<(), MatmulLaunchError> {
     let rank = lhs.strides.len();
 
     let m = lhs.shape[rank - 2] as u32;
@@ -116,10 +618,18 @@ fn matmul_cmma_ref_no_check<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
     let n = rhs.shape[rank - 1] as u32;
 
     let available_vectorizations = R::supported_line_sizes();
-    let lhs_line_size =
-        tensor_line_size(available_vectorizations, lhs.shape, lhs.strides, rank - 1);
-    let rhs_line_size =
-        tensor_line_size(available_vectorizations, rhs.shape, rhs.strides, rank - 1);
+    let lhs_line_size = operand_line_size(
+        available_vectorizations,
+        lhs.shape,
+        lhs.strides,
+        transposed.0,
+    );
+    let rhs_line_size = operand_line_size(
+        available_vectorizations,
+        rhs.shape,
+        rhs.strides,
+        transposed.1,
+    );
     let out_line_size =
         tensor_line_size(available_vectorizations, out.shape, out.strides, rank - 1);
 
@@ -144,8 +654,6 @@ fn matmul_cmma_ref_no_check<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
     let cube_dim = D::cube_dim();
     let cube_count = D::cube_count(&problem);
 
-    let advanced_config = Default::default();
-
     launch_matmul::<R, EG, D>(
         client,
         lhs,
@@ -155,7 +663,35 @@ fn matmul_cmma_ref_no_check<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
         cube_dim,
         cube_count,
         advanced_config,
-    );
+    )
+}
+
+/// The line size used to read an operand along its contiguous dimension.
+///
+/// Row-major operands are read along their last dimension. Transposed operands are read in
+/// column-major order, along their second to last dimension, so they don't need to be copied.
+fn operand_line_size(supported: &[u8], shape: &[usize], strides: &[usize], transposed: bool) -> u8 {
+    let rank = shape.len();
+    if !transposed {
+        return tensor_line_size(supported, shape, strides, rank - 1);
+    }
+    if strides[rank - 2] != 1 {
+        return 1;
+    }
+
+    supported
+        .iter()
+        .map(|line_size| *line_size as usize)
+        .filter(|line_size| {
+            let divides = |value: usize| value.next_multiple_of(*line_size) == value;
+            divides(shape[rank - 2])
+                && strides
+                    .iter()
+                    .enumerate()
+                    .all(|(axis, stride)| axis == rank - 2 || divides(*stride))
+        })
+        .max()
+        .unwrap_or(1) as u8
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -168,8 +704,8 @@ fn launch_matmul<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
     cube_dim: CubeDim,
     cube_count: CubeCount,
     advanced_config: AdvancedConfig,
-) {
-    let config = D::make_config(&problem, &cube_dim, &cube_count, &advanced_config);
+) -> Result<(), MatmulLaunchError> {
+    let config = D::make_config(&problem, &cube_dim, &cube_count, &advanced_config)?;
 
     unsafe {
         D::BatchMatmul::launch_unchecked::<R>(
@@ -197,4 +733,76 @@ fn launch_matmul<R: Runtime, EG: Numeric, D: Algorithm<EG>>(
             config,
         );
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transposed_operands_are_vectorized_along_their_contiguous_dimension() {
+        // A [batch, m, k] operand stored column-major: m is contiguous.
+        let shape = [2, 64, 32];
+        let strides = [64 * 32, 1, 64];
+        assert_eq!(operand_line_size(&[1, 2, 4], &shape, &strides, true), 4);
+
+        // Reading it as row-major would not be vectorized.
+        assert_eq!(operand_line_size(&[1, 2, 4], &shape, &strides, false), 1);
+
+        // The row stride limits the line size of every column.
+        let strides = [6 * 32, 1, 6];
+        assert_eq!(
+            operand_line_size(&[1, 2, 4], &[2, 6, 32], &strides, true),
+            2
+        );
+    }
+
+    #[test]
+    fn transposing_a_contiguous_operand_is_fused() {
+        // A contiguous [batch, k, m] operand, used as its [batch, m, k] transpose.
+        let shape = transpose_last_dims(&[2, 32, 64], true);
+        let strides = transpose_last_dims(&[32 * 64, 64, 1], true);
+
+        assert_eq!(shape, vec![2, 64, 32]);
+        assert!(matches!(
+            matrix_layout(&strides),
+            MatrixLayout::MildlyPermuted {
+                transposed: true,
+                batch_swap: false
+            }
+        ));
+        assert_eq!(operand_line_size(&[1, 2, 4], &shape, &strides, true), 4);
+    }
+
+    #[test]
+    fn cmma_shape_fallback_prefers_shapes_that_tile_the_problem() {
+        let candidates = |available_32x8, available_8x32| {
+            [
+                (SelectedAlgorithm::Cmma32x8x16, (32, 8), available_32x8),
+                (SelectedAlgorithm::Cmma8x32x16, (8, 32), available_8x32),
+            ]
+        };
+
+        // Both shapes tile the problem, the first one is kept.
+        assert_eq!(
+            select_fallback_shape(candidates(true, true), (64, 64)),
+            Some(SelectedAlgorithm::Cmma32x8x16)
+        );
+        // Only 8x32 tiles m=8.
+        assert_eq!(
+            select_fallback_shape(candidates(true, true), (8, 64)),
+            Some(SelectedAlgorithm::Cmma8x32x16)
+        );
+        // A shape that doesn't tile the problem is still used when it's the only one available.
+        assert_eq!(
+            select_fallback_shape(candidates(true, false), (8, 64)),
+            Some(SelectedAlgorithm::Cmma32x8x16)
+        );
+        assert_eq!(
+            select_fallback_shape(candidates(false, false), (64, 64)),
+            None
+        );
+    }
 }
@@ -3,6 +3,7 @@ use crate::matmul::components::MatrixLayout;
 use crate::matmul::components::StageDim;
 
 /// Configs that may impact performance
+#[derive(Clone)]
 pub struct AdvancedConfig {
     /// Order in which tiles should be in shared memory
     pub tiling_order: stage::TilingOrderConfig,
@@ -16,6 +17,11 @@ pub struct AdvancedConfig {
     /// transpose will be done at loading from global memory to stage,
     /// and stage will not be vectorized.
     pub enforced_tile_layout: (Option<MatrixLayout>, Option<MatrixLayout>),
+    /// When the default cmma shape isn't supported by the device, use another supported shape
+    /// instead of falling back on plane operations.
+    ///
+    /// Shapes whose tiles divide m and n are preferred.
+    pub cmma_shape_fallback: bool,
 }
 
 impl Default for AdvancedConfig {
@@ -23,6 +29,7 @@ impl Default for AdvancedConfig {
         Self {
             tiling_order: stage::TilingOrderConfig::XMajor,
             enforced_tile_layout: (None, None),
+            cmma_shape_fallback: false,
         }
     }
 }
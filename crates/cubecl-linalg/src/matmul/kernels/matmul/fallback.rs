@@ -0,0 +1,76 @@
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use cubecl_core::{client::ComputeClient, prelude::Numeric, Runtime};
+
+/// The number of stage sizes cmma is launched with before giving up, from the largest to the
+/// smallest.
+pub(crate) const CMMA_STAGE_LEVELS: usize = 3;
+
+/// The stage level that last worked for every class of problems.
+static WORKING_STAGE_LEVELS: Mutex<BTreeMap<ProblemClass, usize>> = Mutex::new(BTreeMap::new());
+
+/// Problems whose kernels need the same resources on a device, so a stage size that doesn't fit
+/// for one of them doesn't fit for the others either.
+///
+/// The shape of a problem isn't part of its class, since it doesn't change the registers used by
+/// every plane. Devices of the same model with the same driver share their class.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct ProblemClass {
+    runtime: TypeId,
+    /// The vendor ID, device ID and driver of the adapter, when the runtime exposes them.
+    adapter: Option<(u32, u32, String)>,
+    global_elem: TypeId,
+    tile_shape: (u32, u32, u32),
+}
+
+impl ProblemClass {
+    pub(crate) fn new<R: Runtime, EG: Numeric>(
+        client: &ComputeClient<R::Server, R::Channel>,
+        tile_shape: (u32, u32, u32),
+    ) -> Self {
+        Self {
+            runtime: TypeId::of::<R>(),
+            adapter: client
+                .adapter_info()
+                .map(|info| (info.vendor, info.device, info.driver.clone())),
+            global_elem: TypeId::of::<EG>(),
+            tile_shape,
+        }
+    }
+}
+
+/// The stage level to start from for a class of problems, skipping the ones that already failed.
+pub(crate) fn first_stage_level(class: &ProblemClass) -> usize {
+    let levels = WORKING_STAGE_LEVELS.lock().unwrap();
+    levels.get(class).copied().unwrap_or(0)
+}
+
+/// Remember that a stage level works for a class of problems.
+pub(crate) fn remember_stage_level(class: ProblemClass, level: usize) {
+    WORKING_STAGE_LEVELS.lock().unwrap().insert(class, level);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn working_stage_level_is_remembered_per_class() {
+        let class = |global_elem| ProblemClass {
+            runtime: TypeId::of::<()>(),
+            adapter: Some((0x8086, 0x56a0, "test driver".to_string())),
+            global_elem,
+            tile_shape: (16, 16, 16),
+        };
+        let f32_class = class(TypeId::of::<f32>());
+        let f16_class = class(TypeId::of::<half::f16>());
+
+        assert_eq!(first_stage_level(&f32_class), 0);
+        remember_stage_level(f32_class.clone(), 2);
+
+        assert_eq!(first_stage_level(&f32_class), 2);
+        assert_eq!(first_stage_level(&f16_class), 0);
+    }
+}
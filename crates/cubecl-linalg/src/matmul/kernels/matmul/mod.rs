@@ -1,8 +1,12 @@
 mod base;
 mod config;
+mod fallback;
 
 mod algorithm;
 
 pub use algorithm::{cmma, plane_mma, Algorithm};
-pub use base::{launch, launch_ref};
+pub use base::{
+    launch, launch_ref, launch_ref_with_config, matmul_check, matmul_plan, matmul_transposed,
+    MatmulAlgorithmKind, MatmulPlan, TransposeSpec,
+};
 pub use config::{create_stage_dim, AdvancedConfig};
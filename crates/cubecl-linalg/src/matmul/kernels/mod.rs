@@ -4,3 +4,7 @@ pub mod cmma_old;
 pub mod matmul;
 /// Non-cooperative Matmul
 pub mod tiling2d;
+
+mod error;
+
+pub use error::*;
@@ -3,7 +3,7 @@ use std::fmt::Display;
 use cubecl_core::prelude::*;
 use cubecl_core::server::Handle;
 use cubecl_core::CubeElement;
-use cubecl_core::Feature;
+use cubecl_core::{CmmaScope, Feature};
 
 use crate::matmul::components::Ident;
 use crate::matmul::components::MatmulLaunch;
@@ -12,6 +12,7 @@ use crate::matmul::components::MatrixLayout;
 use crate::matmul::kernels::matmul;
 use crate::matmul::kernels::matmul::AdvancedConfig;
 use crate::matmul::kernels::matmul::Algorithm;
+use crate::matmul::kernels::MatmulLaunchError;
 use crate::matmul::tests::test_utils::CastInto;
 use crate::tensor::TensorHandle;
 
@@ -40,9 +41,11 @@ pub fn test_matmul_algorithm<A, EG, ES, R>(
 {
     let client: ComputeClient<<R as Runtime>::Server, <R as Runtime>::Channel> = R::client(device);
 
-    if A::check_availability::<R>(&client).is_err() {
+    match matmul::matmul_check::<R, EG, A>(&client, &problem, &advanced_config) {
+        Ok(()) => {}
         // Can't execute the test.
-        return;
+        Err(MatmulLaunchError::Unavailable(_)) => return,
+        Err(err) => panic!("{err:?}"),
     }
 
     let lhs = tensor_raw_parts::<EG, R>(&client, &problem, Ident::Lhs);
@@ -51,7 +54,7 @@ pub fn test_matmul_algorithm<A, EG, ES, R>(
 
     let cube_dim = A::cube_dim();
     let cube_count = A::cube_count(&problem);
-    let config = A::make_config(&problem, &cube_dim, &cube_count, &advanced_config);
+    let config = A::make_config(&problem, &cube_dim, &cube_count, &advanced_config).unwrap();
 
     unsafe {
         A::BatchMatmul::launch_unchecked(
@@ -118,7 +121,8 @@ pub fn test_matmul_launch<EG: Float + CubeElement + Display + CastInto<EG>, R: R
         TensorHandle::new(rhs.shape, rhs.strides, rhs.handle),
         TensorHandle::new(out.shape, out.strides, out.handle),
         disable_cmma,
-    );
+    )
+    .unwrap();
 
     assert_result::<EG, EG, R>(
         &lhs.original_data.unwrap(),
@@ -217,9 +221,11 @@ fn assert_result<
                 a: ES::as_elem(),
                 b: ES::as_elem(),
                 c: EG::as_elem(),
+                d: EG::as_elem(),
                 m: 16,
                 k: 16,
                 n: 16,
+                scope: CmmaScope::Plane,
             });
 
             // Need to compensate for the temporary conversion to f16/tf32
@@ -138,7 +138,21 @@ where
         Self::new(shape, strides, handle)
     }
 
+    /// Create a contiguous tensor filled with zeros, using the native clear of the backend.
     pub fn zeros(client: &ComputeClient<R::Server, R::Channel>, shape: Vec<usize>) -> Self {
+        let output = Self::empty(client, shape);
+        // Zero is represented by zeroed bytes for every numeric type.
+        client.memset(&output.handle, 0);
+
+        output
+    }
+
+    /// Create a contiguous tensor with every element set to `value`.
+    pub fn filled(
+        client: &ComputeClient<R::Server, R::Channel>,
+        shape: Vec<usize>,
+        value: E,
+    ) -> Self {
         let num_elements: usize = shape.iter().product();
         let rank = shape.len();
         let output = Self::empty(client, shape);
@@ -155,11 +169,12 @@ where
             calculate_cube_count_elemwise(num_elements / vectorization_factor as usize, cube_dim);
 
         unsafe {
-            init::zeros_array::launch_unchecked::<E, R>(
+            init::fill_array::launch_unchecked::<E, R>(
                 client,
                 cube_count,
                 cube_dim,
                 ArrayArg::from_raw_parts::<E>(&output.handle, num_elements, vectorization_factor),
+                ScalarArg::new(value),
             )
         };
 
@@ -172,9 +187,9 @@ pub(crate) mod init {
     use cubecl_core as cubecl;
 
     #[cube(launch_unchecked)]
-    pub fn zeros_array<C: Numeric>(output: &mut Array<C>) {
+    pub fn fill_array<C: Numeric>(output: &mut Array<Line<C>>, value: C) {
         if ABSOLUTE_POS < output.len() {
-            output[ABSOLUTE_POS] = C::from_int(0);
+            output[ABSOLUTE_POS] = Line::empty(output.line_size()).fill(value);
         }
     }
 }
@@ -4,7 +4,9 @@ use quote::{format_ident, quote, ToTokens};
 use syn::Ident;
 
 use crate::{
-    parse::kernel::{KernelBody, KernelFn, KernelParam, KernelReturns, KernelSignature, Launch},
+    parse::kernel::{
+        ExecutionModeArg, KernelBody, KernelFn, KernelParam, KernelReturns, KernelSignature, Launch,
+    },
     paths::{core_type, prelude_path, prelude_type},
 };
 
@@ -213,6 +215,21 @@ impl Launch {
         }
     }
 
+    fn execution_mode(&self) -> TokenStream {
+        let execution_mode = core_type("ExecutionMode");
+        let mode = match self.args.execution_mode {
+            Some(ExecutionModeArg::Checked) => quote![#execution_mode::Checked],
+            Some(ExecutionModeArg::Unchecked) => quote![#execution_mode::Unchecked],
+            None => return TokenStream::new(),
+        };
+
+        quote! {
+            fn execution_mode(&self) -> Option<#execution_mode> {
+                Some(#mode)
+            }
+        }
+    }
+
     pub fn kernel_definition(&self) -> TokenStream {
         if self.args.is_launch() {
             let kernel = core_type("Kernel");
@@ -236,6 +253,7 @@ impl Launch {
                 .map(|_| quote![__ty: ::core::marker::PhantomData]);
             let (compilation_args, args) = self.compilation_args_def();
             let info = param_names.clone().into_iter().chain(args.clone());
+            let execution_mode = self.execution_mode();
 
             quote! {
                 #[doc = #kernel_doc]
@@ -268,6 +286,8 @@ impl Launch {
                         let cube_dim = self.settings.cube_dim.clone();
                         #kernel_id::new::<Self>().info((cube_dim, #(self.#info.clone()),* ))
                     }
+
+                    #execution_mode
                 }
             }
         } else {
@@ -30,6 +30,8 @@ mod statement;
 /// * `launch_unchecked` - generates a launch function without checks
 /// * `debug` - panics after generation to print the output to console
 /// * `create_dummy_kernel` - Generates a function to create a kernel without launching it. Used for testing.
+/// * `execution_mode = "unchecked"` - always compiles the launched kernel without bound checks,
+///   even for checked launches. Only use it for kernels that can't access out of bounds.
 ///
 /// # Example
 ///
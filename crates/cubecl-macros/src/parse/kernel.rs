@@ -16,6 +16,14 @@ pub(crate) struct KernelArgs {
     pub debug: Flag,
     pub create_dummy_kernel: Flag,
     pub local_allocator: Option<Expr>,
+    pub execution_mode: Option<ExecutionModeArg>,
+}
+
+/// The execution mode a launched kernel is always compiled with.
+#[derive(Clone, Copy, FromMeta)]
+pub(crate) enum ExecutionModeArg {
+    Checked,
+    Unchecked,
 }
 
 pub fn from_tokens<T: FromMeta>(tokens: TokenStream) -> syn::Result<T> {
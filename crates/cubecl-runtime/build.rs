@@ -5,5 +5,6 @@ fn main() {
     cfg_aliases! {
         autotune_persistent_cache: { all(feature = "std", any(target_os = "windows", target_os = "linux", target_os = "macos")) },
         exclusive_memory_only: { any(feature = "exclusive-memory-only", target_family = "wasm") },
+        allocation_throttle: { all(feature = "std", not(target_family = "wasm")) },
     }
 }
@@ -2,7 +2,10 @@ use core::future::Future;
 use cubecl_common::benchmark::TimestampsResult;
 
 use crate::{
-    server::{Binding, ComputeServer, CubeCount, Handle},
+    memory_management::{
+        AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError,
+    },
+    server::{Binding, ComputeServer, CubeCount, Handle, KernelTimings, ServerError},
     storage::BindingResource,
     ExecutionMode,
 };
@@ -11,17 +14,53 @@ use alloc::vec::Vec;
 /// The ComputeChannel trait links the ComputeClient to the ComputeServer
 /// while ensuring thread-safety
 pub trait ComputeChannel<Server: ComputeServer>: Clone + core::fmt::Debug + Send + Sync {
-    /// Given a binding, returns owned resource as bytes
-    fn read(&self, binding: Binding) -> impl Future<Output = Vec<u8>> + Send;
+    /// Given a binding, returns owned resource as bytes, or [ServerError::DeviceLost] without
+    /// reading when the device was lost.
+    ///
+    /// The read is sent to the server when called, not when the future is first polled, so it's
+    /// ordered with the other operations of the channel.
+    fn read(&self, binding: Binding) -> impl Future<Output = Result<Vec<u8>, ServerError>> + Send;
 
     /// Given a resource handle, return the storage resource.
     fn get_resource(&self, binding: Binding) -> BindingResource<Server>;
 
     /// Given a resource as bytes, stores it and returns the resource handle
-    fn create(&self, data: &[u8]) -> Handle;
+    fn create(&self, data: &[u8]) -> Result<Handle, MemoryError>;
 
     /// Reserves `size` bytes in the storage, and returns a handle over them
-    fn empty(&self, size: usize) -> Handle;
+    fn empty(&self, size: usize) -> Result<Handle, MemoryError>;
+
+    /// Reserves `size` bytes in the storage, waiting for the server without blocking the thread.
+    fn empty_async(&self, size: usize) -> impl Future<Output = Result<Handle, MemoryError>> + Send;
+
+    /// Set every byte of the given binding to `value`.
+    fn memset(&self, binding: Binding, value: u8);
+
+    /// Copy every byte of `src` to `dst`, which has the same size.
+    fn copy(&self, src: Binding, dst: Binding);
+
+    /// Eagerly allocates memory so that a later allocation of `size` bytes doesn't have to.
+    fn preallocate(&self, size: u64) -> Result<(), MemoryError>;
+
+    /// Limit the number of bytes kept reserved by the memory pools, or remove the limit with `None`.
+    fn set_residency_budget(&self, budget: Option<u64>);
+
+    /// Let allocations ignore the growth limit of a throttled memory configuration while
+    /// `bypass` is true.
+    fn bypass_allocation_throttle(&self, bypass: bool);
+
+    /// Replace the memory pools with the ones of a new configuration.
+    fn reconfigure_memory(
+        &self,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError>;
+
+    /// Start recording the reservations of the memory management in a new trace.
+    fn start_allocation_trace(&self);
+
+    /// Stop recording the reservations and return the trace, if one was started.
+    fn stop_allocation_trace(&self) -> Option<AllocationTrace>;
 
     /// Executes the `kernel` over the given `bindings`.
     ///
@@ -36,11 +75,15 @@ pub trait ComputeChannel<Server: ComputeServer>: Clone + core::fmt::Debug + Send
         mode: ExecutionMode,
     );
 
+    /// Compile the given kernels ahead of time, without executing them.
+    fn precompile(&self, kernels: Vec<Server::Kernel>, mode: ExecutionMode);
+
     /// Flush outstanding work of the server.
     fn flush(&self);
 
-    /// Wait for the completion of every task in the server.
-    fn sync(&self) -> impl Future<Output = ()> + Send;
+    /// Wait for the completion of every task in the server, and return
+    /// [ServerError::DeviceLost] if the device was lost before or while waiting.
+    fn sync(&self) -> impl Future<Output = Result<(), ServerError>> + Send;
 
     /// Wait for the completion of every task in the server.
     ///
@@ -50,9 +93,28 @@ pub trait ComputeChannel<Server: ComputeServer>: Clone + core::fmt::Debug + Send
     /// Get the current memory usage of the server.
     fn memory_usage(&self) -> crate::memory_management::MemoryUsage;
 
+    /// Whether the memory management places several allocations on the same page.
+    fn uses_sub_slices(&self) -> bool;
+
+    /// Whether the server is still able to execute work.
+    fn status(&self) -> Result<(), ServerError>;
+
+    /// Take the error of the last kernel that couldn't be created, if any.
+    fn take_kernel_error(&self) -> Option<ServerError>;
+
     /// Enable collecting timestamps.
     fn enable_timestamps(&self);
 
     /// Disable collecting timestamps.
     fn disable_timestamps(&self);
+
+    /// Wait for the completion of every task in the server and return the execution time of
+    /// every profiled kernel.
+    fn read_timings(&self) -> impl Future<Output = Option<KernelTimings>> + Send;
+
+    /// Run `op` on the server, ordered like the other operations of the channel.
+    fn with_server<R: Send + 'static>(
+        &self,
+        op: impl FnOnce(&mut Server) -> R + Send + 'static,
+    ) -> R;
 }
@@ -1,9 +1,15 @@
 use super::ComputeChannel;
-use crate::server::{Binding, ComputeServer, CubeCount, Handle};
+use crate::memory_management::{
+    AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError,
+};
+use crate::server::{Binding, ComputeServer, CubeCount, Handle, KernelTimings, ServerError};
 use crate::storage::BindingResource;
 use crate::ExecutionMode;
+use alloc::boxed::Box;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
 use cubecl_common::benchmark::TimestampsResult;
 
 /// A channel using a [ref cell](core::cell::RefCell) to access the server with mutability.
@@ -44,26 +50,70 @@ impl<Server> ComputeChannel<Server> for RefCellComputeChannel<Server>
 where
     Server: ComputeServer + Send,
 {
-    async fn read(&self, binding: Binding) -> Vec<u8> {
-        let future = {
-            let mut server = self.server.borrow_mut();
-            server.read(binding)
-        };
-        future.await
+    fn read(&self, binding: Binding) -> impl Future<Output = Result<Vec<u8>, ServerError>> + Send {
+        // The future is boxed so it isn't tied to the borrow of the server.
+        let mut server = self.server.borrow_mut();
+        // Reading from a lost device fails in the backend, so don't enqueue the read at all.
+        let read = server.status().map(|_| server.read(binding));
+        let fut: Pin<Box<dyn Future<Output = Result<Vec<u8>, ServerError>> + Send>> =
+            Box::pin(async move { Ok(read?.await) });
+        fut
     }
 
     fn get_resource(&self, binding: Binding) -> BindingResource<Server> {
         self.server.borrow_mut().get_resource(binding)
     }
 
-    fn create(&self, resource: &[u8]) -> Handle {
+    fn create(&self, resource: &[u8]) -> Result<Handle, MemoryError> {
         self.server.borrow_mut().create(resource)
     }
 
-    fn empty(&self, size: usize) -> Handle {
+    fn empty(&self, size: usize) -> Result<Handle, MemoryError> {
         self.server.borrow_mut().empty(size)
     }
 
+    async fn empty_async(&self, size: usize) -> Result<Handle, MemoryError> {
+        self.empty(size)
+    }
+
+    fn memset(&self, binding: Binding, value: u8) {
+        self.server.borrow_mut().memset(binding, value)
+    }
+
+    fn copy(&self, src: Binding, dst: Binding) {
+        self.server.borrow_mut().copy(src, dst)
+    }
+
+    fn preallocate(&self, size: u64) -> Result<(), MemoryError> {
+        self.server.borrow_mut().preallocate(size)
+    }
+
+    fn set_residency_budget(&self, budget: Option<u64>) {
+        self.server.borrow_mut().set_residency_budget(budget)
+    }
+
+    fn bypass_allocation_throttle(&self, bypass: bool) {
+        self.server.borrow_mut().bypass_allocation_throttle(bypass)
+    }
+
+    fn reconfigure_memory(
+        &self,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError> {
+        self.server
+            .borrow_mut()
+            .reconfigure_memory(properties, config)
+    }
+
+    fn start_allocation_trace(&self) {
+        self.server.borrow_mut().start_allocation_trace()
+    }
+
+    fn stop_allocation_trace(&self) -> Option<AllocationTrace> {
+        self.server.borrow_mut().stop_allocation_trace()
+    }
+
     unsafe fn execute(
         &self,
         kernel_description: Server::Kernel,
@@ -76,16 +126,23 @@ where
             .execute(kernel_description, count, bindings, kind)
     }
 
+    fn precompile(&self, kernels: Vec<Server::Kernel>, mode: ExecutionMode) {
+        self.server.borrow_mut().precompile(kernels, mode)
+    }
+
     fn flush(&self) {
         self.server.borrow_mut().flush()
     }
 
-    async fn sync(&self) {
+    async fn sync(&self) -> Result<(), ServerError> {
         let future = {
             let mut server = self.server.borrow_mut();
+            // The work of a lost device never completes, so don't wait for it.
+            server.status()?;
             server.sync()
         };
-        future.await
+        future.await;
+        self.server.borrow().status()
     }
 
     async fn sync_elapsed(&self) -> TimestampsResult {
@@ -100,6 +157,18 @@ where
         self.server.borrow_mut().memory_usage()
     }
 
+    fn uses_sub_slices(&self) -> bool {
+        self.server.borrow().uses_sub_slices()
+    }
+
+    fn status(&self) -> Result<(), ServerError> {
+        self.server.borrow().status()
+    }
+
+    fn take_kernel_error(&self) -> Option<ServerError> {
+        self.server.borrow_mut().take_kernel_error()
+    }
+
     fn enable_timestamps(&self) {
         self.server.borrow_mut().enable_timestamps();
     }
@@ -107,6 +176,21 @@ where
     fn disable_timestamps(&self) {
         self.server.borrow_mut().disable_timestamps();
     }
+
+    async fn read_timings(&self) -> Option<KernelTimings> {
+        let future = {
+            let mut server = self.server.borrow_mut();
+            server.read_timings()
+        };
+        future.await
+    }
+
+    fn with_server<R: Send + 'static>(
+        &self,
+        op: impl FnOnce(&mut Server) -> R + Send + 'static,
+    ) -> R {
+        op(&mut self.server.borrow_mut())
+    }
 }
 
 /// This is unsafe, since no concurrency is supported by the `RefCell` channel.
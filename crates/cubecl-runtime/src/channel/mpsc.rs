@@ -1,11 +1,13 @@
-use std::{sync::Arc, thread};
+use std::{future::Future, sync::Arc, thread};
 
 use cubecl_common::benchmark::TimestampsResult;
 
 use super::ComputeChannel;
 use crate::{
-    memory_management::MemoryUsage,
-    server::{Binding, ComputeServer, CubeCount, Handle},
+    memory_management::{
+        AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError, MemoryUsage,
+    },
+    server::{Binding, ComputeServer, CubeCount, Handle, KernelTimings, ServerError},
     storage::BindingResource,
     ExecutionMode,
 };
@@ -35,17 +37,35 @@ enum Message<Server>
 where
     Server: ComputeServer,
 {
-    Read(Binding, Callback<Vec<u8>>),
+    Read(Binding, Callback<Result<Vec<u8>, ServerError>>),
     GetResource(Binding, Callback<BindingResource<Server>>),
-    Create(Vec<u8>, Callback<Handle>),
-    Empty(usize, Callback<Handle>),
+    Create(Vec<u8>, Callback<Result<Handle, MemoryError>>),
+    Empty(usize, Callback<Result<Handle, MemoryError>>),
+    Memset(Binding, u8),
+    Copy(Binding, Binding),
+    Preallocate(u64, Callback<Result<(), MemoryError>>),
+    SetResidencyBudget(Option<u64>),
+    BypassAllocationThrottle(bool),
+    ReconfigureMemory(
+        MemoryDeviceProperties,
+        MemoryConfiguration,
+        Callback<Result<(), MemoryError>>,
+    ),
+    StartAllocationTrace,
+    StopAllocationTrace(Callback<Option<AllocationTrace>>),
     ExecuteKernel((Server::Kernel, CubeCount, ExecutionMode), Vec<Binding>),
+    Precompile(Vec<Server::Kernel>, ExecutionMode),
     Flush,
     SyncElapsed(Callback<TimestampsResult>),
-    Sync(Callback<()>),
+    Sync(Callback<Result<(), ServerError>>),
     GetMemoryUsage(Callback<MemoryUsage>),
+    UsesSubSlices(Callback<bool>),
+    Status(Callback<Result<(), ServerError>>),
+    TakeKernelError(Callback<Option<ServerError>>),
     EnableTimestamps,
     DisableTimestamps,
+    ReadTimings(Callback<Option<KernelTimings>>),
+    WithServer(Box<dyn FnOnce(&mut Server) + Send>),
 }
 
 impl<Server> MpscComputeChannel<Server>
@@ -63,7 +83,11 @@ where
                 while let Ok(message) = receiver.recv().await {
                     match message {
                         Message::Read(binding, callback) => {
-                            let data = server.read(binding).await;
+                            // Reading from a lost device fails in the backend, so don't read.
+                            let data = match server.status() {
+                                Ok(()) => Ok(server.read(binding).await),
+                                Err(err) => Err(err),
+                            };
                             callback.send(data).await.unwrap();
                         }
                         Message::GetResource(binding, callback) => {
@@ -78,16 +102,52 @@ where
                             let handle = server.empty(size);
                             callback.send(handle).await.unwrap();
                         }
+                        Message::Preallocate(size, callback) => {
+                            let result = server.preallocate(size);
+                            callback.send(result).await.unwrap();
+                        }
+                        Message::Memset(binding, value) => {
+                            server.memset(binding, value);
+                        }
+                        Message::Copy(src, dst) => {
+                            server.copy(src, dst);
+                        }
+                        Message::SetResidencyBudget(budget) => {
+                            server.set_residency_budget(budget);
+                        }
+                        Message::BypassAllocationThrottle(bypass) => {
+                            server.bypass_allocation_throttle(bypass);
+                        }
+                        Message::ReconfigureMemory(properties, config, callback) => {
+                            let result = server.reconfigure_memory(properties, config);
+                            callback.send(result).await.unwrap();
+                        }
+                        Message::StartAllocationTrace => {
+                            server.start_allocation_trace();
+                        }
+                        Message::StopAllocationTrace(callback) => {
+                            callback.send(server.stop_allocation_trace()).await.unwrap();
+                        }
                         Message::ExecuteKernel(kernel, bindings) => unsafe {
                             server.execute(kernel.0, kernel.1, bindings, kernel.2);
                         },
+                        Message::Precompile(kernels, mode) => {
+                            server.precompile(kernels, mode);
+                        }
                         Message::SyncElapsed(callback) => {
                             let duration = server.sync_elapsed().await;
                             callback.send(duration).await.unwrap();
                         }
                         Message::Sync(callback) => {
-                            server.sync().await;
-                            callback.send(()).await.unwrap();
+                            // The work of a lost device never completes, so don't wait for it.
+                            let result = match server.status() {
+                                Ok(()) => {
+                                    server.sync().await;
+                                    server.status()
+                                }
+                                Err(err) => Err(err),
+                            };
+                            callback.send(result).await.unwrap();
                         }
                         Message::Flush => {
                             server.flush();
@@ -95,12 +155,28 @@ where
                         Message::GetMemoryUsage(callback) => {
                             callback.send(server.memory_usage()).await.unwrap();
                         }
+                        Message::UsesSubSlices(callback) => {
+                            callback.send(server.uses_sub_slices()).await.unwrap();
+                        }
+                        Message::Status(callback) => {
+                            callback.send(server.status()).await.unwrap();
+                        }
+                        Message::TakeKernelError(callback) => {
+                            callback.send(server.take_kernel_error()).await.unwrap();
+                        }
                         Message::EnableTimestamps => {
                             server.enable_timestamps();
                         }
                         Message::DisableTimestamps => {
                             server.disable_timestamps();
                         }
+                        Message::ReadTimings(callback) => {
+                            let timings = server.read_timings().await;
+                            callback.send(timings).await.unwrap();
+                        }
+                        Message::WithServer(op) => {
+                            op(&mut server);
+                        }
                     };
                 }
             });
@@ -124,11 +200,15 @@ impl<Server> ComputeChannel<Server> for MpscComputeChannel<Server>
 where
     Server: ComputeServer + 'static,
 {
-    async fn read(&self, binding: Binding) -> Vec<u8> {
-        let sender = self.state.sender.clone();
+    fn read(&self, binding: Binding) -> impl Future<Output = Result<Vec<u8>, ServerError>> + Send {
         let (callback, response) = async_channel::unbounded();
-        sender.send(Message::Read(binding, callback)).await.unwrap();
-        handle_response(response.recv().await)
+        // The channel is unbounded, so sending never waits.
+        self.state
+            .sender
+            .send_blocking(Message::Read(binding, callback))
+            .unwrap();
+
+        async move { handle_response(response.recv().await) }
     }
 
     fn get_resource(&self, binding: Binding) -> BindingResource<Server> {
@@ -142,7 +222,7 @@ where
         handle_response(response.recv_blocking())
     }
 
-    fn create(&self, data: &[u8]) -> Handle {
+    fn create(&self, data: &[u8]) -> Result<Handle, MemoryError> {
         let (callback, response) = async_channel::unbounded();
 
         self.state
@@ -153,7 +233,7 @@ where
         handle_response(response.recv_blocking())
     }
 
-    fn empty(&self, size: usize) -> Handle {
+    fn empty(&self, size: usize) -> Result<Handle, MemoryError> {
         let (callback, response) = async_channel::unbounded();
         self.state
             .sender
@@ -163,6 +243,86 @@ where
         handle_response(response.recv_blocking())
     }
 
+    async fn empty_async(&self, size: usize) -> Result<Handle, MemoryError> {
+        let (callback, response) = async_channel::unbounded();
+        self.state
+            .sender
+            .send(Message::Empty(size, callback))
+            .await
+            .unwrap();
+
+        handle_response(response.recv().await)
+    }
+
+    fn preallocate(&self, size: u64) -> Result<(), MemoryError> {
+        let (callback, response) = async_channel::unbounded();
+        self.state
+            .sender
+            .send_blocking(Message::Preallocate(size, callback))
+            .unwrap();
+
+        handle_response(response.recv_blocking())
+    }
+
+    fn memset(&self, binding: Binding, value: u8) {
+        self.state
+            .sender
+            .send_blocking(Message::Memset(binding, value))
+            .unwrap();
+    }
+
+    fn copy(&self, src: Binding, dst: Binding) {
+        self.state
+            .sender
+            .send_blocking(Message::Copy(src, dst))
+            .unwrap();
+    }
+
+    fn set_residency_budget(&self, budget: Option<u64>) {
+        self.state
+            .sender
+            .send_blocking(Message::SetResidencyBudget(budget))
+            .unwrap();
+    }
+
+    fn bypass_allocation_throttle(&self, bypass: bool) {
+        self.state
+            .sender
+            .send_blocking(Message::BypassAllocationThrottle(bypass))
+            .unwrap();
+    }
+
+    fn reconfigure_memory(
+        &self,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError> {
+        let (callback, response) = async_channel::unbounded();
+        self.state
+            .sender
+            .send_blocking(Message::ReconfigureMemory(properties, config, callback))
+            .unwrap();
+
+        handle_response(response.recv_blocking())
+    }
+
+    fn start_allocation_trace(&self) {
+        self.state
+            .sender
+            .send_blocking(Message::StartAllocationTrace)
+            .unwrap();
+    }
+
+    fn stop_allocation_trace(&self) -> Option<AllocationTrace> {
+        let (callback, response) = async_channel::unbounded();
+        self.state
+            .sender
+            .send_blocking(Message::StopAllocationTrace(callback))
+            .unwrap();
+
+        handle_response(response.recv_blocking())
+    }
+
     unsafe fn execute(
         &self,
         kernel: Server::Kernel,
@@ -176,11 +336,18 @@ where
             .unwrap()
     }
 
+    fn precompile(&self, kernels: Vec<Server::Kernel>, mode: ExecutionMode) {
+        self.state
+            .sender
+            .send_blocking(Message::Precompile(kernels, mode))
+            .unwrap()
+    }
+
     fn flush(&self) {
         self.state.sender.send_blocking(Message::Flush).unwrap()
     }
 
-    async fn sync(&self) {
+    async fn sync(&self) -> Result<(), ServerError> {
         let (callback, response) = async_channel::unbounded();
         self.state
             .sender
@@ -209,6 +376,33 @@ where
         handle_response(response.recv_blocking())
     }
 
+    fn uses_sub_slices(&self) -> bool {
+        let (callback, response) = async_channel::unbounded();
+        self.state
+            .sender
+            .send_blocking(Message::UsesSubSlices(callback))
+            .unwrap();
+        handle_response(response.recv_blocking())
+    }
+
+    fn status(&self) -> Result<(), ServerError> {
+        let (callback, response) = async_channel::unbounded();
+        self.state
+            .sender
+            .send_blocking(Message::Status(callback))
+            .unwrap();
+        handle_response(response.recv_blocking())
+    }
+
+    fn take_kernel_error(&self) -> Option<ServerError> {
+        let (callback, response) = async_channel::unbounded();
+        self.state
+            .sender
+            .send_blocking(Message::TakeKernelError(callback))
+            .unwrap();
+        handle_response(response.recv_blocking())
+    }
+
     fn enable_timestamps(&self) {
         self.state
             .sender
@@ -222,6 +416,31 @@ where
             .send_blocking(Message::DisableTimestamps)
             .unwrap();
     }
+
+    async fn read_timings(&self) -> Option<KernelTimings> {
+        let (callback, response) = async_channel::unbounded();
+        self.state
+            .sender
+            .send(Message::ReadTimings(callback))
+            .await
+            .unwrap();
+        handle_response(response.recv().await)
+    }
+
+    fn with_server<R: Send + 'static>(
+        &self,
+        op: impl FnOnce(&mut Server) -> R + Send + 'static,
+    ) -> R {
+        let (callback, response) = async_channel::unbounded();
+        let op = Box::new(move |server: &mut Server| {
+            callback.send_blocking(op(server)).unwrap();
+        });
+        self.state
+            .sender
+            .send_blocking(Message::WithServer(op))
+            .unwrap();
+        handle_response(response.recv_blocking())
+    }
 }
 
 fn handle_response<Response, Err: core::fmt::Debug>(response: Result<Response, Err>) -> Response {
@@ -1,9 +1,15 @@
 use super::ComputeChannel;
-use crate::server::{Binding, ComputeServer, CubeCount, Handle};
+use crate::memory_management::{
+    AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError,
+};
+use crate::server::{Binding, ComputeServer, CubeCount, Handle, KernelTimings, ServerError};
 use crate::storage::BindingResource;
 use crate::ExecutionMode;
+use alloc::boxed::Box;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
 use cubecl_common::benchmark::TimestampsResult;
 use spin::Mutex;
 
@@ -37,28 +43,69 @@ impl<Server> ComputeChannel<Server> for MutexComputeChannel<Server>
 where
     Server: ComputeServer,
 {
-    async fn read(&self, handle: Binding) -> Vec<u8> {
-        // Nb: The order here is really important - the mutex guard has to be dropped before
-        // the future is polled. Just calling lock().read().await can deadlock.
-        let fut = {
-            let mut server = self.server.lock();
-            server.read(handle)
-        };
-        fut.await
+    fn read(&self, handle: Binding) -> impl Future<Output = Result<Vec<u8>, ServerError>> + Send {
+        // Nb: The mutex guard is dropped before the future is returned, polling it while holding
+        // the lock can deadlock. The future is boxed so it isn't tied to the guard.
+        let mut server = self.server.lock();
+        // Reading from a lost device fails in the backend, so don't enqueue the read at all.
+        let read = server.status().map(|_| server.read(handle));
+        let fut: Pin<Box<dyn Future<Output = Result<Vec<u8>, ServerError>> + Send>> =
+            Box::pin(async move { Ok(read?.await) });
+        fut
     }
 
     fn get_resource(&self, binding: Binding) -> BindingResource<Server> {
         self.server.lock().get_resource(binding)
     }
 
-    fn create(&self, data: &[u8]) -> Handle {
+    fn create(&self, data: &[u8]) -> Result<Handle, MemoryError> {
         self.server.lock().create(data)
     }
 
-    fn empty(&self, size: usize) -> Handle {
+    fn empty(&self, size: usize) -> Result<Handle, MemoryError> {
         self.server.lock().empty(size)
     }
 
+    async fn empty_async(&self, size: usize) -> Result<Handle, MemoryError> {
+        self.empty(size)
+    }
+
+    fn memset(&self, binding: Binding, value: u8) {
+        self.server.lock().memset(binding, value)
+    }
+
+    fn copy(&self, src: Binding, dst: Binding) {
+        self.server.lock().copy(src, dst)
+    }
+
+    fn preallocate(&self, size: u64) -> Result<(), MemoryError> {
+        self.server.lock().preallocate(size)
+    }
+
+    fn set_residency_budget(&self, budget: Option<u64>) {
+        self.server.lock().set_residency_budget(budget)
+    }
+
+    fn bypass_allocation_throttle(&self, bypass: bool) {
+        self.server.lock().bypass_allocation_throttle(bypass)
+    }
+
+    fn reconfigure_memory(
+        &self,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError> {
+        self.server.lock().reconfigure_memory(properties, config)
+    }
+
+    fn start_allocation_trace(&self) {
+        self.server.lock().start_allocation_trace()
+    }
+
+    fn stop_allocation_trace(&self) -> Option<AllocationTrace> {
+        self.server.lock().stop_allocation_trace()
+    }
+
     unsafe fn execute(
         &self,
         kernel: Server::Kernel,
@@ -69,18 +116,25 @@ where
         self.server.lock().execute(kernel, count, handles, kind)
     }
 
+    fn precompile(&self, kernels: Vec<Server::Kernel>, mode: ExecutionMode) {
+        self.server.lock().precompile(kernels, mode)
+    }
+
     fn flush(&self) {
         self.server.lock().flush();
     }
 
-    async fn sync(&self) {
+    async fn sync(&self) -> Result<(), ServerError> {
         // Nb: The order here is really important - the mutex guard has to be dropped before
         // the future is polled. Just calling lock().sync().await can deadlock.
         let fut = {
             let mut server = self.server.lock();
+            // The work of a lost device never completes, so don't wait for it.
+            server.status()?;
             server.sync()
         };
-        fut.await
+        fut.await;
+        self.server.lock().status()
     }
 
     async fn sync_elapsed(&self) -> TimestampsResult {
@@ -97,6 +151,18 @@ where
         self.server.lock().memory_usage()
     }
 
+    fn uses_sub_slices(&self) -> bool {
+        self.server.lock().uses_sub_slices()
+    }
+
+    fn status(&self) -> Result<(), ServerError> {
+        self.server.lock().status()
+    }
+
+    fn take_kernel_error(&self) -> Option<ServerError> {
+        self.server.lock().take_kernel_error()
+    }
+
     fn enable_timestamps(&self) {
         self.server.lock().enable_timestamps();
     }
@@ -104,4 +170,20 @@ where
     fn disable_timestamps(&self) {
         self.server.lock().disable_timestamps();
     }
+
+    async fn read_timings(&self) -> Option<KernelTimings> {
+        // Nb: The mutex guard has to be dropped before the future is polled.
+        let fut = {
+            let mut server = self.server.lock();
+            server.read_timings()
+        };
+        fut.await
+    }
+
+    fn with_server<R: Send + 'static>(
+        &self,
+        op: impl FnOnce(&mut Server) -> R + Send + 'static,
+    ) -> R {
+        op(&mut self.server.lock())
+    }
 }
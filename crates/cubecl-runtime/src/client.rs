@@ -1,15 +1,20 @@
-use core::future::Future;
+use core::{
+    cell::{Cell, OnceCell},
+    future::Future,
+};
 
 use crate::{
     channel::ComputeChannel,
-    memory_management::MemoryUsage,
-    server::{Binding, ComputeServer, CubeCount, Handle},
+    memory_management::{AllocationTrace, MemoryConfiguration, MemoryError, MemoryUsage},
+    server::{Binding, ComputeServer, CubeCount, Handle, KernelTimings, ServerError},
     storage::BindingResource,
-    DeviceProperties, ExecutionMode,
+    AdapterInfo, DeviceProperties, ExecutionMode,
 };
+use alloc::boxed::Box;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use cubecl_common::benchmark::TimestampsResult;
+use hashbrown::HashMap;
 
 /// The ComputeClient is the entry point to require tasks from the ComputeServer.
 /// It should be obtained for a specific device via the Compute struct.
@@ -19,10 +24,34 @@ pub struct ComputeClient<Server: ComputeServer, Channel> {
     state: Arc<ComputeClientState<Server>>,
 }
 
-#[derive(new, Debug)]
+/// The alignment in bytes of the offset of partial reads.
+const READ_ALIGNMENT: u64 = 4;
+
+/// The alignment in bytes of the offsets and size of copies between handles.
+const COPY_ALIGNMENT: u64 = 4;
+
+/// Called with the number of requested bytes when the device runs out of memory, see
+/// [set_oom_callback](ComputeClient::set_oom_callback).
+pub type OomCallback = Box<dyn Fn(u64) -> bool + Send + Sync>;
+
+#[derive(new)]
 struct ComputeClientState<Server: ComputeServer> {
     properties: DeviceProperties<Server::Feature>,
     timestamp_lock: async_lock::Mutex<()>,
+    oom_callback: spin::Mutex<Option<Arc<OomCallback>>>,
+    /// Scratch buffers by size, see [scratch](ComputeClient::scratch).
+    scratch: spin::Mutex<HashMap<usize, Vec<Handle>>>,
+}
+
+impl<Server: ComputeServer> core::fmt::Debug for ComputeClientState<Server> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ComputeClientState")
+            .field("properties", &self.properties)
+            .field("timestamp_lock", &self.timestamp_lock)
+            .field("oom_callback", &self.oom_callback.lock().is_some())
+            .field("scratch", &self.scratch)
+            .finish()
+    }
 }
 
 impl<S, C> Clone for ComputeClient<S, C>
@@ -45,24 +74,145 @@ where
 {
     /// Create a new client.
     pub fn new(channel: Channel, properties: DeviceProperties<Server::Feature>) -> Self {
-        let state = ComputeClientState::new(properties, async_lock::Mutex::new(()));
+        let state = ComputeClientState::new(
+            properties,
+            async_lock::Mutex::new(()),
+            spin::Mutex::new(None),
+            spin::Mutex::new(HashMap::new()),
+        );
         Self {
             channel,
             state: Arc::new(state),
         }
     }
 
-    /// Given a binding, returns owned resource as bytes.
-    pub async fn read_async(&self, binding: Binding) -> Vec<u8> {
-        self.channel.read(binding).await
+    /// Given a binding, returns owned resource as bytes, without blocking the thread.
+    ///
+    /// The read is enqueued when this is called, so the data is the one written by the work
+    /// submitted before, even if more work is submitted before the future is awaited. This allows
+    /// overlapping the readback with further compute.
+    ///
+    /// # Remarks
+    /// Panics if the device was lost, see [try_read_async](Self::try_read_async).
+    pub fn read_async(&self, binding: Binding) -> impl Future<Output = Vec<u8>> + Send + '_ {
+        let read = self.try_read_async(binding);
+        async move { read.await.unwrap() }
+    }
+
+    /// Same as [read_async](Self::read_async), but returns [ServerError::DeviceLost] instead of
+    /// reading when the device was lost.
+    pub fn try_read_async(
+        &self,
+        binding: Binding,
+    ) -> impl Future<Output = Result<Vec<u8>, ServerError>> + Send + '_ {
+        self.channel.read(binding)
     }
 
     /// Given a binding, returns owned resource as bytes.
     ///
     /// # Remarks
-    /// Panics if the read operation fails.
+    /// Panics if the read operation fails, or if the device was lost, see
+    /// [try_read](Self::try_read).
     pub fn read(&self, binding: Binding) -> Vec<u8> {
-        cubecl_common::reader::read_sync(self.channel.read(binding))
+        self.try_read(binding).unwrap()
+    }
+
+    /// Given a binding, returns owned resource as bytes, or [ServerError::DeviceLost] when the
+    /// device was lost.
+    pub fn try_read(&self, binding: Binding) -> Result<Vec<u8>, ServerError> {
+        cubecl_common::reader::read_sync(self.try_read_async(binding))
+    }
+
+    /// Read `len` bytes of the handle, starting at `offset` bytes, without copying the rest of
+    /// the buffer.
+    ///
+    /// # Remarks
+    /// Panics if the range goes past the end of the handle, or if the read operation fails.
+    pub fn read_range(&self, handle: &Handle, offset: u64, len: u64) -> Vec<u8> {
+        let size = handle.size();
+        let end = offset
+            .checked_add(len)
+            .filter(|end| *end <= size)
+            .unwrap_or_else(|| {
+                panic!("Can't read {len} bytes at offset {offset} of a handle of {size} bytes")
+            });
+
+        // Backends can't read empty ranges, and the aligned range would still read a few bytes.
+        if len == 0 {
+            return Vec::new();
+        }
+
+        // Some backends can only copy from offsets aligned to 4 bytes, so read from the closest
+        // aligned offset and drop the extra bytes.
+        let aligned_offset = offset - offset % READ_ALIGNMENT;
+        let binding = handle
+            .clone()
+            .offset_start(aligned_offset)
+            .offset_end(size - end)
+            .binding();
+
+        let mut data = self.read(binding);
+        data.drain(..(offset - aligned_offset) as usize);
+        data
+    }
+
+    /// Set every byte of the given handle to `value`.
+    ///
+    /// Uses the native clear of the backend instead of launching a kernel, so it's the cheapest way
+    /// to zero a buffer. The operation is ordered after the work already submitted by the client.
+    pub fn memset(&self, handle: &Handle, value: u8) {
+        self.channel.memset(handle.clone().binding(), value)
+    }
+
+    /// Copy the first `len` bytes of `src` to the start of `dst`, without a round trip through
+    /// the host.
+    ///
+    /// Uses the native buffer copy of the backend. The copy is ordered like the other operations
+    /// of the client, so it sees every write to `src` submitted before it, and the work submitted
+    /// after it sees the copied bytes in `dst`.
+    ///
+    /// Handles from the same page of a memory pool share a buffer. Backends that can't copy within
+    /// a buffer, like `wgpu`, copy them through a temporary buffer.
+    ///
+    /// # Remarks
+    /// Panics if `len` is bigger than one of the handles, if `len` or the offset of a handle isn't
+    /// a multiple of 4 bytes, or if the copied ranges overlap.
+    pub fn copy(&self, src: &Handle, dst: &Handle, len: u64) {
+        let (src_size, dst_size) = (src.size(), dst.size());
+        assert!(
+            len <= src_size && len <= dst_size,
+            "Can't copy {len} bytes from a handle of {src_size} bytes to a handle of {dst_size} bytes"
+        );
+
+        let src_offset = src.offset_start.unwrap_or(0);
+        let dst_offset = dst.offset_start.unwrap_or(0);
+        for (name, value) in [
+            ("size", len),
+            ("source offset", src_offset),
+            ("destination offset", dst_offset),
+        ] {
+            assert_eq!(
+                value.next_multiple_of(COPY_ALIGNMENT),
+                value,
+                "The {name} of a copy has to be a multiple of {COPY_ALIGNMENT} bytes, got {value}"
+            );
+        }
+
+        // Different handles never overlap, even when they are slices of the same page.
+        if src.memory.id() == dst.memory.id() {
+            assert!(
+                src_offset + len <= dst_offset || dst_offset + len <= src_offset,
+                "Can't copy {len} bytes between overlapping ranges of the same handle"
+            );
+        }
+
+        if len == 0 {
+            return;
+        }
+
+        let src = src.clone().offset_end(src_size - len).binding();
+        let dst = dst.clone().offset_end(dst_size - len).binding();
+        self.channel.copy(src, dst)
     }
 
     /// Given a resource handle, returns the storage resource.
@@ -71,13 +221,256 @@ where
     }
 
     /// Given a resource, stores it and returns the resource handle.
+    ///
+    /// # Remarks
+    /// Panics if the memory can't be reserved, see [try_create](Self::try_create).
     pub fn create(&self, data: &[u8]) -> Handle {
-        self.channel.create(data)
+        self.try_create(data).unwrap()
+    }
+
+    /// Given a resource, stores it and returns the resource handle, or an error if the memory
+    /// can't be reserved.
+    pub fn try_create(&self, data: &[u8]) -> Result<Handle, MemoryError> {
+        self.retry_on_oom(data.len() as u64, || self.channel.create(data))
     }
 
     /// Reserves `size` bytes in the storage, and returns a handle over them.
+    ///
+    /// # Remarks
+    /// Panics if the memory can't be reserved, see [try_empty](Self::try_empty).
     pub fn empty(&self, size: usize) -> Handle {
-        self.channel.empty(size)
+        self.try_empty(size).unwrap()
+    }
+
+    /// Reserves `size` bytes in the storage, and returns a handle over them, or an error if the
+    /// memory can't be reserved.
+    pub fn try_empty(&self, size: usize) -> Result<Handle, MemoryError> {
+        self.retry_on_oom(size as u64, || self.channel.empty(size))
+    }
+
+    /// Reserves `size` bytes in the storage, and returns a handle over them.
+    ///
+    /// Unlike [empty](Self::empty), this waits for the server without blocking the thread, so it
+    /// can be used from an async executor. It goes through the same channel, so it's ordered like
+    /// the other operations of this client.
+    pub async fn empty_async(&self, size: usize) -> Handle {
+        self.try_empty_async(size).await.unwrap()
+    }
+
+    /// Same as [empty_async](Self::empty_async), but returns an error if the memory can't be
+    /// reserved.
+    ///
+    /// While a [throttled](MemoryConfiguration::Throttled) memory can't grow, the thread is
+    /// blocked until the next period.
+    pub async fn try_empty_async(&self, size: usize) -> Result<Handle, MemoryError> {
+        let err = match self.throttled_empty_async(size).await {
+            Err(err @ MemoryError::OutOfMemory { .. }) => err,
+            result => return result,
+        };
+
+        if self.call_oom_callback(size as u64) {
+            self.throttled_empty_async(size).await
+        } else {
+            Err(err)
+        }
+    }
+
+    async fn throttled_empty_async(&self, size: usize) -> Result<Handle, MemoryError> {
+        #[cfg(allocation_throttle)]
+        loop {
+            match self.channel.empty_async(size).await {
+                Err(MemoryError::Throttled { retry_after }) => std::thread::sleep(retry_after),
+                result => return result,
+            }
+        }
+
+        #[cfg(not(allocation_throttle))]
+        self.channel.empty_async(size).await
+    }
+
+    /// Returns a temporary buffer of `size` bytes, reusing one returned by a previous call when
+    /// every handle to it was dropped.
+    ///
+    /// Useful for temporaries of loops that repeatedly run kernels of the same size, since no
+    /// allocation happens once the buffers are created. Queued kernels may still use a reused
+    /// buffer, but they run in order so they're done with it before the next kernel using it.
+    ///
+    /// The buffers are kept by the client, see [clear_scratch](Self::clear_scratch) to release
+    /// them. Their content is undefined.
+    pub fn scratch(&self, size: usize) -> Handle {
+        if let Some(buffers) = self.state.scratch.lock().get(&size) {
+            if let Some(handle) = buffers.iter().find(|handle| handle.can_mut()) {
+                return handle.clone();
+            }
+        }
+
+        // Allocate without holding the lock, since the allocation may call back into the client.
+        let handle = self.empty(size);
+        self.state
+            .scratch
+            .lock()
+            .entry(size)
+            .or_default()
+            .push(handle.clone());
+        handle
+    }
+
+    /// Release the buffers kept for [scratch](Self::scratch), so they can be reused by other
+    /// allocations once no handle to them is left.
+    pub fn clear_scratch(&self) {
+        self.state.scratch.lock().clear();
+    }
+
+    /// Create a [memory scope](MemoryScope) owning the handles allocated through it.
+    ///
+    /// The handles are released when the scope is dropped, which can happen in the middle of a
+    /// function to lower its peak memory. The borrow checker ensures the handles aren't used
+    /// after that.
+    pub fn memory_scope(&self) -> MemoryScope<'_, Server, Channel> {
+        MemoryScope {
+            client: self,
+            chunks: core::array::from_fn(|_| OnceCell::new()),
+            len: Cell::new(0),
+        }
+    }
+
+    /// Run `func` with a [memory scope](MemoryScope), releasing the handles allocated through it
+    /// as soon as `func` returns.
+    pub fn scope<R>(&self, func: impl FnOnce(&MemoryScope<'_, Server, Channel>) -> R) -> R {
+        func(&self.memory_scope())
+    }
+
+    /// Eagerly grows the memory pool that would serve an allocation of `bytes`, so that it holds
+    /// at least `bytes` of contiguous memory.
+    ///
+    /// Useful before a large workload whose memory requirements are known, to avoid allocating
+    /// while the first kernels are launched. Nothing is allocated when the pool already has a free
+    /// slice of at least `bytes`.
+    pub fn reserve(&self, bytes: u64) -> Result<(), MemoryError> {
+        self.retry_on_oom(bytes, || self.channel.preallocate(bytes))
+    }
+
+    /// Limit the number of bytes the memory pools keep reserved on the device, or remove the
+    /// limit with `None`.
+    ///
+    /// When an allocation needs a page that doesn't fit in the budget, the pages that don't hold
+    /// a live handle are first returned to the device. If it still doesn't fit, the allocation
+    /// fails with [MemoryError::OutOfMemory], which calls the
+    /// [OOM callback](Self::set_oom_callback) so the application can drop handles it doesn't
+    /// need anymore.
+    ///
+    /// The data of live handles is never spilled to host memory, it stays on the device until
+    /// the handles are dropped.
+    pub fn set_residency_budget(&self, bytes: Option<u64>) {
+        self.channel.set_residency_budget(bytes)
+    }
+
+    /// Let allocations grow the memory as fast as they need while `bypass` is true, ignoring the
+    /// limit of a [throttled](MemoryConfiguration::Throttled) memory configuration.
+    ///
+    /// Useful around a burst of allocations that shouldn't be delayed. The pages allocated while
+    /// bypassing don't count towards the limit.
+    pub fn bypass_allocation_throttle(&self, bypass: bool) {
+        self.channel.bypass_allocation_throttle(bypass)
+    }
+
+    /// Replace the memory pools with the ones of a new [configuration](MemoryConfiguration).
+    ///
+    /// The server first waits for the work in flight, then every page of the current pools is
+    /// given back to the device. Pages can't be moved, so this fails with
+    /// [MemoryError::LiveAllocations] when some handles are still alive; drop them first.
+    pub fn reconfigure_memory(&self, config: MemoryConfiguration) -> Result<(), MemoryError> {
+        let properties = self.properties().memory_properties().clone();
+        self.channel.reconfigure_memory(properties, config)
+    }
+
+    /// Start recording the size of every allocation, to create pools fitting a repeatable
+    /// workload with [MemoryConfiguration::from_trace].
+    ///
+    /// Recording slows down allocations, so only enable it for a run made to size the memory of
+    /// the next ones.
+    pub fn start_allocation_trace(&self) {
+        self.channel.start_allocation_trace()
+    }
+
+    /// Stop recording the allocations and return the trace, if one was started.
+    pub fn stop_allocation_trace(&self) -> Option<AllocationTrace> {
+        self.channel.stop_allocation_trace()
+    }
+
+    /// Register a callback invoked with the number of requested bytes when an allocation fails
+    /// because the device is [out of memory](MemoryError::OutOfMemory).
+    ///
+    /// The callback can release memory, for instance by dropping cached tensors, and return `true`
+    /// to retry the allocation once. Returning `false` reports the error to the caller.
+    ///
+    /// The callback runs on the thread that requested the allocation, after the server was
+    /// released, so it's free to call back into this client. It replaces any previously
+    /// registered callback, and is shared by every clone of this client.
+    pub fn set_oom_callback(&self, callback: OomCallback) {
+        *self.state.oom_callback.lock() = Some(Arc::new(callback));
+    }
+
+    /// Remove the callback registered with [set_oom_callback](Self::set_oom_callback).
+    pub fn clear_oom_callback(&self) {
+        *self.state.oom_callback.lock() = None;
+    }
+
+    /// Run `op` on the server, ordered like the other operations of this client.
+    ///
+    /// Gives access to the operations specific to a backend, that have no counterpart on the
+    /// client. The server is busy until `op` returns, so it mustn't call back into the client.
+    pub fn with_server<R: Send + 'static>(
+        &self,
+        op: impl FnOnce(&mut Server) -> R + Send + 'static,
+    ) -> R {
+        self.channel.with_server(op)
+    }
+
+    fn retry_on_oom<T>(
+        &self,
+        size: u64,
+        alloc: impl Fn() -> Result<T, MemoryError>,
+    ) -> Result<T, MemoryError> {
+        // Only running out of device memory can be solved by releasing memory, any other error
+        // fails the same way when retried.
+        let err = match Self::wait_on_throttle(&alloc) {
+            Err(err @ MemoryError::OutOfMemory { .. }) => err,
+            result => return result,
+        };
+
+        if self.call_oom_callback(size) {
+            Self::wait_on_throttle(&alloc)
+        } else {
+            Err(err)
+        }
+    }
+
+    /// Run `alloc` until a [throttled](MemoryConfiguration::Throttled) memory lets it grow.
+    ///
+    /// The wait happens on the calling thread after the server was released, so the other users
+    /// of the server aren't delayed.
+    fn wait_on_throttle<T>(alloc: impl Fn() -> Result<T, MemoryError>) -> Result<T, MemoryError> {
+        #[cfg(allocation_throttle)]
+        loop {
+            match alloc() {
+                Err(MemoryError::Throttled { retry_after }) => std::thread::sleep(retry_after),
+                result => return result,
+            }
+        }
+
+        #[cfg(not(allocation_throttle))]
+        alloc()
+    }
+
+    /// Call the OOM callback, and return whether the allocation should be retried.
+    fn call_oom_callback(&self, size: u64) -> bool {
+        // Clone the callback so no lock is held while it runs.
+        let callback = self.state.oom_callback.lock().clone();
+        match callback {
+            Some(callback) => callback(size),
+            None => false,
+        }
     }
 
     /// Executes the `kernel` over the given `bindings`.
@@ -103,13 +496,32 @@ where
             .execute(kernel, count, bindings, ExecutionMode::Unchecked)
     }
 
+    /// Compile the given kernels ahead of time, so their first execution doesn't pay for it.
+    ///
+    /// Useful to move the compilation cost to a loading screen instead of the middle of a
+    /// workload. Kernels are compiled for the given execution `mode`, and the ones that were
+    /// already compiled are skipped. When the server persists compiled kernels, for instance
+    /// with a pipeline cache, they are stored there as well.
+    pub fn precompile(&self, kernels: Vec<Server::Kernel>, mode: ExecutionMode) {
+        self.channel.precompile(kernels, mode)
+    }
+
     /// Flush all outstanding commands.
     pub fn flush(&self) {
         self.channel.flush();
     }
 
     /// Wait for the completion of every task in the server.
+    ///
+    /// # Remarks
+    /// Panics if the device was lost, see [try_sync](Self::try_sync).
     pub async fn sync(&self) {
+        self.try_sync().await.unwrap()
+    }
+
+    /// Wait for the completion of every task in the server, and return [ServerError::DeviceLost]
+    /// if the device was lost before or while waiting.
+    pub async fn try_sync(&self) -> Result<(), ServerError> {
         self.channel.sync().await
     }
 
@@ -118,16 +530,68 @@ where
         self.channel.sync_elapsed().await
     }
 
+    /// Wait for the completion of every task in the server and return the GPU execution time of
+    /// every kernel profiled since the last call, keyed by kernel name.
+    ///
+    /// Returns `None` when kernel profiling isn't enabled or supported by the runtime.
+    pub async fn read_timings(&self) -> Option<KernelTimings> {
+        self.channel.read_timings().await
+    }
+
     /// Get the features supported by the compute server.
     pub fn properties(&self) -> &DeviceProperties<Server::Feature> {
         &self.state.properties
     }
 
+    /// Describe everything known about the device: its adapter, hardware limits, memory
+    /// properties and features.
+    ///
+    /// Meant to be pasted in bug reports, for instance when a kernel isn't available on a device.
+    /// The same summary is the [Display] of the [properties](Self::properties).
+    pub fn describe(&self) -> alloc::string::String {
+        alloc::format!("{}", self.state.properties)
+    }
+
+    /// Get the name, IDs and driver of the device, when the runtime exposes them.
+    ///
+    /// Useful to log which device ran a kernel, or to work around driver-specific bugs.
+    pub fn adapter_info(&self) -> Option<&AdapterInfo> {
+        self.state.properties.adapter_info()
+    }
+
     /// Get the current memory usage of this client.
     pub fn memory_usage(&self) -> MemoryUsage {
         self.channel.memory_usage()
     }
 
+    /// Whether the memory management places several allocations on the same page.
+    ///
+    /// When it does, different handles may be slices of the same buffer, so kernels can't assume
+    /// that distinct bindings never alias.
+    pub fn uses_sub_slices(&self) -> bool {
+        self.channel.uses_sub_slices()
+    }
+
+    /// Check whether the server is still able to execute work.
+    ///
+    /// Most operations don't report failures, so long-running applications should check the
+    /// status regularly, or use [try_sync](Self::try_sync) and [try_read](Self::try_read) which
+    /// check it for them. Once it returns [ServerError::DeviceLost], the client can't be used
+    /// anymore and has to be recreated on a new device.
+    pub fn status(&self) -> Result<(), ServerError> {
+        self.channel.status()
+    }
+
+    /// Take the error of the last kernel that couldn't be created since the previous call, such
+    /// as [ServerError::OutOfResources].
+    ///
+    /// Kernels are created when they are first executed, which doesn't report failures, so call
+    /// it after executing a kernel that may not fit on the device to try a smaller one instead.
+    /// Runtimes that can't attribute a failure to a kernel never return an error.
+    pub fn take_kernel_error(&self) -> Option<ServerError> {
+        self.channel.take_kernel_error()
+    }
+
     /// When executing operation within the profile scope, you can call
     /// [sync_elapsed](Self::sync_elapsed) safely even in multithreaded workloads.
     /// Creates a profiling scope that enables safe timing measurements in concurrent contexts.
@@ -181,3 +645,52 @@ where
         self.channel.enable_timestamps();
     }
 }
+
+/// Owns the handles allocated through it, and releases them all when dropped.
+///
+/// Handles are lent to the caller for the lifetime of the scope, so they can't outlive it. A
+/// handle cloned out of the scope keeps its memory alive like any other handle.
+pub struct MemoryScope<'a, Server: ComputeServer, Channel> {
+    client: &'a ComputeClient<Server, Channel>,
+    /// The handles, in chunks of doubling size so that the handles already lent never move when
+    /// more are tracked. The chunk `k` holds the handles `2^k - 1` to `2^(k + 1) - 2`.
+    chunks: [OnceCell<Box<[OnceCell<Handle>]>>; usize::BITS as usize],
+    len: Cell<usize>,
+}
+
+impl<Server, Channel> MemoryScope<'_, Server, Channel>
+where
+    Server: ComputeServer,
+    Channel: ComputeChannel<Server>,
+{
+    /// Allocate a buffer of `size` bytes owned by the scope, see [empty](ComputeClient::empty).
+    pub fn empty(&self, size: usize) -> &Handle {
+        self.track(self.client.empty(size))
+    }
+
+    /// Allocate a buffer owned by the scope with the given data, see
+    /// [create](ComputeClient::create).
+    pub fn create(&self, data: &[u8]) -> &Handle {
+        self.track(self.client.create(data))
+    }
+
+    /// Give ownership of a handle allocated elsewhere to the scope.
+    pub fn track(&self, handle: Handle) -> &Handle {
+        // Counting from 1, the chunk of a handle is the base 2 logarithm of its position.
+        let position = self.len.get() + 1;
+        self.len.set(position);
+
+        let chunk = position.ilog2() as usize;
+        let slots = self.chunks[chunk]
+            .get_or_init(|| (0..1usize << chunk).map(|_| OnceCell::new()).collect());
+        slots[position - (1 << chunk)].get_or_init(|| handle)
+    }
+}
+
+impl<Server: ComputeServer, Channel> core::fmt::Debug for MemoryScope<'_, Server, Channel> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MemoryScope")
+            .field("handles", &self.len.get())
+            .finish()
+    }
+}
@@ -1,5 +1,35 @@
 use crate::memory_management::{HardwareProperties, MemoryDeviceProperties};
 use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display};
+
+/// A change to the [features](Feature) detected by a runtime, applied when its client is created.
+///
+/// Useful to try a capability the detection misses, or to disable a feature that misbehaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureOverride<Feature> {
+    /// Register the feature, even if it wasn't detected.
+    Add(Feature),
+    /// Remove the feature, even if it was detected.
+    Remove(Feature),
+}
+
+/// Identification of the physical device and driver used by a client, for logging and
+/// driver-specific workarounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdapterInfo {
+    /// The name of the device.
+    pub name: String,
+    /// The vendor ID, generally the PCI vendor ID.
+    pub vendor: u32,
+    /// The device ID, generally the PCI device ID.
+    pub device: u32,
+    /// The name and version of the driver.
+    pub driver: String,
+    /// The graphics API used to talk to the device, such as `vulkan` or `metal`.
+    pub backend: String,
+}
 
 /// Properties of what the device can do, like what [features](Feature) are
 /// supported by it and what its memory properties are.
@@ -8,6 +38,7 @@ pub struct DeviceProperties<Feature: Ord + Copy> {
     set: alloc::collections::BTreeSet<Feature>,
     memory: MemoryDeviceProperties,
     hardware: HardwareProperties,
+    adapter: Option<AdapterInfo>,
 }
 
 impl<Feature: Ord + Copy> DeviceProperties<Feature> {
@@ -26,6 +57,7 @@ impl<Feature: Ord + Copy> DeviceProperties<Feature> {
             set,
             memory: memory_props,
             hardware,
+            adapter: None,
         }
     }
 
@@ -34,6 +66,21 @@ impl<Feature: Ord + Copy> DeviceProperties<Feature> {
         self.set.contains(&feature)
     }
 
+    /// The [features](Feature) of the given set that aren't supported by the runtime, in the
+    /// order they were given.
+    pub fn missing_features(&self, features: &[Feature]) -> Vec<Feature> {
+        features
+            .iter()
+            .filter(|feature| !self.feature_enabled(**feature))
+            .copied()
+            .collect()
+    }
+
+    /// Iterate over every [feature](Feature) supported by the runtime.
+    pub fn features(&self) -> impl Iterator<Item = Feature> + '_ {
+        self.set.iter().copied()
+    }
+
     /// Register a [feature](Feature) supported by the compute server.
     ///
     /// This should only be used by a [runtime](Runtime) when initializing a device.
@@ -41,6 +88,34 @@ impl<Feature: Ord + Copy> DeviceProperties<Feature> {
         self.set.insert(feature)
     }
 
+    /// Remove a [feature](Feature) that isn't supported after all.
+    ///
+    /// This should only be used by a [runtime](Runtime) when initializing a device.
+    pub fn unregister_feature(&mut self, feature: Feature) -> bool {
+        self.set.remove(&feature)
+    }
+
+    /// Set the information about the device and its driver.
+    ///
+    /// This should only be used by a [runtime](Runtime) when initializing a device.
+    pub fn set_adapter_info(&mut self, info: AdapterInfo) {
+        self.adapter = Some(info);
+    }
+
+    /// Apply the given overrides in order, after the detected features were registered.
+    pub fn apply_overrides(&mut self, overrides: &[FeatureOverride<Feature>]) {
+        for feature_override in overrides {
+            match *feature_override {
+                FeatureOverride::Add(feature) => {
+                    self.register_feature(feature);
+                }
+                FeatureOverride::Remove(feature) => {
+                    self.unregister_feature(feature);
+                }
+            }
+        }
+    }
+
     /// The memory properties of this client.
     pub fn memory_properties(&self) -> &MemoryDeviceProperties {
         &self.memory
@@ -50,4 +125,167 @@ impl<Feature: Ord + Copy> DeviceProperties<Feature> {
     pub fn hardware_properties(&self) -> &HardwareProperties {
         &self.hardware
     }
+
+    /// The device and driver of this client, when the runtime exposes them.
+    pub fn adapter_info(&self) -> Option<&AdapterInfo> {
+        self.adapter.as_ref()
+    }
+}
+
+/// A summary of everything known about the device, one property per line, to paste in bug
+/// reports.
+impl<Feature: Ord + Copy + Debug> Display for DeviceProperties<Feature> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.adapter {
+            Some(adapter) => {
+                writeln!(
+                    f,
+                    "Adapter: {} (vendor {:#06x}, device {:#06x})",
+                    adapter.name, adapter.vendor, adapter.device
+                )?;
+                writeln!(f, "Driver: {} ({})", adapter.driver, adapter.backend)?;
+            }
+            None => writeln!(f, "Adapter: unknown")?,
+        }
+
+        let hardware = &self.hardware;
+        writeln!(f, "Hardware:")?;
+        write!(
+            f,
+            "  plane size: {}..={}",
+            hardware.plane_size_min, hardware.plane_size_max
+        )?;
+        match hardware.plane_size_actual {
+            Some(actual) => writeln!(f, " (measured {actual})")?,
+            None => writeln!(f)?,
+        }
+        writeln!(f, "  max bindings: {}", hardware.max_bindings)?;
+        writeln!(
+            f,
+            "  max shared memory: {} bytes",
+            hardware.max_shared_memory_bytes
+        )?;
+        writeln!(f, "  max cube count: {:?}", hardware.max_cube_count)?;
+
+        let memory = &self.memory;
+        writeln!(f, "Memory:")?;
+        match memory.total_memory {
+            Some(total) => writeln!(f, "  total: {total} bytes")?,
+            None => writeln!(f, "  total: unknown")?,
+        }
+        writeln!(f, "  max page size: {} bytes", memory.max_page_size)?;
+        writeln!(f, "  alignment: {} bytes", memory.alignment)?;
+
+        write!(f, "Features:")?;
+        if self.set.is_empty() {
+            write!(f, " none")?;
+        }
+        for feature in self.set.iter() {
+            write!(f, "\n  {feature:?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_are_applied_in_order() {
+        let mut props = DeviceProperties::new(
+            &[1u32, 2],
+            MemoryDeviceProperties {
+                max_page_size: 1024,
+                alignment: 32,
+                total_memory: None,
+            },
+            HardwareProperties {
+                plane_size_min: 32,
+                plane_size_max: 32,
+                plane_size_actual: None,
+                max_bindings: 8,
+                max_shared_memory_bytes: 1024,
+                max_cube_count: (1, 1, 1),
+            },
+        );
+
+        props.apply_overrides(&[
+            FeatureOverride::Remove(1),
+            FeatureOverride::Add(3),
+            FeatureOverride::Add(4),
+            FeatureOverride::Remove(4),
+        ]);
+
+        assert_eq!(props.features().collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn missing_features_keeps_the_requested_order() {
+        let props = DeviceProperties::new(
+            &[1u32, 3],
+            MemoryDeviceProperties {
+                max_page_size: 1024,
+                alignment: 32,
+                total_memory: None,
+            },
+            HardwareProperties {
+                plane_size_min: 32,
+                plane_size_max: 32,
+                plane_size_actual: None,
+                max_bindings: 8,
+                max_shared_memory_bytes: 1024,
+                max_cube_count: (1, 1, 1),
+            },
+        );
+
+        assert_eq!(props.missing_features(&[4, 1, 2, 3]), vec![4, 2]);
+        assert!(props.missing_features(&[3, 1]).is_empty());
+    }
+
+    #[test]
+    fn display_lists_every_property() {
+        let mut props = DeviceProperties::new(
+            &[3u32, 1],
+            MemoryDeviceProperties {
+                max_page_size: 1024,
+                alignment: 32,
+                total_memory: Some(4096),
+            },
+            HardwareProperties {
+                plane_size_min: 16,
+                plane_size_max: 32,
+                plane_size_actual: Some(32),
+                max_bindings: 8,
+                max_shared_memory_bytes: 1024,
+                max_cube_count: (65535, 65535, 1),
+            },
+        );
+        props.set_adapter_info(AdapterInfo {
+            name: "Test GPU".into(),
+            vendor: 0x8086,
+            device: 0x56a0,
+            driver: "test 1.0".into(),
+            backend: "vulkan".into(),
+        });
+
+        assert_eq!(
+            alloc::format!("{props}"),
+            "Adapter: Test GPU (vendor 0x8086, device 0x56a0)
+Driver: test 1.0 (vulkan)
+Hardware:
+  plane size: 16..=32 (measured 32)
+  max bindings: 8
+  max shared memory: 1024 bytes
+  max cube count: (65535, 65535, 1)
+Memory:
+  total: 4096 bytes
+  max page size: 1024 bytes
+  alignment: 32 bytes
+Features:
+  1
+  3"
+        );
+    }
 }
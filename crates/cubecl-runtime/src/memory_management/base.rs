@@ -4,6 +4,8 @@ use alloc::{format, string::String};
 /// Amount of memory in use by this allocator
 /// and statistics on how much memory is reserved and
 /// wasted in total.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemoryUsage {
     /// The number of allocations currently active.
     pub number_allocs: u64,
@@ -34,6 +36,46 @@ impl MemoryUsage {
     }
 }
 
+/// Counters of the allocations done by a memory pool, to follow its behavior over time.
+///
+/// Unlike [MemoryUsage], which is a snapshot computed on demand, the counters are updated on every
+/// allocation and are cheap enough to always be enabled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllocCounters {
+    /// The number of pages allocated on the storage.
+    pub allocations: u64,
+    /// The number of pages deallocated from the storage.
+    pub deallocations: u64,
+    /// The number of reservations that reused memory already allocated by the pool, instead of
+    /// allocating a new page.
+    pub cache_hits: u64,
+    /// The highest number of bytes allocated on the storage by the pool at once.
+    pub bytes_high_water_mark: u64,
+    bytes_allocated: u64,
+}
+
+impl AllocCounters {
+    pub(crate) fn record_alloc(&mut self, size: u64) {
+        self.allocations += 1;
+        self.bytes_allocated += size;
+        self.bytes_high_water_mark = u64::max(self.bytes_high_water_mark, self.bytes_allocated);
+    }
+
+    pub(crate) fn record_dealloc(&mut self, size: u64) {
+        self.deallocations += 1;
+        self.bytes_allocated -= size;
+    }
+
+    pub(crate) fn record_cache_hit(&mut self) {
+        self.cache_hits += 1;
+    }
+
+    pub(crate) fn bytes_allocated(&self) -> u64 {
+        self.bytes_allocated
+    }
+}
+
 fn bytes_format(bytes: u64) -> String {
     let unit = 1000;
 
@@ -0,0 +1,69 @@
+use super::{throttle::PageAllowance, MemoryError};
+use crate::storage::{ComputeStorage, StorageHandle, StorageId};
+use alloc::format;
+
+/// A storage that refuses to allocate past the
+/// [residency budget](super::MemoryManagement::set_residency_budget) of the memory management, as
+/// if the device was out of memory, and to allocate more pages than the
+/// [throttle](super::MemoryConfiguration::Throttled) allows.
+pub(crate) struct BudgetStorage<'a, Storage> {
+    storage: &'a mut Storage,
+    budget: Option<u64>,
+    available: u64,
+    allowance: Option<PageAllowance>,
+}
+
+impl<'a, Storage: ComputeStorage> BudgetStorage<'a, Storage> {
+    /// Wraps the storage, allowing `budget - reserved` more bytes to be allocated, in at most the
+    /// pages of the `allowance`.
+    pub(crate) fn new(
+        storage: &'a mut Storage,
+        budget: Option<u64>,
+        reserved: u64,
+        allowance: Option<PageAllowance>,
+    ) -> Self {
+        let available = budget.map_or(u64::MAX, |budget| budget.saturating_sub(reserved));
+
+        Self {
+            storage,
+            budget,
+            available,
+            allowance,
+        }
+    }
+}
+
+impl<Storage: ComputeStorage> ComputeStorage for BudgetStorage<'_, Storage> {
+    type Resource = Storage::Resource;
+
+    const ALIGNMENT: u64 = Storage::ALIGNMENT;
+
+    fn get(&mut self, handle: &StorageHandle) -> Self::Resource {
+        self.storage.get(handle)
+    }
+
+    fn alloc(&mut self, size: u64) -> Result<StorageHandle, MemoryError> {
+        if let Some(allowance) = self.allowance.filter(|allowance| allowance.pages == 0) {
+            return Err(MemoryError::Throttled {
+                retry_after: allowance.retry_after,
+            });
+        }
+        if let Some(budget) = self.budget.filter(|_| size > self.available) {
+            return Err(MemoryError::OutOfMemory {
+                size,
+                reason: format!("the residency budget of {budget} bytes is reached"),
+            });
+        }
+
+        let handle = self.storage.alloc(size)?;
+        self.available -= size;
+        if let Some(allowance) = self.allowance.as_mut() {
+            allowance.pages -= 1;
+        }
+        Ok(handle)
+    }
+
+    fn dealloc(&mut self, id: StorageId) {
+        self.storage.dealloc(id)
+    }
+}
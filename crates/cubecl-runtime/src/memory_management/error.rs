@@ -0,0 +1,92 @@
+use alloc::string::String;
+use core::{
+    fmt::{Debug, Display},
+    time::Duration,
+};
+
+/// Errors that can happen when reserving memory.
+#[derive(Clone, PartialEq, Eq)]
+pub enum MemoryError {
+    /// The requested size is larger than the biggest page of any memory pool.
+    ///
+    /// Every allocation has to fit in a single page, since a binding is always one contiguous
+    /// range of a buffer. Bigger data has to be split into several allocations by the caller.
+    PageTooLarge {
+        /// The number of bytes requested.
+        requested: u64,
+        /// The biggest allocation the memory pools can handle.
+        max: u64,
+    },
+    /// The storage couldn't allocate a new page, because the device is out of memory.
+    ///
+    /// Releasing memory, for instance by dropping cached handles, may let the allocation succeed
+    /// when retried.
+    OutOfMemory {
+        /// The size of the page that couldn't be allocated.
+        size: u64,
+        /// The error reported by the device.
+        reason: String,
+    },
+    /// Every slot of a [ring buffer](super::PoolType::RingBuffer) pool is still in use.
+    ///
+    /// Slots used by work in flight count as in use until that work is done, so syncing or
+    /// dropping handles may let the reservation succeed when retried.
+    RingBufferFull {
+        /// The number of slots of the ring.
+        num_slots: u64,
+    },
+    /// A [throttled](super::MemoryConfiguration::Throttled) memory already allocated every page
+    /// allowed during the current period.
+    ///
+    /// The client waits for the next period outside of the server, then retries the reservation.
+    Throttled {
+        /// The time left until the next period.
+        retry_after: Duration,
+    },
+    /// The memory can't be reconfigured while some allocations are still alive.
+    LiveAllocations {
+        /// The number of allocations still in use.
+        count: u64,
+    },
+}
+
+impl Debug for MemoryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MemoryError::PageTooLarge { requested, max } => write!(
+                f,
+                "No memory pool big enough to reserve {requested} bytes, the maximum is {max} bytes. \
+                 Allocations can't span multiple pages, so the data has to be split into smaller \
+                 buffers."
+            ),
+            MemoryError::OutOfMemory { size, reason } => write!(
+                f,
+                "The device is out of memory, unable to allocate a page of {size} bytes: {reason}"
+            ),
+            MemoryError::RingBufferFull { num_slots } => write!(
+                f,
+                "All {num_slots} slots of the ring buffer pool are in use. Drop handles or wait \
+                 for the work using them to finish before reserving another slot."
+            ),
+            MemoryError::Throttled { retry_after } => write!(
+                f,
+                "The allocation throttle allows no more pages during this period, retry the \
+                 reservation in {retry_after:?}."
+            ),
+            MemoryError::LiveAllocations { count } => write!(
+                f,
+                "Unable to reconfigure the memory while {count} allocations are still in use. \
+                 Drop every handle before changing the memory configuration."
+            ),
+        }
+    }
+}
+
+impl Display for MemoryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MemoryError {}
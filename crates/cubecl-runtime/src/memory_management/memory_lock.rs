@@ -4,6 +4,14 @@ use crate::storage::StorageId;
 
 /// A set of storage buffers that are 'locked' and cannot be
 /// used for allocations currently.
+///
+/// Despite its name, this isn't a synchronization primitive and never blocks: allocations simply
+/// skip the locked storage and pick, or create, another one. Servers lock the storage of
+/// resources that may still be used by queued work, for instance in `get_resource` or when
+/// `create` copies data into a new buffer, and clear the set once the queue is flushed.
+///
+/// Contention between threads happens on the [channel](crate::channel::ComputeChannel) instead,
+/// which serializes every operation on the server, allocations included.
 #[derive(Debug, Default)]
 pub struct MemoryLock {
     locked: BTreeSet<StorageId>,
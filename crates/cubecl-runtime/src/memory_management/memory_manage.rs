@@ -1,16 +1,23 @@
 use alloc::collections::BTreeSet;
 
 use super::{
-    memory_pool::{ExclusiveMemoryPool, MemoryPool, SliceBinding, SliceHandle, SlicedPool},
-    MemoryConfiguration, MemoryDeviceProperties, MemoryLock, MemoryPoolOptions, MemoryUsage,
-    PoolType,
+    budget::BudgetStorage,
+    memory_pool::{
+        ExclusiveMemoryPool, MemoryPool, RingBufferPool, SliceBinding, SliceHandle, SliceId,
+        SlicedPool,
+    },
+    throttle::{PageAllowance, ThrottleState},
+    AllocCounters, AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError,
+    MemoryLock, MemoryPoolOptions, MemoryUsage, PageGrowth, PoolType,
 };
 use crate::storage::{ComputeStorage, StorageHandle};
 use alloc::vec::Vec;
+use hashbrown::HashMap;
 
 enum DynamicPool {
     Sliced(SlicedPool),
     Exclusive(ExclusiveMemoryPool),
+    RingBuffer(RingBufferPool),
 }
 
 // Bin sizes as per https://github.com/sebbbi/OffsetAllocator/blob/main/README.md
@@ -46,6 +53,7 @@ impl MemoryPool for DynamicPool {
         match self {
             DynamicPool::Sliced(m) => m.get(binding),
             DynamicPool::Exclusive(m) => m.get(binding),
+            DynamicPool::RingBuffer(m) => m.get(binding),
         }
     }
 
@@ -54,17 +62,23 @@ impl MemoryPool for DynamicPool {
         storage: &mut Storage,
         size: u64,
         locked: Option<&MemoryLock>,
-    ) -> SliceHandle {
+    ) -> Result<SliceHandle, MemoryError> {
         match self {
             DynamicPool::Sliced(m) => m.reserve(storage, size, locked),
             DynamicPool::Exclusive(m) => m.reserve(storage, size, locked),
+            DynamicPool::RingBuffer(m) => m.reserve(storage, size, locked),
         }
     }
 
-    fn alloc<Storage: ComputeStorage>(&mut self, storage: &mut Storage, size: u64) -> SliceHandle {
+    fn alloc<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        size: u64,
+    ) -> Result<SliceHandle, MemoryError> {
         match self {
             DynamicPool::Sliced(m) => m.alloc(storage, size),
             DynamicPool::Exclusive(m) => m.alloc(storage, size),
+            DynamicPool::RingBuffer(m) => m.alloc(storage, size),
         }
     }
 
@@ -72,6 +86,31 @@ impl MemoryPool for DynamicPool {
         match self {
             DynamicPool::Sliced(m) => m.get_memory_usage(),
             DynamicPool::Exclusive(m) => m.get_memory_usage(),
+            DynamicPool::RingBuffer(m) => m.get_memory_usage(),
+        }
+    }
+
+    fn live_allocations(&self, size: u64) -> u64 {
+        match self {
+            DynamicPool::Sliced(m) => m.live_allocations(size),
+            DynamicPool::Exclusive(m) => m.live_allocations(size),
+            DynamicPool::RingBuffer(m) => m.live_allocations(size),
+        }
+    }
+
+    fn has_free(&self, size: u64) -> bool {
+        match self {
+            DynamicPool::Sliced(m) => m.has_free(size),
+            DynamicPool::Exclusive(m) => m.has_free(size),
+            DynamicPool::RingBuffer(m) => m.has_free(size),
+        }
+    }
+
+    fn counters(&self) -> &AllocCounters {
+        match self {
+            DynamicPool::Sliced(m) => m.counters(),
+            DynamicPool::Exclusive(m) => m.counters(),
+            DynamicPool::RingBuffer(m) => m.counters(),
         }
     }
 
@@ -79,169 +118,488 @@ impl MemoryPool for DynamicPool {
         match self {
             DynamicPool::Sliced(m) => m.max_alloc_size(),
             DynamicPool::Exclusive(m) => m.max_alloc_size(),
+            DynamicPool::RingBuffer(m) => m.max_alloc_size(),
         }
     }
-    fn cleanup<Storage: ComputeStorage>(&mut self, storage: &mut Storage, alloc_nr: u64) {
+    fn cleanup<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        alloc_nr: u64,
+        force: bool,
+        locked: Option<&MemoryLock>,
+    ) {
         match self {
-            DynamicPool::Sliced(m) => m.cleanup(storage, alloc_nr),
-            DynamicPool::Exclusive(m) => m.cleanup(storage, alloc_nr),
+            DynamicPool::Sliced(m) => m.cleanup(storage, alloc_nr, force, locked),
+            DynamicPool::Exclusive(m) => m.cleanup(storage, alloc_nr, force, locked),
+            DynamicPool::RingBuffer(m) => m.cleanup(storage, alloc_nr, force, locked),
         }
     }
 }
 
-/// Reserves and keeps track of chunks of memory in the storage, and slices upon these chunks.
-pub struct MemoryManagement<Storage> {
-    pools: Vec<DynamicPool>,
-    storage: Storage,
-    alloc_reserve_count: u64,
+impl DynamicPool {
+    /// Deallocates every page of the pool, which must all be free.
+    fn dealloc_all<Storage: ComputeStorage>(&mut self, storage: &mut Storage) {
+        match self {
+            DynamicPool::Sliced(m) => m.dealloc_all(storage),
+            DynamicPool::Exclusive(m) => m.dealloc_all(storage),
+            DynamicPool::RingBuffer(m) => m.dealloc_all(storage),
+        }
+    }
 }
 
-impl<Storage: ComputeStorage> MemoryManagement<Storage> {
-    /// Creates the options from device limits.
-    pub fn from_configuration(
-        storage: Storage,
-        properties: MemoryDeviceProperties,
-        config: MemoryConfiguration,
-    ) -> Self {
-        let pools = match config {
-            #[cfg(not(exclusive_memory_only))]
-            MemoryConfiguration::SubSlices => {
-                // Round chunk size to be aligned.
-                let memory_alignment = properties.alignment;
-                let max_page = properties.max_page_size;
-
-                let mut pools = Vec::new();
+/// Creates one exclusive pool per bin size fitting in a page, using the given deallocation period
+/// for each page size.
+fn exclusive_pools(
+    properties: &MemoryDeviceProperties,
+    dealloc_period: impl Fn(u64) -> u64,
+) -> Vec<MemoryPoolOptions> {
+    // Round chunk size to be aligned.
+    let memory_alignment = properties.alignment;
+
+    // Add all bin sizes. Nb: because of alignment some buckets
+    // end up as the same size, so only want unique ones,
+    // but also keep the order, so a BTree will do.
+    let sizes: BTreeSet<_> = EXP_BIN_SIZES
+        .iter()
+        .copied()
+        .map(|size| size.next_multiple_of(memory_alignment))
+        .take_while(|&size| size < properties.max_page_size)
+        .collect();
+
+    sizes
+        .iter()
+        .map(|&s| MemoryPoolOptions {
+            page_size: s,
+            chunk_num_prealloc: 0,
+            pool_type: PoolType::ExclusivePages,
+            dealloc_period: Some(dealloc_period(s)),
+            alignment: None,
+            page_growth: PageGrowth::Constant,
+        })
+        .collect()
+}
+
+/// Create the pools described by the options, sorted by their maximum allocation size, along
+/// with their types and their indices in the options.
+fn create_pools<Storage: ComputeStorage>(
+    storage: &mut Storage,
+    options: &[MemoryPoolOptions],
+    memory_alignment: u64,
+) -> (Vec<DynamicPool>, Vec<PoolType>, Vec<usize>) {
+    let mut pools: Vec<_> = options
+        .iter()
+        .enumerate()
+        .map(|(index, options)| {
+            // Padding is computed with the alignment as a divisor.
+            assert_ne!(memory_alignment, 0, "The device alignment can't be zero");
+            if let Some(alignment) = options.alignment {
+                assert!(
+                    alignment.is_power_of_two(),
+                    "The alignment of a memory pool must be a non-zero power of two, got {alignment}"
+                );
+            }
+            let alignment = options.alignment.unwrap_or(memory_alignment);
+            assert_eq!(
+                alignment % memory_alignment,
+                0,
+                "The alignment of a memory pool must be a multiple of the device alignment"
+            );
+
+            let mut pool = match options.pool_type {
+                PoolType::SlicedPages {
+                    max_slice_size: max_slice,
+                } => DynamicPool::Sliced(SlicedPool::new(
+                    options.page_size,
+                    max_slice,
+                    alignment,
+                    options.page_growth,
+                )),
+                PoolType::ExclusivePages => DynamicPool::Exclusive(ExclusiveMemoryPool::new(
+                    options.page_size,
+                    alignment,
+                    options.dealloc_period.unwrap_or(u64::MAX),
+                )),
+                PoolType::RingBuffer {
+                    num_slots,
+                    slot_size,
+                } => DynamicPool::RingBuffer(RingBufferPool::new(num_slots, slot_size, alignment)),
+            };
+
+            // The slots of a ring are all allocated up front.
+            let prealloc = match options.pool_type {
+                PoolType::RingBuffer {
+                    num_slots,
+                    slot_size,
+                } => {
+                    assert_eq!(
+                        options.page_size, slot_size,
+                        "The page size of a ring buffer pool must be its slot size"
+                    );
+                    num_slots
+                }
+                _ => options.chunk_num_prealloc,
+            };
+            for _ in 0..prealloc {
+                if let Err(err) = pool.alloc(storage, options.page_size) {
+                    log::warn!("Stopped preallocating pages: {err}");
+                    break;
+                }
+            }
+
+            (pool, (options.pool_type.clone(), index))
+        })
+        .collect();
+
+    pools.sort_by(|(pool1, _), (pool2, _)| {
+        u64::cmp(&pool1.max_alloc_size(), &pool2.max_alloc_size())
+    });
+    let (pools, configs): (Vec<_>, Vec<_>) = pools.into_iter().unzip();
+    let (pool_types, pool_indices) = configs.into_iter().unzip();
+    (pools, pool_types, pool_indices)
+}
+
+/// The options of the pools created for a [memory configuration](MemoryConfiguration).
+fn pool_options(
+    properties: &MemoryDeviceProperties,
+    config: MemoryConfiguration,
+) -> Vec<MemoryPoolOptions> {
+    let mut pools = match config {
+        #[cfg(not(exclusive_memory_only))]
+        MemoryConfiguration::SubSlices => {
+            // Round chunk size to be aligned.
+            let memory_alignment = properties.alignment;
+            let max_page = properties.default_page_size();
+
+            let mut pools = Vec::new();
+            pools.push(MemoryPoolOptions {
+                page_size: max_page / memory_alignment * memory_alignment, // align the size to max_page.
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: max_page,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            });
+
+            const MB: u64 = 1024 * 1024;
+
+            let mut current = max_page;
+            while current >= 32 * MB {
+                current /= 4;
+                // Make sure every pool has an aligned size.
+                current = current.next_multiple_of(memory_alignment);
+
                 pools.push(MemoryPoolOptions {
-                    page_size: max_page / memory_alignment * memory_alignment, // align the size to max_page.
+                    page_size: current,
                     chunk_num_prealloc: 0,
+                    // Creating max slices lower than the chunk size reduces fragmentation.
                     pool_type: PoolType::SlicedPages {
-                        max_slice_size: max_page,
+                        max_slice_size: current / 2u64.pow(pools.len() as u32),
                     },
                     dealloc_period: None,
+                    alignment: None,
+                    page_growth: PageGrowth::Constant,
                 });
-
-                const MB: u64 = 1024 * 1024;
-
-                let mut current = max_page;
-                while current >= 32 * MB {
-                    current /= 4;
-                    // Make sure every pool has an aligned size.
-                    current = current.next_multiple_of(memory_alignment);
-
-                    pools.push(MemoryPoolOptions {
-                        page_size: current,
-                        chunk_num_prealloc: 0,
-                        // Creating max slices lower than the chunk size reduces fragmentation.
-                        pool_type: PoolType::SlicedPages {
-                            max_slice_size: current / 2u64.pow(pools.len() as u32),
-                        },
-                        dealloc_period: None,
-                    });
-                }
-                // Add in a pool for allocations that are smaller than the min alignment,
-                // as they can't use offsets at all (on wgpu at least).
+            }
+            // Add in a pool for allocations that are smaller than the min alignment,
+            // as they can't use offsets at all (on wgpu at least).
+            pools.push(MemoryPoolOptions {
+                page_size: memory_alignment,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            });
+            // Allocations bigger than the default page size get their own page.
+            if max_page < properties.max_page_size {
                 pools.push(MemoryPoolOptions {
-                    page_size: memory_alignment,
+                    page_size: properties.max_page_size / memory_alignment * memory_alignment,
                     chunk_num_prealloc: 0,
                     pool_type: PoolType::ExclusivePages,
                     dealloc_period: None,
+                    alignment: None,
+                    page_growth: PageGrowth::Constant,
                 });
-                pools
             }
-            MemoryConfiguration::ExclusivePages => {
-                // Round chunk size to be aligned.
-                let memory_alignment = properties.alignment;
-
-                // Add all bin sizes. Nb: because of alignment some buckets
-                // end up as the same size, so only want unique ones,
-                // but also keep the order, so a BTree will do.
-                let sizes: BTreeSet<_> = EXP_BIN_SIZES
-                    .iter()
-                    .copied()
-                    .map(|size| size.next_multiple_of(memory_alignment))
-                    .take_while(|&size| size < properties.max_page_size)
-                    .collect();
-
-                // Add in one pool for all massive allocations.
-                sizes
-                    .iter()
-                    .map(|&s| {
-                        // Bigger buckets will logically have less slices, and are a bigger win
-                        // to deallocate, so make the deallocation period roughly proportional to
-                        // alloc size.
-                        //
-                        // This also +- follows zipfs law https://en.wikipedia.org/wiki/Zipf%27s_law
-                        // which is an ok assumption for the distribution of allocations.
-                        //
-                        // This ranges from:
-                        //   128 bytes, 8389608 allocations (aka almost never)
-                        //   10kb, 105857 allocations
-                        //   1MB, 2024 allocations
-                        //   100MB+, 1000-1011 allocations
-                        let base_period = 1000;
-                        let dealloc_period = base_period + 1024 * MB as u64 / s;
-
-                        MemoryPoolOptions {
-                            page_size: s,
-                            chunk_num_prealloc: 0,
-                            pool_type: PoolType::ExclusivePages,
-                            dealloc_period: Some(dealloc_period),
-                        }
-                    })
-                    .collect()
+            pools
+        }
+        MemoryConfiguration::ExclusivePages => {
+            exclusive_pools(properties, |size| {
+                // Bigger buckets will logically have less slices, and are a bigger win
+                // to deallocate, so make the deallocation period roughly proportional to
+                // alloc size.
+                //
+                // This also +- follows zipfs law https://en.wikipedia.org/wiki/Zipf%27s_law
+                // which is an ok assumption for the distribution of allocations.
+                //
+                // This ranges from:
+                //   128 bytes, 8389608 allocations (aka almost never)
+                //   10kb, 105857 allocations
+                //   1MB, 2024 allocations
+                //   100MB+, 1000-1011 allocations
+                let base_period = 1000;
+                base_period + 1024 * MB as u64 / size
+            })
+        }
+        MemoryConfiguration::Conservative => {
+            exclusive_pools(properties, |size| {
+                // Same distribution as the exclusive pages, but around 30 times sooner.
+                //
+                // This ranges from:
+                //   128 bytes, 262154 allocations
+                //   10kb, 3286 allocations
+                //   1MB, 42 allocations
+                //   100MB+, 10 allocations
+                let base_period = 10;
+                base_period + 32 * MB as u64 / size
+            })
+        }
+        MemoryConfiguration::Custom(pool_settings) => pool_settings,
+        MemoryConfiguration::Throttled { config, .. } => pool_options(properties, *config),
+    };
+
+    // Pages can't grow past the memory of the device either.
+    let max_page_size = properties
+        .total_memory
+        .map_or(properties.max_page_size, |total| {
+            total.min(properties.max_page_size)
+        });
+    clamp_page_growth(&mut pools, max_page_size);
+    if let Some(total_memory) = properties.total_memory {
+        clamp_preallocation(&mut pools, total_memory);
+    }
+
+    for pool in pools.iter() {
+        log::trace!("Using memory pool: \n {pool:?}");
+    }
+
+    pools
+}
+
+/// Lower the size [geometric growth](PageGrowth::Geometric) stops at to the biggest page the
+/// device can allocate.
+fn clamp_page_growth(pools: &mut [MemoryPoolOptions], device_max_page_size: u64) {
+    for pool in pools.iter_mut() {
+        let page_size = pool.page_size;
+        if let PageGrowth::Geometric { max_page_size, .. } = &mut pool.page_growth {
+            if *max_page_size > device_max_page_size {
+                log::warn!(
+                    "Pages can't grow to {max_page_size} bytes, the device is limited to {device_max_page_size} bytes"
+                );
+                // Growth never shrinks pages below the initial size.
+                *max_page_size = device_max_page_size.max(page_size);
+            }
+        }
+    }
+}
+
+/// The sizes of the pages a pool allocates one after the other, following its
+/// [page growth](MemoryPoolOptions::page_growth).
+fn page_sizes(pool: &MemoryPoolOptions) -> impl Iterator<Item = u64> {
+    let growth = match (&pool.pool_type, pool.page_growth) {
+        (
+            PoolType::SlicedPages { .. },
+            PageGrowth::Geometric {
+                factor,
+                max_page_size,
+            },
+        ) => Some((factor, max_page_size)),
+        _ => None,
+    };
+
+    core::iter::successors(Some(pool.page_size.max(1)), move |&size| match growth {
+        Some((factor, max_page_size)) => Some(
+            (((size as f64) * factor) as u64)
+                .min(max_page_size)
+                .max(size),
+        ),
+        None => Some(size),
+    })
+}
+
+/// Lower the [preallocated pages](MemoryPoolOptions::chunk_num_prealloc) of the pools so that
+/// together they fit in the memory of the device, instead of failing to allocate them.
+fn clamp_preallocation(pools: &mut [MemoryPoolOptions], total_memory: u64) {
+    let mut available = total_memory;
+
+    for pool in pools.iter_mut() {
+        // Growing pools preallocate bigger and bigger pages.
+        let mut fitting = 0;
+        let mut reserved = 0;
+        for size in page_sizes(pool) {
+            if fitting == pool.chunk_num_prealloc || reserved + size > available {
+                break;
             }
-            MemoryConfiguration::Custom(pool_settings) => pool_settings,
-        };
+            fitting += 1;
+            reserved += size;
+        }
 
-        for pool in pools.iter() {
-            log::trace!("Using memory pool: \n {pool:?}");
+        if pool.chunk_num_prealloc > fitting {
+            log::warn!(
+                "Preallocating {} pages from {} bytes exceeds the memory of the device, only {fitting} are preallocated",
+                pool.chunk_num_prealloc,
+                pool.page_size
+            );
+            pool.chunk_num_prealloc = fitting;
         }
+        available -= reserved;
+    }
+}
+
+/// Reserves and keeps track of chunks of memory in the storage, and slices upon these chunks.
+pub struct MemoryManagement<Storage> {
+    pools: Vec<DynamicPool>,
+    /// The type of every pool, in the same order as `pools`.
+    pool_types: Vec<PoolType>,
+    /// The index of every pool in the options it was created from, in the same order as `pools`.
+    pool_indices: Vec<usize>,
+    storage: Storage,
+    alloc_reserve_count: u64,
+    /// The number of bytes the pools may keep reserved before unused pages are evicted.
+    residency_budget: Option<u64>,
+    /// The reservations recorded since the trace was started.
+    trace: Option<AllocationTrace>,
+    /// The highest number of bytes allocated on the storage since the peak was last reset.
+    peak_bytes: u64,
+    /// The storage registered with [register_external](Self::register_external), by slice.
+    external: HashMap<SliceId, (SliceHandle, StorageHandle)>,
+    /// The growth limit of the pools, from a [throttled](MemoryConfiguration::Throttled)
+    /// configuration.
+    throttle: Option<ThrottleState>,
+    /// Whether reservations currently ignore the throttle.
+    throttle_bypass: bool,
+}
+
+impl<Storage: ComputeStorage> MemoryManagement<Storage> {
+    /// Creates the options from device limits.
+    pub fn from_configuration(
+        storage: Storage,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Self {
+        let throttle = config.throttle();
+        let pools = pool_options(&properties, config);
 
-        Self::new(storage, pools, properties.alignment)
+        let mut memory_management = Self::new(storage, pools, properties.alignment);
+        memory_management.throttle = throttle.map(ThrottleState::new);
+        memory_management
     }
 
     /// Creates a new instance using the given storage, merging_strategy strategy and slice strategy.
     pub fn new(mut storage: Storage, pools: Vec<MemoryPoolOptions>, memory_alignment: u64) -> Self {
-        let mut pools: Vec<_> = pools
-            .iter()
-            .map(|options| {
-                let mut pool = match options.pool_type {
-                    PoolType::SlicedPages {
-                        max_slice_size: max_slice,
-                    } => DynamicPool::Sliced(SlicedPool::new(
-                        options.page_size,
-                        max_slice,
-                        memory_alignment,
-                    )),
-                    PoolType::ExclusivePages => DynamicPool::Exclusive(ExclusiveMemoryPool::new(
-                        options.page_size,
-                        memory_alignment,
-                        options.dealloc_period.unwrap_or(u64::MAX),
-                    )),
-                };
-
-                for _ in 0..options.chunk_num_prealloc {
-                    pool.alloc(&mut storage, options.page_size);
-                }
+        let (pools, pool_types, pool_indices) =
+            create_pools(&mut storage, &pools, memory_alignment);
 
-                pool
-            })
-            .collect();
-
-        pools.sort_by(|pool1, pool2| u64::cmp(&pool1.max_alloc_size(), &pool2.max_alloc_size()));
-
-        Self {
+        let mut memory_management = Self {
             pools,
+            pool_types,
+            pool_indices,
             storage,
             alloc_reserve_count: 0,
+            residency_budget: None,
+            trace: None,
+            peak_bytes: 0,
+            external: HashMap::new(),
+            throttle: None,
+            throttle_bypass: false,
+        };
+        // Preallocated pages count towards the peak.
+        memory_management.record_peak();
+        memory_management
+    }
+
+    /// Replace the memory pools with the ones of a new [configuration](MemoryConfiguration).
+    ///
+    /// Every page of the current pools is deallocated, so this fails with
+    /// [MemoryError::LiveAllocations] when some of their memory is still in use. The storage
+    /// may still be used by kernels in flight, so the server has to wait for them before.
+    pub fn reconfigure(
+        &mut self,
+        properties: &MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError> {
+        let count = self.memory_usage().number_allocs;
+        if count > 0 {
+            return Err(MemoryError::LiveAllocations { count });
+        }
+
+        for pool in self.pools.iter_mut() {
+            pool.dealloc_all(&mut self.storage);
+        }
+        self.release_external();
+
+        // Keep the period of an unchanged throttle going, so reconfiguring doesn't reset it.
+        let throttle = config.throttle();
+        if self.throttle.as_ref().map(ThrottleState::throttle) != throttle {
+            self.throttle = throttle.map(ThrottleState::new);
         }
+
+        let options = pool_options(properties, config);
+        let (pools, pool_types, pool_indices) =
+            create_pools(&mut self.storage, &options, properties.alignment);
+        self.pools = pools;
+        self.pool_types = pool_types;
+        self.pool_indices = pool_indices;
+        self.record_peak();
+        mem_trace!("Reconfigured the memory pools");
+
+        Ok(())
     }
 
     /// Cleanup allocations in pools that are deemed unnecessary.
-    pub fn cleanup(&mut self) {
+    ///
+    /// When `force` is true, unused pages are deallocated right away, instead of waiting for the
+    /// [deallocation period](MemoryPoolOptions::dealloc_period). This returns the memory to the
+    /// system, but the pages have to be allocated again when they are needed, so it may hurt
+    /// performance if called in the middle of a computation.
+    ///
+    /// Pages whose storage is in `exclude` are never deallocated, so the pages used by work in
+    /// flight have to be locked there. Without `force`, pages freed since the previous cleanup
+    /// are kept until a later cleanup sees them still unused.
+    pub fn cleanup(&mut self, force: bool, exclude: Option<&MemoryLock>) {
+        for pool in self.pools.iter_mut() {
+            pool.cleanup(&mut self.storage, self.alloc_reserve_count, force, exclude);
+        }
+        self.release_external();
+        mem_trace!("Cleanup (force: {force}), {:?}", self.memory_usage());
+    }
+
+    /// Give the free [external](Self::register_external) slices back to the storage.
+    fn release_external(&mut self) {
+        self.external.retain(|_, (slice, storage)| {
+            let free = slice.is_free();
+            if free {
+                self.storage.dealloc(storage.id);
+            }
+            !free
+        });
+    }
+
+    /// Merge the adjacent free slices of the pools using [sliced pages](PoolType::SlicedPages),
+    /// and return the number of bytes given back to the storage.
+    ///
+    /// Bigger reservations then fit in the existing pages, and the pages without any used slice
+    /// are deallocated, following the rules of a [forced cleanup](Self::cleanup).
+    ///
+    /// This isn't a compaction: live slices are never relocated, since the memory management
+    /// can't copy their data between pages, so a single small slice still keeps its whole page
+    /// alive. Slices are free once their handles are dropped, so call this between workloads when
+    /// few tensors are alive.
+    pub fn coalesce_free_slices(&mut self) -> u64 {
+        let before = self.memory_usage().bytes_reserved;
+
         for pool in self.pools.iter_mut() {
-            pool.cleanup(&mut self.storage, self.alloc_reserve_count);
+            if let DynamicPool::Sliced(pool) = pool {
+                pool.merge_free_slices();
+                pool.cleanup(&mut self.storage, self.alloc_reserve_count, true, None);
+            }
         }
+
+        let reclaimed = before - self.memory_usage().bytes_reserved;
+        mem_trace!("Coalesced the free slices, reclaimed {reclaimed} bytes");
+        reclaimed
     }
 
     /// Returns the storage from the specified binding
@@ -249,10 +607,26 @@ impl<Storage: ComputeStorage> MemoryManagement<Storage> {
         self.pools
             .iter()
             .find_map(|p| p.get(&binding))
+            .or_else(|| self.external.get(binding.id()).map(|(_, storage)| storage))
             .expect("No handle found in memory pools")
             .clone()
     }
 
+    /// Create a slice for storage that wasn't allocated by the pools, such as a buffer shared
+    /// with another library.
+    ///
+    /// The slice is never reused by the pools and doesn't count in the
+    /// [memory usage](Self::memory_usage). Once every handle to it is dropped, the next
+    /// [cleanup](Self::cleanup) gives its id back to the storage with
+    /// [dealloc](ComputeStorage::dealloc), so the storage has to release external memory without
+    /// freeing it.
+    pub fn register_external(&mut self, storage: StorageHandle) -> SliceHandle {
+        let slice = SliceHandle::new();
+        mem_trace!("Registered {} external bytes", storage.size());
+        self.external.insert(*slice.id(), (slice.clone(), storage));
+        slice
+    }
+
     /// Returns the resource from the storage at the specified handle
     pub fn get_resource(
         &mut self,
@@ -273,18 +647,165 @@ impl<Storage: ComputeStorage> MemoryManagement<Storage> {
     }
 
     /// Finds a spot in memory for a resource with the given size in bytes, and returns a handle to it
-    pub fn reserve(&mut self, size: u64, exclude: Option<&MemoryLock>) -> SliceHandle {
+    ///
+    /// # Errors
+    ///
+    /// Returns [MemoryError::PageTooLarge] when no memory pool can hold `size` bytes,
+    /// [MemoryError::OutOfMemory] when the storage can't allocate a new page, or when it would
+    /// exceed the [residency budget](Self::set_residency_budget), and
+    /// [MemoryError::RingBufferFull] when every slot of the ring buffer pool holding `size` bytes
+    /// is in use, and [MemoryError::Throttled] when a new page would exceed the
+    /// [throttle](MemoryConfiguration::Throttled) of the current period.
+    pub fn reserve(
+        &mut self,
+        size: u64,
+        exclude: Option<&MemoryLock>,
+    ) -> Result<SliceHandle, MemoryError> {
         // If this happens every nanosecond, counts overflows after 585 years, so not worth thinking too
         // hard about overflow here.
         self.alloc_reserve_count += 1;
 
-        // Find first pool where size <= p.max_alloc with a binary search.
-        let pool_ind = self.pools.partition_point(|p| size > p.max_alloc_size());
-        let pool = &mut self.pools[pool_ind];
-        if pool.max_alloc_size() < size {
-            panic!("No memory pool big enough to reserve {size} bytes.");
+        let pool_ind = self.find_pool(size)?;
+        let throttle = self.throttle.as_mut().filter(|_| !self.throttle_bypass);
+        let allowance = throttle.and_then(ThrottleState::allowance);
+        let pages_before = self.pages_allocated();
+        let handle = self.within_budget(exclude, allowance, |pools, storage| {
+            pools[pool_ind].reserve(storage, size, exclude)
+        })?;
+        self.record_peak();
+        let pages = self.pages_allocated() - pages_before;
+        if let Some(throttle) = self.throttle.as_mut().filter(|_| !self.throttle_bypass) {
+            throttle.record_pages(pages);
+        }
+        if let Some(trace) = self.trace.as_mut() {
+            let live = self
+                .pools
+                .iter()
+                .map(|pool| pool.live_allocations(size))
+                .sum();
+            trace.record(size, live);
+        }
+        mem_trace!(
+            "Reserved {size} bytes in pool {:?}, {:?}",
+            self.pool_types[pool_ind],
+            self.memory_usage()
+        );
+
+        Ok(handle)
+    }
+
+    /// Run an operation allocating pages on a storage limited to the residency budget and to the
+    /// page `allowance` of the throttle.
+    ///
+    /// When the operation runs out of memory, the unused pages are evicted and it is tried once
+    /// more.
+    fn within_budget<R>(
+        &mut self,
+        exclude: Option<&MemoryLock>,
+        allowance: Option<PageAllowance>,
+        op: impl Fn(&mut [DynamicPool], &mut BudgetStorage<'_, Storage>) -> Result<R, MemoryError>,
+    ) -> Result<R, MemoryError> {
+        let attempt = |this: &mut Self| {
+            let reserved = this.memory_usage().bytes_reserved;
+            let mut storage = BudgetStorage::new(
+                &mut this.storage,
+                this.residency_budget,
+                reserved,
+                allowance,
+            );
+            op(&mut this.pools, &mut storage)
+        };
+
+        match attempt(self) {
+            Err(MemoryError::OutOfMemory { .. }) if self.residency_budget.is_some() => {
+                mem_trace!("Over the residency budget, evicting unused pages");
+                self.cleanup(true, exclude);
+                attempt(self)
+            }
+            result => result,
+        }
+    }
+
+    /// Start recording the reservations in a new [trace](AllocationTrace).
+    ///
+    /// Recording counts the live allocations of the reserved size on every reservation, so it
+    /// should only be enabled for a run made to size the pools of the next ones.
+    pub fn start_trace(&mut self) {
+        self.trace = Some(AllocationTrace::default());
+    }
+
+    /// Stop recording the reservations and return the trace, if one was started.
+    pub fn stop_trace(&mut self) -> Option<AllocationTrace> {
+        self.trace.take()
+    }
+
+    /// Limit the number of bytes kept reserved by the pools, or remove the limit with `None`.
+    ///
+    /// When a reservation or a preallocation needs a page that doesn't fit in the budget, the
+    /// unused pages are evicted as with a [forced cleanup](Self::cleanup). If the page still
+    /// doesn't fit, it fails with [MemoryError::OutOfMemory].
+    ///
+    /// Pages holding live handles are never evicted: their data isn't spilled to the host, so
+    /// the caller has to drop handles to stay within the budget. Lowering the budget below the
+    /// memory held by live handles doesn't release it, but no page is allocated until enough of
+    /// them are dropped.
+    pub fn set_residency_budget(&mut self, budget: Option<u64>) {
+        self.residency_budget = budget;
+
+        if let Some(budget) = budget {
+            if self.memory_usage().bytes_reserved > budget {
+                mem_trace!("Over the residency budget of {budget} bytes, evicting unused pages");
+                self.cleanup(true, None);
+            }
+        }
+    }
+
+    /// Let reservations allocate as many pages as they need while `bypass` is true, ignoring the
+    /// [throttle](MemoryConfiguration::Throttled) of the configuration.
+    ///
+    /// The pages allocated while bypassing don't count towards the budget of the throttle.
+    pub fn bypass_throttle(&mut self, bypass: bool) {
+        self.throttle_bypass = bypass;
+    }
+
+    /// The number of pages allocated on the storage by the pools since their creation.
+    fn pages_allocated(&self) -> u64 {
+        self.pools
+            .iter()
+            .map(|pool| pool.counters().allocations)
+            .sum()
+    }
+
+    /// Allocate a page in the pool that would hold an allocation of `size` bytes, so later
+    /// reservations of up to `size` bytes don't have to allocate.
+    ///
+    /// Nothing is allocated when the pool already has a free slice of at least `size` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [MemoryError::PageTooLarge] when no memory pool can hold `size` bytes, and
+    /// [MemoryError::OutOfMemory] when the storage can't allocate the page, or when it would
+    /// exceed the [residency budget](Self::set_residency_budget).
+    pub fn preallocate(&mut self, size: u64) -> Result<(), MemoryError> {
+        let pool_ind = self.find_pool(size)?;
+        if self.pools[pool_ind].has_free(size) {
+            mem_trace!("Skipped preallocating {size} bytes, a free slice already fits them");
+            return Ok(());
         }
-        pool.reserve(&mut self.storage, size, exclude)
+        // Allocating the biggest size the pool handles creates a full page, which any future
+        // reservation in this pool can reuse.
+        let page_size = self.pools[pool_ind].max_alloc_size();
+        self.within_budget(None, None, |pools, storage| {
+            pools[pool_ind].alloc(storage, page_size)
+        })?;
+        self.record_peak();
+        mem_trace!(
+            "Preallocated {page_size} bytes in pool {:?}, {:?}",
+            self.pool_types[pool_ind],
+            self.memory_usage()
+        );
+
+        Ok(())
     }
 
     /// Bypass the memory allocation algorithm to allocate data directly.
@@ -292,14 +813,46 @@ impl<Storage: ComputeStorage> MemoryManagement<Storage> {
     /// # Notes
     ///
     /// Can be useful for servers that want specific control over memory.
-    pub fn alloc(&mut self, size: u64) -> SliceHandle {
-        // Find first pool where size <= p.max_alloc with a binary search.
+    pub fn alloc(&mut self, size: u64) -> Result<SliceHandle, MemoryError> {
+        let pool_ind = self.find_pool(size)?;
+        let handle = self.pools[pool_ind].alloc(&mut self.storage, size)?;
+        self.record_peak();
+        mem_trace!(
+            "Allocated {size} bytes in pool {:?}, {:?}",
+            self.pool_types[pool_ind],
+            self.memory_usage()
+        );
+
+        Ok(handle)
+    }
+
+    /// The index of the pool serving allocations of `size` bytes, among the
+    /// [pool options](MemoryPoolOptions) the memory management was created with.
+    ///
+    /// Follows the same routing as [reserve](Self::reserve): the pool with the smallest maximum
+    /// allocation size that fits. With a [memory configuration](MemoryConfiguration) other than
+    /// [custom](MemoryConfiguration::Custom), the index is the one of the generated pools.
+    ///
+    /// # Errors
+    ///
+    /// Returns [MemoryError::PageTooLarge] when no memory pool can hold `size` bytes.
+    pub fn pool_for_size(&self, size: u64) -> Result<usize, MemoryError> {
+        let pool_ind = self.find_pool(size)?;
+        Ok(self.pool_indices[pool_ind])
+    }
+
+    /// Find the index of the first pool where `size <= max_alloc_size` with a binary search.
+    fn find_pool(&self, size: u64) -> Result<usize, MemoryError> {
         let pool_ind = self.pools.partition_point(|p| size > p.max_alloc_size());
-        let pool = &mut self.pools[pool_ind];
-        if pool.max_alloc_size() < size {
-            panic!("No memory pool big enough to alloc {size} bytes.");
+
+        if pool_ind < self.pools.len() {
+            Ok(pool_ind)
+        } else {
+            Err(MemoryError::PageTooLarge {
+                requested: size,
+                max: self.pools.last().map(|p| p.max_alloc_size()).unwrap_or(0),
+            })
         }
-        pool.alloc(&mut self.storage, size)
     }
 
     /// Bypass the memory allocation algorithm to deallocate data directly.
@@ -338,6 +891,51 @@ impl<Storage: ComputeStorage> MemoryManagement<Storage> {
         )
     }
 
+    /// Whether some pools place several allocations on the same page.
+    ///
+    /// When they do, different handles may share a buffer. This reflects the pools that were
+    /// actually created, not only the requested [configuration](MemoryConfiguration).
+    pub fn uses_sub_slices(&self) -> bool {
+        self.pool_types
+            .iter()
+            .any(|pool_type| matches!(pool_type, PoolType::SlicedPages { .. }))
+    }
+
+    /// The allocation counters of every pool, along with the type of the pool.
+    pub fn counters(&self) -> Vec<(PoolType, AllocCounters)> {
+        self.pool_types
+            .iter()
+            .cloned()
+            .zip(self.pools.iter().map(|pool| pool.counters().clone()))
+            .collect()
+    }
+
+    /// The highest number of bytes reserved on the storage by the pools since the memory management
+    /// was created, or since the last call to [reset_peak](Self::reset_peak).
+    ///
+    /// The peak is updated on every allocation, so it catches short spikes that polling the
+    /// [memory usage](Self::memory_usage) would miss.
+    pub fn peak_bytes(&self) -> u64 {
+        self.peak_bytes
+    }
+
+    /// Restart the [peak](Self::peak_bytes) from the number of bytes currently reserved, so the
+    /// peak of the following allocations can be measured on its own.
+    pub fn reset_peak(&mut self) {
+        self.peak_bytes = self.bytes_allocated();
+    }
+
+    fn bytes_allocated(&self) -> u64 {
+        self.pools
+            .iter()
+            .map(|pool| pool.counters().bytes_allocated())
+            .sum()
+    }
+
+    fn record_peak(&mut self) {
+        self.peak_bytes = u64::max(self.peak_bytes, self.bytes_allocated());
+    }
+
     /// Print out a report of the current memory usage.
     pub fn print_memory_usage(&self) {
         #[cfg(feature = "std")]
@@ -360,7 +958,43 @@ impl<Storage> core::fmt::Debug for MemoryManagement<Storage> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{memory_management::MemoryManagement, storage::BytesStorage};
+    use crate::{
+        memory_management::{test_util, MemoryManagement, TracedSize},
+        storage::BytesStorage,
+    };
+
+    #[test]
+    #[cfg(not(exclusive_memory_only))]
+    fn sliced_allocations_are_aligned_and_disjoint() {
+        let alignment = 32;
+        let mut memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: 8 * 1024 * 1024,
+                alignment,
+                total_memory: None,
+            },
+            MemoryConfiguration::SubSlices,
+        );
+
+        test_util::check_allocations(&mut memory_management, alignment, 64 * 1024, 500, 42);
+    }
+
+    #[test]
+    fn exclusive_allocations_are_aligned_and_disjoint() {
+        let alignment = 32;
+        let mut memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: 8 * 1024 * 1024,
+                alignment,
+                total_memory: None,
+            },
+            MemoryConfiguration::ExclusivePages,
+        );
+
+        test_util::check_allocations(&mut memory_management, alignment, 64 * 1024, 500, 7);
+    }
 
     // Test pools with slices.
     #[test]
@@ -371,10 +1005,11 @@ mod tests {
             MemoryDeviceProperties {
                 max_page_size: 128 * 1024 * 1024,
                 alignment: 32,
+                total_memory: None,
             },
             MemoryConfiguration::SubSlices,
         );
-        let handle = memory_management.reserve(10, None);
+        let handle = memory_management.reserve(10, None).unwrap();
         let other_ref = handle.clone();
         assert!(!handle.can_mut(), "Handle can't be mut when multiple ref.");
         drop(other_ref);
@@ -394,13 +1029,15 @@ mod tests {
                     max_slice_size: page_size,
                 },
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             }],
             32,
         );
 
         let alloc_size = 512;
-        let _handle = memory_management.reserve(alloc_size, None);
-        let _new_handle = memory_management.reserve(alloc_size, None);
+        let _handle = memory_management.reserve(alloc_size, None).unwrap();
+        let _new_handle = memory_management.reserve(alloc_size, None).unwrap();
 
         let usage = memory_management.memory_usage();
         assert_eq!(usage.number_allocs, 2);
@@ -422,14 +1059,16 @@ mod tests {
                     max_slice_size: page_size,
                 },
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             }],
             32,
         );
 
         let alloc_size = 512;
-        let _handle = memory_management.reserve(alloc_size, None);
+        let _handle = memory_management.reserve(alloc_size, None).unwrap();
         drop(_handle);
-        let _new_handle = memory_management.reserve(alloc_size, None);
+        let _new_handle = memory_management.reserve(alloc_size, None).unwrap();
 
         let usage = memory_management.memory_usage();
         assert_eq!(usage.number_allocs, 1);
@@ -437,6 +1076,43 @@ mod tests {
         assert_eq!(usage.bytes_reserved, page_size);
     }
 
+    #[test]
+    fn forced_cleanup_deallocates_unused_pages() {
+        let page_size = 512;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: page_size,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        let handle = memory_management.reserve(page_size, None).unwrap();
+        let _kept = memory_management.reserve(page_size, None).unwrap();
+        drop(handle);
+
+        // A single forced cleanup frees the page, without an earlier cleanup marking it.
+        memory_management.cleanup(true, None);
+        let usage = memory_management.memory_usage();
+        assert_eq!(usage.number_allocs, 1);
+        assert_eq!(usage.bytes_reserved, page_size);
+
+        // Pages are allocated again once they are needed.
+        let _new_handle = memory_management.reserve(page_size, None).unwrap();
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            2 * page_size
+        );
+    }
+
     #[test]
     fn alloc_allocs_new_storage() {
         let page_size = 1024;
@@ -450,13 +1126,15 @@ mod tests {
                     max_slice_size: page_size,
                 },
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             }],
             32,
         );
 
         let alloc_size = 768;
-        let _handle = memory_management.reserve(alloc_size, None);
-        let _new_handle = memory_management.reserve(alloc_size, None);
+        let _handle = memory_management.reserve(alloc_size, None).unwrap();
+        let _new_handle = memory_management.reserve(alloc_size, None).unwrap();
 
         let usage = memory_management.memory_usage();
         assert_eq!(usage.number_allocs, 2);
@@ -476,36 +1154,133 @@ mod tests {
                     max_slice_size: page_size,
                 },
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             }],
             50,
         );
         let alloc_size = 40;
-        let _handle = memory_management.reserve(alloc_size, None);
-        let _new_handle = memory_management.reserve(alloc_size, None);
+        let _handle = memory_management.reserve(alloc_size, None).unwrap();
+        let _new_handle = memory_management.reserve(alloc_size, None).unwrap();
         let usage = memory_management.memory_usage();
         // Each slice should be aligned to 60 bytes, so 20 padding bytes.
         assert_eq!(usage.bytes_padding, 10 * 2);
     }
 
     #[test]
-    fn allocs_on_correct_page() {
-        let sizes = [100, 200, 300, 400];
-
-        let pools = sizes
-            .iter()
-            .map(|&size| MemoryPoolOptions {
-                page_size: size,
-                chunk_num_prealloc: 0,
-                pool_type: PoolType::SlicedPages {
-                    max_slice_size: size,
+    fn pool_alignment_overrides_device_alignment() {
+        let page_size = 1024;
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: page_size,
+                },
+                dealloc_period: None,
+                alignment: Some(256),
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+        let _handle = memory_management.reserve(100, None).unwrap();
+        let new_handle = memory_management.reserve(100, None).unwrap();
+
+        let storage = memory_management.get(new_handle.binding());
+        assert_eq!(storage.offset(), 256);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pool_alignment_must_be_multiple_of_device_alignment() {
+        MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size: 1024,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: Some(48),
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero power of two")]
+    fn pool_alignment_must_not_be_zero() {
+        MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size: 1024,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: Some(0),
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+    }
+
+    #[test]
+    fn sliced_pages_grow_geometrically() {
+        let page_size = 1024;
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: page_size,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Geometric {
+                    factor: 2.0,
+                    max_page_size: 3000,
+                },
+            }],
+            32,
+        );
+
+        // Fills the first page, then the second one which is twice as big.
+        let _handles: Vec<_> = (0..3)
+            .map(|_| memory_management.reserve(page_size, None).unwrap())
+            .collect();
+        assert_eq!(memory_management.memory_usage().bytes_reserved, 1024 + 2048);
+
+        // The next page is capped, and aligned.
+        let _handle = memory_management.reserve(page_size, None).unwrap();
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            1024 + 2048 + 2976
+        );
+    }
+
+    #[test]
+    fn allocs_on_correct_page() {
+        let sizes = [100, 200, 300, 400];
+
+        let pools = sizes
+            .iter()
+            .map(|&size| MemoryPoolOptions {
+                page_size: size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: size,
                 },
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             })
             .collect();
         let mut memory_management = MemoryManagement::new(BytesStorage::default(), pools, 10);
         // Allocate one thing on each page.
         let alloc_sizes = [50, 150, 250, 350];
-        let _handles = alloc_sizes.map(|s| memory_management.reserve(s, None));
+        let _handles = alloc_sizes.map(|s| memory_management.reserve(s, None).unwrap());
 
         let usage = memory_management.memory_usage();
 
@@ -522,19 +1297,20 @@ mod tests {
             MemoryDeviceProperties {
                 max_page_size: 128 * 1024 * 1024,
                 alignment: 32,
+                total_memory: None,
             },
             MemoryConfiguration::SubSlices,
         );
         // Allocate a bunch
         let handles: Vec<_> = (0..5)
-            .map(|i| memory_management.reserve(1000 * (i + 1), None))
+            .map(|i| memory_management.reserve(1000 * (i + 1), None).unwrap())
             .collect();
         let usage_before = memory_management.memory_usage();
         // Deallocate
         drop(handles);
         // Reallocate
         let _new_handles: Vec<_> = (0..5)
-            .map(|i| memory_management.reserve(1000 * (i + 1), None))
+            .map(|i| memory_management.reserve(1000 * (i + 1), None).unwrap())
             .collect();
         let usage_after = memory_management.memory_usage();
         assert_eq!(usage_before.number_allocs, usage_after.number_allocs);
@@ -551,6 +1327,7 @@ mod tests {
             MemoryDeviceProperties {
                 max_page_size: 128 * 1024 * 1024,
                 alignment: 32,
+                total_memory: None,
             },
             MemoryConfiguration::SubSlices,
         );
@@ -558,7 +1335,7 @@ mod tests {
         let sizes = [50, 1000, 100, 5000, 200, 10000, 300];
         let handles: Vec<_> = sizes
             .iter()
-            .map(|&size| memory_management.reserve(size, None))
+            .map(|&size| memory_management.reserve(size, None).unwrap())
             .collect();
         let usage_before = memory_management.memory_usage();
         // Deallocate every other allocation
@@ -567,32 +1344,769 @@ mod tests {
         }
         // Reallocate similar sizes
         for &size in &sizes[0..sizes.len() / 2] {
-            memory_management.reserve(size, None);
+            memory_management.reserve(size, None).unwrap();
         }
         let usage_after = memory_management.memory_usage();
         // Check that we haven't increased our memory usage significantly
         assert!(usage_after.bytes_reserved <= (usage_before.bytes_reserved as f64 * 1.1) as u64);
     }
 
+    #[test]
+    fn ring_buffer_reuses_slots() {
+        let slot_size = 512;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size: slot_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::RingBuffer {
+                    num_slots: 2,
+                    slot_size,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        // Both slots are allocated with the pool.
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            2 * slot_size
+        );
+
+        for _ in 0..10 {
+            let _handle = memory_management.reserve(256, None).unwrap();
+        }
+
+        let usage = memory_management.memory_usage();
+        assert_eq!(usage.number_allocs, 0);
+        assert_eq!(usage.bytes_reserved, 2 * slot_size);
+
+        let _first = memory_management.reserve(256, None).unwrap();
+        let _second = memory_management.reserve(256, None).unwrap();
+        assert!(matches!(
+            memory_management.reserve(256, None),
+            Err(MemoryError::RingBufferFull { num_slots: 2 })
+        ));
+    }
+
     // Test pools without slices. More or less same as tests above.
     #[test]
     fn noslice_test_handle_mutability() {
         let mem_props = MemoryDeviceProperties {
             max_page_size: 128 * 1024 * 1024,
             alignment: 32,
+            total_memory: None,
         };
         let mut memory_management = MemoryManagement::from_configuration(
             BytesStorage::default(),
             mem_props,
             MemoryConfiguration::ExclusivePages,
         );
-        let handle = memory_management.reserve(10, None);
+        let handle = memory_management.reserve(10, None).unwrap();
         let other_ref = handle.clone();
         assert!(!handle.can_mut(), "Handle can't be mut when multiple ref.");
         drop(other_ref);
         assert!(handle.can_mut(), "Handle should be mut when only one ref.");
     }
 
+    #[test]
+    #[cfg(not(exclusive_memory_only))]
+    fn sub_slices_pages_follow_total_memory() {
+        let mut memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: 128 * 1024 * 1024,
+                alignment: 32,
+                total_memory: Some(256 * 1024 * 1024),
+            },
+            MemoryConfiguration::SubSlices,
+        );
+
+        let _small = memory_management.reserve(1024, None).unwrap();
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            16 * 1024 * 1024
+        );
+
+        // Allocations bigger than the default pages are still possible.
+        let _big = memory_management.reserve(64 * 1024 * 1024, None).unwrap();
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            (16 + 128) * 1024 * 1024
+        );
+    }
+
+    #[test]
+    fn sub_slices_follow_the_pool_types() {
+        let exclusive = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: 128 * 1024 * 1024,
+                alignment: 32,
+                total_memory: None,
+            },
+            MemoryConfiguration::ExclusivePages,
+        );
+        assert!(!exclusive.uses_sub_slices());
+
+        let sliced = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size: 1024,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: 1024,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+        assert!(sliced.uses_sub_slices());
+    }
+
+    #[test]
+    fn preallocation_is_clamped_to_the_device_memory() {
+        let page_size = 1024;
+        let pool = |chunk_num_prealloc| MemoryPoolOptions {
+            page_size,
+            chunk_num_prealloc,
+            pool_type: PoolType::ExclusivePages,
+            dealloc_period: None,
+            alignment: None,
+            page_growth: PageGrowth::Constant,
+        };
+
+        let memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: page_size,
+                alignment: 32,
+                total_memory: Some(6 * page_size),
+            },
+            MemoryConfiguration::Custom(vec![pool(4), pool(4)]),
+        );
+
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            6 * page_size
+        );
+    }
+
+    #[test]
+    fn preallocation_of_growing_pages_is_clamped_to_the_device_memory() {
+        let page_size = 1024;
+        let memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: 1024 * 1024,
+                alignment: 32,
+                total_memory: Some(4 * page_size),
+            },
+            MemoryConfiguration::Custom(vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 4,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: page_size,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Geometric {
+                    factor: 2.0,
+                    max_page_size: 1024 * 1024,
+                },
+            }]),
+        );
+
+        // Pages of 1024 and 2048 bytes fit, the next one of 4096 bytes doesn't.
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            3 * page_size
+        );
+    }
+
+    #[test]
+    fn geometric_growth_is_clamped_to_the_device_max_page_size() {
+        let page_size = 1024;
+        let mut memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: 2048,
+                alignment: 32,
+                total_memory: None,
+            },
+            MemoryConfiguration::Custom(vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: page_size,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Geometric {
+                    factor: 4.0,
+                    max_page_size: 1024 * 1024,
+                },
+            }]),
+        );
+
+        // The second page would be 4096 bytes, more than the device allows.
+        let _handles: Vec<_> = (0..2)
+            .map(|_| memory_management.reserve(page_size, None).unwrap())
+            .collect();
+        assert_eq!(memory_management.memory_usage().bytes_reserved, 1024 + 2048);
+    }
+
+    #[test]
+    fn pool_for_size_follows_the_configured_order() {
+        let pool = |page_size| MemoryPoolOptions {
+            page_size,
+            chunk_num_prealloc: 0,
+            pool_type: PoolType::ExclusivePages,
+            dealloc_period: None,
+            alignment: None,
+            page_growth: PageGrowth::Constant,
+        };
+        let memory_management =
+            MemoryManagement::new(BytesStorage::default(), vec![pool(4096), pool(256)], 32);
+
+        assert_eq!(memory_management.pool_for_size(100).unwrap(), 1);
+        assert_eq!(memory_management.pool_for_size(256).unwrap(), 1);
+        assert_eq!(memory_management.pool_for_size(1000).unwrap(), 0);
+        assert!(matches!(
+            memory_management.pool_for_size(5000),
+            Err(MemoryError::PageTooLarge {
+                requested: 5000,
+                max: 4096
+            })
+        ));
+    }
+
+    #[test]
+    fn residency_budget_evicts_unused_pages() {
+        let page_size = 512;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        let first = memory_management.reserve(page_size, None).unwrap();
+        let second = memory_management.reserve(page_size, None).unwrap();
+        drop(first);
+        drop(second);
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            2 * page_size
+        );
+
+        memory_management.set_residency_budget(Some(page_size));
+        assert_eq!(memory_management.memory_usage().bytes_reserved, 0);
+
+        // Live handles are never evicted, so a page past the budget can't be allocated.
+        let first = memory_management.reserve(page_size, None).unwrap();
+        assert!(matches!(
+            memory_management.reserve(page_size, None),
+            Err(MemoryError::OutOfMemory { .. })
+        ));
+        assert_eq!(memory_management.memory_usage().bytes_reserved, page_size);
+
+        // Dropping the handle makes its page reusable within the budget.
+        drop(first);
+        assert!(memory_management.reserve(page_size, None).is_ok());
+    }
+
+    #[test]
+    fn reserve_evicts_unused_pages_to_fit_the_residency_budget() {
+        let pool = |page_size| MemoryPoolOptions {
+            page_size,
+            chunk_num_prealloc: 0,
+            pool_type: PoolType::ExclusivePages,
+            dealloc_period: None,
+            alignment: None,
+            page_growth: PageGrowth::Constant,
+        };
+        let mut memory_management =
+            MemoryManagement::new(BytesStorage::default(), vec![pool(512), pool(1024)], 32);
+        memory_management.set_residency_budget(Some(1024));
+
+        let small = memory_management.reserve(512, None).unwrap();
+        drop(small);
+
+        // The unused small page is evicted so the big one fits in the budget.
+        let _big = memory_management.reserve(1024, None).unwrap();
+        assert_eq!(memory_management.memory_usage().bytes_reserved, 1024);
+    }
+
+    #[cfg(allocation_throttle)]
+    fn throttled(max_pages: u64, period: core::time::Duration) -> MemoryManagement<BytesStorage> {
+        let page_size = 512;
+        let config = MemoryConfiguration::Throttled {
+            config: Box::new(MemoryConfiguration::Custom(vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }])),
+            throttle: crate::memory_management::AllocationThrottle { max_pages, period },
+        };
+
+        MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: page_size,
+                alignment: 32,
+                total_memory: None,
+            },
+            config,
+        )
+    }
+
+    #[test]
+    #[cfg(allocation_throttle)]
+    fn throttle_refuses_pages_past_the_budget_until_the_next_period() {
+        let period = core::time::Duration::from_millis(50);
+        let mut memory_management = throttled(2, period);
+
+        let _first = memory_management.reserve(512, None).unwrap();
+        let second = memory_management.reserve(512, None).unwrap();
+        let result = memory_management.reserve(512, None);
+        assert!(matches!(
+            result,
+            Err(MemoryError::Throttled { retry_after }) if retry_after <= period
+        ));
+
+        // Reusing a page doesn't need the throttle.
+        core::mem::drop(second);
+        let _reused = memory_management.reserve(512, None).unwrap();
+
+        std::thread::sleep(period);
+        let _third = memory_management.reserve(512, None).unwrap();
+        assert_eq!(memory_management.memory_usage().bytes_reserved, 3 * 512);
+    }
+
+    #[test]
+    #[cfg(allocation_throttle)]
+    fn throttle_can_be_bypassed() {
+        // Waiting for the period would time out the test.
+        let mut memory_management = throttled(1, core::time::Duration::from_secs(3600));
+
+        memory_management.bypass_throttle(true);
+        let _handles: Vec<_> = (0..4)
+            .map(|_| memory_management.reserve(512, None).unwrap())
+            .collect();
+        assert_eq!(memory_management.memory_usage().bytes_reserved, 4 * 512);
+    }
+
+    #[test]
+    fn coalesce_free_slices_merges_them_and_frees_empty_pages() {
+        let page_size = 1024;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: page_size,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        let _first = memory_management.reserve(256, None).unwrap();
+        let second = memory_management.reserve(256, None).unwrap();
+        let third = memory_management.reserve(256, None).unwrap();
+        let _fourth = memory_management.reserve(256, None).unwrap();
+        let full_page = memory_management.reserve(page_size, None).unwrap();
+        drop(second);
+        drop(third);
+        drop(full_page);
+
+        // Pages are only deallocated once a cleanup saw them unused, so they aren't in flight.
+        memory_management.cleanup(false, None);
+        assert_eq!(memory_management.coalesce_free_slices(), page_size);
+        assert_eq!(memory_management.memory_usage().bytes_reserved, page_size);
+
+        // The two free slices in the middle of the first page were merged.
+        let _merged = memory_management.reserve(512, None).unwrap();
+        assert_eq!(memory_management.memory_usage().bytes_reserved, page_size);
+    }
+
+    #[test]
+    #[cfg(not(exclusive_memory_only))]
+    fn trace_records_peak_live_allocations() {
+        let mut memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: 128 * 1024 * 1024,
+                alignment: 32,
+                total_memory: None,
+            },
+            MemoryConfiguration::SubSlices,
+        );
+        memory_management.start_trace();
+
+        let first = memory_management.reserve(100, None).unwrap();
+        let second = memory_management.reserve(100, None).unwrap();
+        drop((first, second));
+        let _third = memory_management.reserve(100, None).unwrap();
+        let _other = memory_management.reserve(4096, None).unwrap();
+
+        let trace = memory_management.stop_trace().unwrap();
+        assert_eq!(
+            trace.sizes.get(&100),
+            Some(&TracedSize {
+                reservations: 3,
+                peak_live: 2
+            })
+        );
+        assert_eq!(trace.sizes.get(&4096).unwrap().peak_live, 1);
+        assert_eq!(memory_management.stop_trace(), None);
+    }
+
+    #[test]
+    #[cfg(not(exclusive_memory_only))]
+    fn reconfigure_refuses_live_allocations() {
+        let properties = MemoryDeviceProperties {
+            max_page_size: 128 * 1024 * 1024,
+            alignment: 32,
+            total_memory: None,
+        };
+        let mut memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            properties.clone(),
+            MemoryConfiguration::SubSlices,
+        );
+        let handle = memory_management.reserve(1024, None).unwrap();
+
+        assert_eq!(
+            memory_management.reconfigure(&properties, MemoryConfiguration::ExclusivePages),
+            Err(MemoryError::LiveAllocations { count: 1 })
+        );
+        assert!(memory_management.uses_sub_slices());
+
+        drop(handle);
+        memory_management
+            .reconfigure(&properties, MemoryConfiguration::ExclusivePages)
+            .unwrap();
+        assert!(!memory_management.uses_sub_slices());
+        assert_eq!(memory_management.memory_usage().bytes_reserved, 0);
+
+        // The new pools are usable right away.
+        let _handle = memory_management.reserve(1024, None).unwrap();
+        assert_eq!(memory_management.memory_usage().number_allocs, 1);
+    }
+
+    #[test]
+    fn noslice_forced_cleanup_ignores_dealloc_period() {
+        let page_size = 512;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: Some(u64::MAX),
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        let handle = memory_management.reserve(page_size, None).unwrap();
+        let _kept = memory_management.reserve(page_size, None).unwrap();
+        drop(handle);
+
+        memory_management.cleanup(false, None);
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            2 * page_size
+        );
+
+        memory_management.cleanup(true, None);
+        assert_eq!(memory_management.memory_usage().bytes_reserved, page_size);
+    }
+
+    #[test]
+    fn forced_cleanup_keeps_locked_pages() {
+        let page_size = 512;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        let handle = memory_management.reserve(page_size, None).unwrap();
+        let mut locked = MemoryLock::default();
+        locked.add_locked(memory_management.get(handle.clone().binding()).id);
+        drop(handle);
+
+        memory_management.cleanup(true, Some(&locked));
+        assert_eq!(memory_management.memory_usage().bytes_reserved, page_size);
+
+        locked.clear_locked();
+        memory_management.cleanup(true, Some(&locked));
+        assert_eq!(memory_management.memory_usage().bytes_reserved, 0);
+    }
+
+    #[test]
+    fn conservative_preset_deallocates_unused_pages_quickly() {
+        let mut memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: 128 * 1024 * 1024,
+                alignment: 32,
+                total_memory: None,
+            },
+            MemoryConfiguration::Conservative,
+        );
+
+        let handle = memory_management.reserve(1024 * 1024, None).unwrap();
+        drop(handle);
+
+        let mut small_handles = Vec::new();
+        for _ in 0..128 {
+            small_handles.push(memory_management.reserve(32, None).unwrap());
+            // The first cleanup marks the page, and a later one deallocates it.
+            memory_management.cleanup(false, None);
+        }
+
+        let usage = memory_management.memory_usage();
+        assert_eq!(usage.number_allocs, 128);
+        assert!(usage.bytes_reserved < 1024 * 1024);
+    }
+
+    #[test]
+    fn reserve_larger_than_any_page_fails() {
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size: 1024,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        assert_eq!(
+            memory_management.reserve(2048, None).unwrap_err(),
+            MemoryError::PageTooLarge {
+                requested: 2048,
+                max: 1024
+            }
+        );
+    }
+
+    #[test]
+    fn reserve_reports_storage_out_of_memory() {
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::with_limit(1536),
+            vec![MemoryPoolOptions {
+                page_size: 1024,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: 1024,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        let first = memory_management.reserve(1024, None).unwrap();
+        assert!(matches!(
+            memory_management.reserve(1024, None),
+            Err(MemoryError::OutOfMemory { size: 1024, .. })
+        ));
+
+        // Once the first page is free again, the reservation can reuse it.
+        drop(first);
+        assert!(memory_management.reserve(1024, None).is_ok());
+    }
+
+    #[test]
+    fn preallocated_page_is_reused() {
+        let page_size = 2048;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        memory_management.preallocate(1024).unwrap();
+        assert_eq!(memory_management.memory_usage().bytes_reserved, page_size);
+
+        let _handle = memory_management.reserve(1024, None).unwrap();
+        let usage = memory_management.memory_usage();
+        assert_eq!(usage.number_allocs, 1);
+        assert_eq!(usage.bytes_reserved, page_size);
+    }
+
+    #[test]
+    fn preallocate_skips_pools_with_a_free_slice() {
+        let page_size = 2048;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::SlicedPages {
+                    max_slice_size: page_size,
+                },
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        memory_management.preallocate(1024).unwrap();
+        // The first page has room left for this reservation.
+        let _handle = memory_management.reserve(512, None).unwrap();
+        memory_management.preallocate(1024).unwrap();
+        assert_eq!(memory_management.memory_usage().bytes_reserved, page_size);
+
+        // The free part of the page is too small for a whole page.
+        memory_management.preallocate(page_size).unwrap();
+        assert_eq!(
+            memory_management.memory_usage().bytes_reserved,
+            2 * page_size
+        );
+    }
+
+    #[test]
+    fn counters_track_allocations_and_reuse() {
+        let page_size = 2048;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        let first = memory_management.reserve(512, None).unwrap();
+        let _second = memory_management.reserve(512, None).unwrap();
+        drop(first);
+        let third = memory_management.reserve(512, None).unwrap();
+        core::mem::drop(third);
+        memory_management.cleanup(true, None);
+
+        let counters = memory_management.counters();
+        assert_eq!(counters.len(), 1);
+        assert!(matches!(counters[0].0, PoolType::ExclusivePages));
+        let counters = &counters[0].1;
+        assert_eq!(counters.allocations, 2);
+        assert_eq!(counters.deallocations, 1);
+        assert_eq!(counters.cache_hits, 1);
+        assert_eq!(counters.bytes_high_water_mark, page_size * 2);
+    }
+
+    #[test]
+    fn peak_bytes_follow_the_highest_reservation_since_reset() {
+        let page_size = 2048;
+
+        let mut memory_management = MemoryManagement::new(
+            BytesStorage::default(),
+            vec![MemoryPoolOptions {
+                page_size,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }],
+            32,
+        );
+
+        let first = memory_management.reserve(512, None).unwrap();
+        let second = memory_management.reserve(512, None).unwrap();
+        drop(first);
+        core::mem::drop(second);
+        memory_management.cleanup(true, None);
+        assert_eq!(memory_management.memory_usage().bytes_reserved, 0);
+        assert_eq!(memory_management.peak_bytes(), page_size * 2);
+
+        memory_management.reset_peak();
+        assert_eq!(memory_management.peak_bytes(), 0);
+
+        let _third = memory_management.reserve(512, None).unwrap();
+        assert_eq!(memory_management.peak_bytes(), page_size);
+    }
+
+    #[test]
+    fn external_storage_is_released_once_unused() {
+        let mut memory_management = MemoryManagement::from_configuration(
+            BytesStorage::default(),
+            MemoryDeviceProperties {
+                max_page_size: 128 * 1024 * 1024,
+                alignment: 32,
+                total_memory: None,
+            },
+            MemoryConfiguration::ExclusivePages,
+        );
+        let storage = memory_management.storage().alloc(64).unwrap();
+        let id = storage.id;
+
+        let handle = memory_management.register_external(storage);
+        assert_eq!(memory_management.get(handle.clone().binding()).id, id);
+        assert_eq!(memory_management.memory_usage().number_allocs, 0);
+
+        memory_management.cleanup(true, None);
+        assert_eq!(memory_management.external.len(), 1);
+
+        core::mem::drop(handle);
+        memory_management.cleanup(true, None);
+        assert!(memory_management.external.is_empty());
+    }
+
     #[test]
     fn noslice_alloc_two_chunk() {
         let page_size = 2048;
@@ -604,13 +2118,15 @@ mod tests {
                 chunk_num_prealloc: 0,
                 pool_type: PoolType::ExclusivePages,
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             }],
             32,
         );
 
         let alloc_size = 512;
-        let _handle = memory_management.reserve(alloc_size, None);
-        let _new_handle = memory_management.reserve(alloc_size, None);
+        let _handle = memory_management.reserve(alloc_size, None).unwrap();
+        let _new_handle = memory_management.reserve(alloc_size, None).unwrap();
 
         let usage = memory_management.memory_usage();
         assert_eq!(usage.number_allocs, 2);
@@ -628,14 +2144,16 @@ mod tests {
                 chunk_num_prealloc: 0,
                 pool_type: PoolType::ExclusivePages,
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             }],
             32,
         );
 
         let alloc_size = 512;
-        let _handle = memory_management.reserve(alloc_size, None);
+        let _handle = memory_management.reserve(alloc_size, None).unwrap();
         drop(_handle);
-        let _new_handle = memory_management.reserve(alloc_size, None);
+        let _new_handle = memory_management.reserve(alloc_size, None).unwrap();
 
         let usage = memory_management.memory_usage();
         assert_eq!(usage.number_allocs, 1);
@@ -653,13 +2171,15 @@ mod tests {
                 chunk_num_prealloc: 0,
                 pool_type: PoolType::ExclusivePages,
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             }],
             32,
         );
 
         let alloc_size = 768;
-        let _handle = memory_management.reserve(alloc_size, None);
-        let _new_handle = memory_management.reserve(alloc_size, None);
+        let _handle = memory_management.reserve(alloc_size, None).unwrap();
+        let _new_handle = memory_management.reserve(alloc_size, None).unwrap();
         let usage = memory_management.memory_usage();
         assert_eq!(usage.number_allocs, 2);
         assert_eq!(usage.bytes_in_use, alloc_size * 2);
@@ -676,12 +2196,14 @@ mod tests {
                 chunk_num_prealloc: 0,
                 pool_type: PoolType::ExclusivePages,
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             }],
             50,
         );
         let alloc_size = 40;
-        let _handle = memory_management.reserve(alloc_size, None);
-        let _new_handle = memory_management.reserve(alloc_size, None);
+        let _handle = memory_management.reserve(alloc_size, None).unwrap();
+        let _new_handle = memory_management.reserve(alloc_size, None).unwrap();
         let usage = memory_management.memory_usage();
         // Each slice should be aligned to 60 bytes, so 20 padding bytes.
         assert_eq!(usage.bytes_padding, 10 * 2);
@@ -698,12 +2220,14 @@ mod tests {
                     max_slice_size: size,
                 },
                 dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
             })
             .collect();
         let mut memory_management = MemoryManagement::new(BytesStorage::default(), pools, 10);
         // Allocate one thing on each page.
         let alloc_sizes = [50, 150, 250, 350];
-        let _handles = alloc_sizes.map(|s| memory_management.reserve(s, None));
+        let _handles = alloc_sizes.map(|s| memory_management.reserve(s, None).unwrap());
         let usage = memory_management.memory_usage();
         // Total memory should be size of all pages, and no more.
         assert_eq!(usage.bytes_in_use, alloc_sizes.iter().sum::<u64>());
@@ -716,19 +2240,20 @@ mod tests {
             MemoryDeviceProperties {
                 max_page_size: 128 * 1024 * 1024,
                 alignment: 32,
+                total_memory: None,
             },
             MemoryConfiguration::ExclusivePages,
         );
         // Allocate a bunch
         let handles: Vec<_> = (0..5)
-            .map(|i| memory_management.reserve(1000 * (i + 1), None))
+            .map(|i| memory_management.reserve(1000 * (i + 1), None).unwrap())
             .collect();
         let usage_before = memory_management.memory_usage();
         // Deallocate
         drop(handles);
         // Reallocate
         let _new_handles: Vec<_> = (0..5)
-            .map(|i| memory_management.reserve(1000 * (i + 1), None))
+            .map(|i| memory_management.reserve(1000 * (i + 1), None).unwrap())
             .collect();
         let usage_after = memory_management.memory_usage();
         assert_eq!(usage_before.number_allocs, usage_after.number_allocs);
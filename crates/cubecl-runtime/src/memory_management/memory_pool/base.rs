@@ -1,7 +1,7 @@
 use super::{SliceBinding, SliceHandle, SliceId};
-use crate::memory_management::MemoryLock;
+use crate::memory_management::{MemoryError, MemoryLock};
 use crate::{
-    memory_management::MemoryUsage,
+    memory_management::{AllocCounters, MemoryUsage},
     storage::{ComputeStorage, StorageHandle},
 };
 
@@ -45,11 +45,40 @@ pub trait MemoryPool {
         storage: &mut Storage,
         size: u64,
         locked: Option<&MemoryLock>,
-    ) -> SliceHandle;
+    ) -> Result<SliceHandle, MemoryError>;
 
-    fn alloc<Storage: ComputeStorage>(&mut self, storage: &mut Storage, size: u64) -> SliceHandle;
+    /// Allocates a new page holding `size` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [MemoryError::OutOfMemory] when the storage can't allocate the page.
+    fn alloc<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        size: u64,
+    ) -> Result<SliceHandle, MemoryError>;
 
     fn get_memory_usage(&self) -> MemoryUsage;
 
-    fn cleanup<Storage: ComputeStorage>(&mut self, storage: &mut Storage, alloc_nr: u64);
+    /// The number of slices of exactly `size` bytes that are currently in use.
+    fn live_allocations(&self, size: u64) -> u64;
+
+    /// Whether a free slice can hold `size` bytes, so reserving them doesn't allocate a page.
+    fn has_free(&self, size: u64) -> bool;
+
+    /// The allocation counters of the pool since its creation.
+    fn counters(&self) -> &AllocCounters;
+
+    /// Deallocate pages that are no longer used.
+    ///
+    /// A page is only deallocated once a previous cleanup already saw it unused, since memory
+    /// freed in between may still be used by work in flight, and never when it's `locked`.
+    /// When `force` is true, those pages are deallocated regardless of the deallocation period.
+    fn cleanup<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        alloc_nr: u64,
+        force: bool,
+        locked: Option<&MemoryLock>,
+    );
 }
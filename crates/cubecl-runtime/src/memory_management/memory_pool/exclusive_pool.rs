@@ -1,6 +1,6 @@
 use super::{calculate_padding, MemoryPool, Slice, SliceBinding, SliceHandle, SliceId};
 use crate::{
-    memory_management::{MemoryLock, MemoryUsage},
+    memory_management::{AllocCounters, MemoryError, MemoryLock, MemoryUsage},
     storage::{ComputeStorage, StorageHandle, StorageId, StorageUtilization},
 };
 use alloc::vec::Vec;
@@ -20,6 +20,7 @@ pub struct ExclusiveMemoryPool {
     alignment: u64,
     dealloc_period: u64,
     last_dealloc: u64,
+    counters: AllocCounters,
 }
 
 struct MemoryPage {
@@ -40,6 +41,7 @@ impl ExclusiveMemoryPool {
             alignment,
             dealloc_period,
             last_dealloc: 0,
+            counters: AllocCounters::default(),
         }
     }
 
@@ -65,6 +67,38 @@ impl ExclusiveMemoryPool {
 
         None
     }
+
+    /// Deallocates every page of the pool, which must all be free.
+    pub(crate) fn dealloc_all<Storage: ComputeStorage>(&mut self, storage: &mut Storage) {
+        let pages = self.pages.keys().copied().collect();
+        self.dealloc_pages(storage, &pages);
+    }
+
+    fn dealloc_pages<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        deallocations: &HashSet<StorageId>,
+    ) {
+        if deallocations.is_empty() {
+            return;
+        }
+
+        for storage_id in deallocations.iter() {
+            let slice_id = self.pages[storage_id].slice_id;
+            self.pages.remove(storage_id);
+            self.slices.remove(&slice_id);
+            storage.dealloc(*storage_id);
+            self.counters.record_dealloc(self.max_page_size);
+            mem_trace!(
+                "Exclusive pool: deallocated a page of {} bytes",
+                self.max_page_size
+            );
+        }
+
+        self.index = 0;
+        self.ring_buffer
+            .retain(|storage| !deallocations.contains(storage));
+    }
 }
 
 impl MemoryPool for ExclusiveMemoryPool {
@@ -82,12 +116,14 @@ impl MemoryPool for ExclusiveMemoryPool {
         storage: &mut Storage,
         size: u64,
         exclude: Option<&MemoryLock>,
-    ) -> SliceHandle {
+    ) -> Result<SliceHandle, MemoryError> {
         let page = self.get_free_page(exclude);
         let slice_id = if let Some(page) = page {
+            self.counters.record_cache_hit();
+            mem_trace!("Exclusive pool: reused a free page for {size} bytes");
             page
         } else {
-            *self.alloc(storage, self.max_page_size).id()
+            *self.alloc(storage, self.max_page_size)?.id()
         };
 
         let padding = calculate_padding(size, self.alignment);
@@ -96,12 +132,22 @@ impl MemoryPool for ExclusiveMemoryPool {
         // get a page with a size > size, so this is ok to do.
         slice.storage.utilization = StorageUtilization { offset: 0, size };
         slice.padding = padding;
-        slice.handle.clone()
+        Ok(slice.handle.clone())
     }
 
-    fn alloc<Storage: ComputeStorage>(&mut self, storage: &mut Storage, size: u64) -> SliceHandle {
-        let storage = storage.alloc(size);
+    fn alloc<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        size: u64,
+    ) -> Result<SliceHandle, MemoryError> {
+        let storage = storage.alloc(size)?;
         self.ring_buffer.push(storage.id);
+        // Pages are accounted for as full pages, like in the memory usage.
+        self.counters.record_alloc(self.max_page_size);
+        mem_trace!(
+            "Exclusive pool: allocated a page of {} bytes for {size} bytes",
+            self.max_page_size
+        );
 
         let handle = SliceHandle::new();
         let padding = calculate_padding(size, self.alignment);
@@ -117,7 +163,19 @@ impl MemoryPool for ExclusiveMemoryPool {
             },
         );
         self.slices.insert(slice_id, slice);
-        handle_slice
+        Ok(handle_slice)
+    }
+
+    fn live_allocations(&self, size: u64) -> u64 {
+        self.slices
+            .values()
+            .filter(|slice| !slice.is_free() && slice.storage.size() == size)
+            .count() as u64
+    }
+
+    fn has_free(&self, _size: u64) -> bool {
+        // Every page is as big as the biggest allocation of this pool.
+        self.slices.values().any(|slice| slice.is_free())
     }
 
     fn get_memory_usage(&self) -> MemoryUsage {
@@ -139,10 +197,20 @@ impl MemoryPool for ExclusiveMemoryPool {
         self.max_page_size
     }
 
-    fn cleanup<Storage: ComputeStorage>(&mut self, storage: &mut Storage, alloc_nr: u64) {
+    fn counters(&self) -> &AllocCounters {
+        &self.counters
+    }
+
+    fn cleanup<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        alloc_nr: u64,
+        force: bool,
+        locked: Option<&MemoryLock>,
+    ) {
         let elapsed = alloc_nr - self.last_dealloc;
 
-        if elapsed < self.dealloc_period {
+        if elapsed < self.dealloc_period && !force {
             return;
         }
 
@@ -151,12 +219,13 @@ impl MemoryPool for ExclusiveMemoryPool {
         let deallocations: HashSet<_> = self
             .pages
             .iter_mut()
+            .filter(|(storage_id, _)| !locked.is_some_and(|locked| locked.is_locked(storage_id)))
             .filter_map(|(storage_id, page)| {
                 let slice = self.slices.get(&page.slice_id).unwrap();
 
                 if slice.is_free() {
-                    // If not marked yet the memory might just have been freed.
-                    if !page.dealloc_mark {
+                    // If not marked yet the memory might just have been freed, unless forced.
+                    if !page.dealloc_mark && !force {
                         page.dealloc_mark = true;
                         None
                     } else {
@@ -168,18 +237,6 @@ impl MemoryPool for ExclusiveMemoryPool {
             })
             .collect();
 
-        // Perform any deallocations if necessary.
-        if !deallocations.is_empty() {
-            for storage_id in deallocations.iter() {
-                let slice_id = self.pages[storage_id].slice_id;
-                self.pages.remove(storage_id);
-                self.slices.remove(&slice_id);
-                storage.dealloc(*storage_id);
-            }
-
-            self.index = 0;
-            self.ring_buffer
-                .retain(|storage| !deallocations.contains(storage));
-        }
+        self.dealloc_pages(storage, &deallocations);
     }
 }
@@ -4,10 +4,12 @@ mod ring;
 mod base;
 mod exclusive_pool;
 mod handle;
+mod ring_pool;
 mod sliced_pool;
 
 pub(crate) use base::*;
 pub(crate) use exclusive_pool::*;
 pub(crate) use handle::*;
 pub(crate) use ring::*;
+pub(crate) use ring_pool::*;
 pub(crate) use sliced_pool::*;
@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 
 use crate::{memory_management::MemoryLock, storage::StorageId};
 
@@ -31,6 +31,19 @@ impl RingBuffer {
             .insert(storage_id, self.queue.len() - 1);
     }
 
+    /// Remove the given pages from the ring, restarting the search from the first page.
+    pub fn remove_pages(&mut self, storage_ids: &HashSet<StorageId>) {
+        self.queue.retain(|id| !storage_ids.contains(id));
+        self.chunk_positions = self
+            .queue
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (*id, position))
+            .collect();
+        self.cursor_chunk = 0;
+        self.cursor_slice = 0;
+    }
+
     pub fn find_free_slice(
         &mut self,
         size: u64,
@@ -0,0 +1,240 @@
+use super::{calculate_padding, MemoryPool, Slice, SliceBinding, SliceHandle, SliceId};
+use crate::{
+    memory_management::{AllocCounters, MemoryError, MemoryLock, MemoryUsage},
+    storage::{ComputeStorage, StorageHandle, StorageUtilization},
+};
+use alloc::vec::Vec;
+use hashbrown::HashMap;
+
+/// A memory pool that cycles through a fixed set of equally sized slots.
+///
+/// - Every slot is backed by its own page, so slots still used by in-flight work can be excluded
+///   with the [memory lock](MemoryLock).
+/// - Slots are handed out in order, so the oldest slot is reused first once it isn't referenced
+///   anymore.
+/// - The ring never holds more than `num_slots` slots, reserving fails when all of them are in
+///   use.
+pub(crate) struct RingBufferPool {
+    slices: HashMap<SliceId, Slice>,
+    ring: Vec<SliceId>,
+    cursor: usize,
+    num_slots: u64,
+    slot_size: u64,
+    alignment: u64,
+    counters: AllocCounters,
+}
+
+impl RingBufferPool {
+    pub(crate) fn new(num_slots: u64, slot_size: u64, alignment: u64) -> Self {
+        // Slots should be allocated to be aligned.
+        assert_eq!(slot_size % alignment, 0);
+        Self {
+            slices: HashMap::new(),
+            ring: Vec::new(),
+            cursor: 0,
+            num_slots,
+            slot_size,
+            alignment,
+            counters: AllocCounters::default(),
+        }
+    }
+
+    /// Finds the oldest slot that isn't referenced or locked anymore.
+    fn get_free_slot(&mut self, locked: Option<&MemoryLock>) -> Option<SliceId> {
+        for _ in 0..self.ring.len() {
+            let slice_id = self.ring[self.cursor];
+            self.cursor = (self.cursor + 1) % self.ring.len();
+
+            let slice = self.slices.get(&slice_id).unwrap();
+
+            if let Some(locked) = locked.as_ref() {
+                if locked.is_locked(&slice.storage.id) {
+                    continue;
+                }
+            }
+
+            if slice.is_free() {
+                return Some(slice_id);
+            }
+        }
+
+        None
+    }
+
+    /// Deallocates every slot of the ring, which must all be free.
+    pub(crate) fn dealloc_all<Storage: ComputeStorage>(&mut self, storage: &mut Storage) {
+        for slice_id in self.ring.drain(..) {
+            let slice = self.slices.remove(&slice_id).unwrap();
+            debug_assert!(slice.is_free());
+            storage.dealloc(slice.storage.id);
+            self.counters.record_dealloc(self.slot_size);
+        }
+        self.cursor = 0;
+    }
+}
+
+impl MemoryPool for RingBufferPool {
+    fn max_alloc_size(&self) -> u64 {
+        self.slot_size
+    }
+
+    fn counters(&self) -> &AllocCounters {
+        &self.counters
+    }
+
+    fn get(&self, binding: &SliceBinding) -> Option<&StorageHandle> {
+        self.slices.get(binding.id()).map(|s| &s.storage)
+    }
+
+    fn reserve<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        size: u64,
+        locked: Option<&MemoryLock>,
+    ) -> Result<SliceHandle, MemoryError> {
+        let slice_id = match self.get_free_slot(locked) {
+            Some(slice_id) => {
+                self.counters.record_cache_hit();
+                mem_trace!("Ring buffer pool: reused a free slot for {size} bytes");
+                slice_id
+            }
+            // The slots that couldn't be allocated with the pool are allocated on demand.
+            None => *self.alloc(storage, size)?.id(),
+        };
+
+        let slice = self.slices.get_mut(&slice_id).unwrap();
+        // Every slot is as big as the biggest allocation of this pool, so only
+        // expose the requested part of it.
+        slice.storage.utilization = StorageUtilization { offset: 0, size };
+        slice.padding = calculate_padding(size, self.alignment);
+        Ok(slice.handle.clone())
+    }
+
+    fn alloc<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        _size: u64,
+    ) -> Result<SliceHandle, MemoryError> {
+        if self.ring.len() as u64 >= self.num_slots {
+            return Err(MemoryError::RingBufferFull {
+                num_slots: self.num_slots,
+            });
+        }
+
+        let storage = storage.alloc(self.slot_size)?;
+        self.counters.record_alloc(self.slot_size);
+        mem_trace!(
+            "Ring buffer pool: allocated a slot of {} bytes",
+            self.slot_size
+        );
+        let padding = calculate_padding(self.slot_size, self.alignment);
+        let slice = Slice::new(storage, SliceHandle::new(), padding);
+
+        let handle = slice.handle.clone();
+        let slice_id = slice.id();
+
+        // Insert the new slot right before the cursor, so it is the last one to be reused.
+        self.ring.insert(self.cursor, slice_id);
+        self.cursor = (self.cursor + 1) % self.ring.len();
+        self.slices.insert(slice_id, slice);
+
+        Ok(handle)
+    }
+
+    fn live_allocations(&self, size: u64) -> u64 {
+        self.slices
+            .values()
+            .filter(|slice| !slice.is_free() && slice.storage.size() == size)
+            .count() as u64
+    }
+
+    fn has_free(&self, _size: u64) -> bool {
+        // Every page is as big as the biggest allocation of this pool.
+        self.slices.values().any(|slice| slice.is_free())
+    }
+
+    fn get_memory_usage(&self) -> MemoryUsage {
+        let used_slices: Vec<_> = self
+            .slices
+            .values()
+            .filter(|slice| !slice.is_free())
+            .collect();
+
+        MemoryUsage {
+            number_allocs: used_slices.len() as u64,
+            bytes_in_use: used_slices.iter().map(|s| s.storage.size()).sum(),
+            bytes_padding: used_slices.iter().map(|s| s.padding).sum(),
+            bytes_reserved: self.ring.len() as u64 * self.slot_size,
+        }
+    }
+
+    fn cleanup<Storage: ComputeStorage>(
+        &mut self,
+        _storage: &mut Storage,
+        _alloc_nr: u64,
+        _force: bool,
+        _locked: Option<&MemoryLock>,
+    ) {
+        // The slots of the ring are kept alive for the lifetime of the pool.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::BytesStorage;
+
+    #[test]
+    fn reuses_oldest_free_slot() {
+        let mut storage = BytesStorage::default();
+        let mut pool = RingBufferPool::new(2, 64, 32);
+
+        let first = pool.reserve(&mut storage, 32, None).unwrap();
+        let first_id = *first.id();
+        let second = pool.reserve(&mut storage, 32, None).unwrap();
+        assert_ne!(first_id, *second.id());
+
+        drop(first);
+        drop(second);
+
+        let third = pool.reserve(&mut storage, 32, None).unwrap();
+        assert_eq!(first_id, *third.id());
+        assert_eq!(pool.get_memory_usage().bytes_reserved, 2 * 64);
+    }
+
+    #[test]
+    fn skips_slots_in_flight() {
+        let mut storage = BytesStorage::default();
+        let mut pool = RingBufferPool::new(2, 64, 32);
+
+        let first = pool.reserve(&mut storage, 32, None).unwrap();
+        let first_id = *first.id();
+        let second = pool.reserve(&mut storage, 32, None).unwrap();
+        let second_id = *second.id();
+        let first_storage = pool.get(&first.clone().binding()).unwrap().id;
+
+        drop(first);
+        drop(second);
+
+        let mut locked = MemoryLock::default();
+        locked.add_locked(first_storage);
+
+        let third = pool.reserve(&mut storage, 32, Some(&locked)).unwrap();
+        assert_eq!(second_id, *third.id());
+        assert_ne!(first_id, *third.id());
+    }
+
+    #[test]
+    fn fails_when_all_slots_are_used() {
+        let mut storage = BytesStorage::default();
+        let mut pool = RingBufferPool::new(1, 64, 32);
+
+        let _first = pool.reserve(&mut storage, 32, None).unwrap();
+
+        assert!(matches!(
+            pool.reserve(&mut storage, 32, None),
+            Err(MemoryError::RingBufferFull { num_slots: 1 })
+        ));
+        assert_eq!(pool.get_memory_usage().bytes_reserved, 64);
+    }
+}
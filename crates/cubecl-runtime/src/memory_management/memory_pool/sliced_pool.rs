@@ -1,10 +1,10 @@
 use super::index::SearchIndex;
 use super::{MemoryPool, RingBuffer, Slice, SliceBinding, SliceHandle, SliceId};
 use crate::memory_management::memory_pool::calculate_padding;
-use crate::memory_management::{MemoryLock, MemoryUsage};
+use crate::memory_management::{AllocCounters, MemoryError, MemoryLock, MemoryUsage, PageGrowth};
 use crate::storage::{ComputeStorage, StorageHandle, StorageId, StorageUtilization};
 use alloc::vec::Vec;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 
 /// A memory pool that allocates buffers in a range of sizes and reuses them to minimize allocations.
 ///
@@ -17,9 +17,12 @@ pub(crate) struct SlicedPool {
     ring: RingBuffer,
     recently_added_pages: Vec<StorageId>,
     recently_allocated_size: u64,
+    /// The size of the next page to allocate.
     page_size: u64,
+    page_growth: PageGrowth,
     max_alloc_size: u64,
     alignment: u64,
+    counters: AllocCounters,
 }
 
 // TODO: consider using generic trait and decouple from Slice
@@ -85,6 +88,10 @@ impl MemoryPool for SlicedPool {
         self.max_alloc_size
     }
 
+    fn counters(&self) -> &AllocCounters {
+        &self.counters
+    }
+
     /// Returns the resource from the storage, for the specified handle.
     fn get(&self, binding: &SliceBinding) -> Option<&StorageHandle> {
         self.slices.get(binding.id()).map(|s| &s.storage)
@@ -99,26 +106,37 @@ impl MemoryPool for SlicedPool {
         storage: &mut Storage,
         size: u64,
         locked: Option<&MemoryLock>,
-    ) -> SliceHandle {
+    ) -> Result<SliceHandle, MemoryError> {
         let slice = self.get_free_slice(size, locked);
 
         match slice {
-            Some(slice) => slice.clone(),
+            Some(slice) => {
+                self.counters.record_cache_hit();
+                mem_trace!("Sliced pool: reused a free slice for {size} bytes");
+                Ok(slice)
+            }
             None => self.alloc(storage, size),
         }
     }
 
-    fn alloc<Storage: ComputeStorage>(&mut self, storage: &mut Storage, size: u64) -> SliceHandle {
-        let storage_id = self.create_page(storage, self.page_size);
+    fn alloc<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        size: u64,
+    ) -> Result<SliceHandle, MemoryError> {
+        let page_size = self.page_size;
+        let storage_id = self.create_page(storage, page_size)?;
+        // Only grow once the page exists, so a failed allocation is retried at the same size.
+        self.page_size = self.grow_page_size(page_size);
         self.recently_added_pages.push(storage_id);
-        self.recently_allocated_size += self.page_size;
+        self.recently_allocated_size += page_size;
 
         let slice = self.create_slice(0, size, storage_id);
 
         let effective_size = slice.effective_size();
 
-        let extra_slice = if effective_size < self.page_size {
-            Some(self.create_slice(effective_size, self.page_size - effective_size, storage_id))
+        let extra_slice = if effective_size < page_size {
+            Some(self.create_slice(effective_size, page_size - effective_size, storage_id))
         } else {
             None
         };
@@ -139,7 +157,21 @@ impl MemoryPool for SlicedPool {
             page.slices.insert(extra_slice_offset, extra_slice_id);
         }
 
-        handle_slice
+        Ok(handle_slice)
+    }
+
+    fn live_allocations(&self, size: u64) -> u64 {
+        self.slices
+            .values()
+            .filter(|slice| !slice.is_free() && slice.storage.size() == size)
+            .count() as u64
+    }
+
+    fn has_free(&self, size: u64) -> bool {
+        let effective_size = size + calculate_padding(size, self.alignment);
+        self.slices
+            .values()
+            .any(|slice| slice.is_free() && slice.effective_size() >= effective_size)
     }
 
     fn get_memory_usage(&self) -> MemoryUsage {
@@ -157,15 +189,80 @@ impl MemoryPool for SlicedPool {
         }
     }
 
-    fn cleanup<Storage: ComputeStorage>(&mut self, _storage: &mut Storage, _alloc_nr: u64) {
-        // This pool doesn't do any shrinking currently.
+    fn cleanup<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        _alloc_nr: u64,
+        force: bool,
+        locked: Option<&MemoryLock>,
+    ) {
+        let unused_pages: HashSet<_> = self
+            .pages
+            .iter()
+            .filter(|(storage_id, _)| !locked.is_some_and(|locked| locked.is_locked(storage_id)))
+            .filter(|(_, page)| {
+                page.slices
+                    .values()
+                    .all(|slice_id| self.slices[slice_id].is_free())
+            })
+            .map(|(storage_id, _)| *storage_id)
+            .collect();
+
+        // This pool only shrinks when explicitly requested, since pages are shared by many
+        // slices and are likely to be reused.
+        if force {
+            self.dealloc_pages(storage, &unused_pages);
+        }
     }
 }
 
 impl SlicedPool {
-    pub(crate) fn new(page_size: u64, max_alloc_size: u64, alignment: u64) -> Self {
+    /// Deallocates every page of the pool, which must all be free.
+    pub(crate) fn dealloc_all<Storage: ComputeStorage>(&mut self, storage: &mut Storage) {
+        let pages = self.pages.keys().copied().collect();
+        self.dealloc_pages(storage, &pages);
+    }
+
+    fn dealloc_pages<Storage: ComputeStorage>(
+        &mut self,
+        storage: &mut Storage,
+        deallocations: &HashSet<StorageId>,
+    ) {
+        if deallocations.is_empty() {
+            return;
+        }
+
+        for storage_id in deallocations.iter() {
+            let page = self.pages.remove(storage_id).unwrap();
+            let mut page_size = 0;
+            for slice_id in page.slices.values() {
+                page_size += self.slices.remove(slice_id).unwrap().effective_size();
+            }
+            self.storage_index.remove(storage_id);
+            storage.dealloc(*storage_id);
+            self.counters.record_dealloc(page_size);
+            mem_trace!("Sliced pool: deallocated a page of {page_size} bytes");
+        }
+
+        self.ring.remove_pages(deallocations);
+        self.recently_added_pages
+            .retain(|storage_id| !deallocations.contains(storage_id));
+    }
+
+    pub(crate) fn new(
+        page_size: u64,
+        max_alloc_size: u64,
+        alignment: u64,
+        page_growth: PageGrowth,
+    ) -> Self {
         // Pages should be allocated to be aligned.
         assert_eq!(page_size % alignment, 0);
+        if let PageGrowth::Geometric { factor, .. } = page_growth {
+            assert!(
+                factor >= 1.0,
+                "Pages can't shrink, the growth factor must be at least 1"
+            );
+        }
         Self {
             pages: HashMap::new(),
             slices: HashMap::new(),
@@ -175,7 +272,46 @@ impl SlicedPool {
             recently_allocated_size: 0,
             alignment,
             page_size,
+            page_growth,
             max_alloc_size,
+            counters: AllocCounters::default(),
+        }
+    }
+
+    /// Merge every run of adjacent free slices into a single slice, so bigger reservations can
+    /// reuse them without allocating a new page.
+    ///
+    /// Free slices are otherwise only merged when the ring buffer walks over them while searching.
+    pub(crate) fn merge_free_slices(&mut self) {
+        for page in self.pages.values_mut() {
+            let mut addresses: Vec<_> = page.slices.keys().copied().collect();
+            addresses.sort_unstable();
+
+            for address in addresses {
+                let Some(slice_id) = page.find_slice(address) else {
+                    // Already merged into the previous slice.
+                    continue;
+                };
+                if !self.slices[&slice_id].is_free() {
+                    continue;
+                }
+                while page.merge_with_next_slice(address, &mut self.slices) {}
+            }
+        }
+    }
+
+    /// The size of the page allocated after one of `page_size` bytes.
+    fn grow_page_size(&self, page_size: u64) -> u64 {
+        match self.page_growth {
+            PageGrowth::Constant => page_size,
+            PageGrowth::Geometric {
+                factor,
+                max_page_size,
+            } => {
+                let grown = ((page_size as f64 * factor) as u64).min(max_page_size);
+                // Pages should be allocated to be aligned, and never shrink.
+                (grown / self.alignment * self.alignment).max(page_size)
+            }
         }
     }
 
@@ -228,8 +364,10 @@ impl SlicedPool {
         &mut self,
         storage: &mut Storage,
         size: u64,
-    ) -> StorageId {
-        let storage = storage.alloc(self.page_size);
+    ) -> Result<StorageId, MemoryError> {
+        let storage = storage.alloc(size)?;
+        self.counters.record_alloc(size);
+        mem_trace!("Sliced pool: allocated a page of {size} bytes");
 
         let id = storage.id;
         self.ring.push_page(id);
@@ -237,7 +375,7 @@ impl SlicedPool {
         self.pages.insert(id, MemoryPage::new(HashMap::new()));
         self.storage_index.insert(id, size);
 
-        id
+        Ok(id)
     }
 }
 
@@ -1,20 +1,43 @@
+/// Emit a trace event about the memory management, only when the `mem-trace` feature is enabled.
+///
+/// Without the feature, the arguments aren't evaluated at all. It is declared before the
+/// submodules so they can all use it.
+macro_rules! mem_trace {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "mem-trace")]
+        log::trace!(target: "cubecl_runtime::memory", $($arg)+);
+    };
+}
+
 pub(crate) mod memory_pool;
 
 mod base;
+mod budget;
+mod error;
 mod memory_lock;
+mod throttle;
+mod trace;
 
 pub use base::*;
+pub use error::*;
 pub use memory_lock::*;
+pub use throttle::*;
+pub use trace::*;
+
+/// Conformance checks of the memory management, shared by the tests of every runtime.
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
 /// Dynamic memory management strategy.
 mod memory_manage;
 pub use memory_manage::*;
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{boxed::Box, vec::Vec};
 
 /// The type of memory pool to use.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PoolType {
     /// Use a memory where every allocation is a separate page.
     ExclusivePages,
@@ -23,6 +46,40 @@ pub enum PoolType {
         /// The maximum size of a slice to allocate in the pool.
         max_slice_size: u64,
     },
+    /// Use a fixed set of equally sized slots that are reused in order, oldest first.
+    ///
+    /// This gives an allocation-free steady state for transient allocations like staging buffers.
+    /// Each slot is its own page of `slot_size` bytes, so the
+    /// [page size](MemoryPoolOptions::page_size) of the pool has to be `slot_size`. All the slots
+    /// are allocated when the pool is created, and reserving fails with
+    /// [MemoryError::RingBufferFull] while every slot is in use.
+    RingBuffer {
+        /// The number of slots in the ring.
+        num_slots: u64,
+        /// The size of every slot in bytes.
+        slot_size: u64,
+    },
+}
+
+/// How the pages of a [sliced pool](PoolType::SlicedPages) grow as new ones are allocated.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PageGrowth {
+    /// Every page has the [page size](MemoryPoolOptions::page_size) of the pool.
+    #[default]
+    Constant,
+    /// Each new page is `factor` times bigger than the previous one, starting from the
+    /// [page size](MemoryPoolOptions::page_size) of the pool.
+    ///
+    /// Useful for working sets that grow over time, since fewer pages are allocated to hold them.
+    Geometric {
+        /// The growth factor between two consecutive pages, at least 1.
+        factor: f64,
+        /// The size pages stop growing at.
+        ///
+        /// When the pools are created from a [configuration](MemoryConfiguration), it's lowered to
+        /// the [max page size](MemoryDeviceProperties::max_page_size) of the device.
+        max_page_size: u64,
+    },
 }
 
 /// Options to create a memory pool.
@@ -34,7 +91,10 @@ pub struct MemoryPoolOptions {
     pub page_size: u64,
     /// The number of chunks allocated directly at creation.
     ///
-    /// Useful when you know in advance how much memory you'll need.
+    /// Useful when you know in advance how much memory you'll need. When the
+    /// [total memory](MemoryDeviceProperties::total_memory) of the device is known, the pages
+    /// preallocated by all the pools are clamped to fit in it, with a warning. The pages of a
+    /// growing pool are counted at their grown size.
     pub chunk_num_prealloc: u64,
     /// Period after which allocations are deemed unused and deallocated.
     ///
@@ -42,6 +102,165 @@ pub struct MemoryPoolOptions {
     /// in the pool was unused for the entire period, it will be deallocated. This period is
     /// approximmate, as checks are only done occasionally.
     pub dealloc_period: Option<u64>,
+    /// Alignment of the allocations made in this pool, overriding the
+    /// [device alignment](MemoryDeviceProperties::alignment).
+    ///
+    /// Useful for operations requiring a stricter alignment than the device default, such as
+    /// cooperative matrix loads. It has to be a power of two and a multiple of the device
+    /// alignment, and the page size has to be a multiple of it.
+    pub alignment: Option<u64>,
+    /// How the pages grow as new ones are allocated. Only used by
+    /// [sliced pages](PoolType::SlicedPages), the other pools always use pages of the same size.
+    pub page_growth: PageGrowth,
+}
+
+impl MemoryPoolOptions {
+    /// Create a [builder](MemoryPoolOptionsBuilder) to configure memory pool options.
+    pub fn builder() -> MemoryPoolOptionsBuilder {
+        MemoryPoolOptionsBuilder::default()
+    }
+}
+
+/// Error returned when [memory pool options](MemoryPoolOptions) are invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryPoolOptionsError {
+    /// No page size was provided.
+    MissingPageSize,
+    /// The maximum slice size of a sliced pool can't be bigger than its page size.
+    SliceSizeExceedsPageSize {
+        /// The requested maximum slice size.
+        max_slice_size: u64,
+        /// The page size of the pool.
+        page_size: u64,
+    },
+    /// The alignment of the pool is zero or not a power of two.
+    InvalidAlignment {
+        /// The requested alignment.
+        alignment: u64,
+    },
+    /// The page size of the pool isn't a multiple of its alignment.
+    UnalignedPageSize {
+        /// The page size of the pool.
+        page_size: u64,
+        /// The alignment of the pool.
+        alignment: u64,
+    },
+    /// The geometric growth factor is smaller than 1, or the maximum page size is smaller than
+    /// the page size.
+    InvalidPageGrowth,
+}
+
+/// Builder for [memory pool options](MemoryPoolOptions).
+///
+/// By default, the pool uses [exclusive pages](PoolType::ExclusivePages), doesn't preallocate
+/// any chunk and never deallocates pages.
+#[derive(Debug, Clone)]
+pub struct MemoryPoolOptionsBuilder {
+    pool_type: PoolType,
+    page_size: Option<u64>,
+    chunk_num_prealloc: u64,
+    dealloc_period: Option<u64>,
+    alignment: Option<u64>,
+    page_growth: PageGrowth,
+}
+
+impl Default for MemoryPoolOptionsBuilder {
+    fn default() -> Self {
+        Self {
+            pool_type: PoolType::ExclusivePages,
+            page_size: None,
+            chunk_num_prealloc: 0,
+            dealloc_period: None,
+            alignment: None,
+            page_growth: PageGrowth::Constant,
+        }
+    }
+}
+
+impl MemoryPoolOptionsBuilder {
+    /// Set the kind of pool to use.
+    pub fn pool_type(mut self, pool_type: PoolType) -> Self {
+        self.pool_type = pool_type;
+        self
+    }
+
+    /// Set the amount of bytes used for each chunk in the memory pool.
+    pub fn page_size(mut self, page_size: u64) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Set the number of chunks allocated directly at creation.
+    pub fn chunk_num_prealloc(mut self, chunk_num_prealloc: u64) -> Self {
+        self.chunk_num_prealloc = chunk_num_prealloc;
+        self
+    }
+
+    /// Set the period after which unused pages are deallocated.
+    pub fn dealloc_period(mut self, dealloc_period: u64) -> Self {
+        self.dealloc_period = Some(dealloc_period);
+        self
+    }
+
+    /// Set the alignment of the allocations, overriding the device alignment.
+    pub fn alignment(mut self, alignment: u64) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+
+    /// Set how the pages grow as new ones are allocated.
+    pub fn page_growth(mut self, page_growth: PageGrowth) -> Self {
+        self.page_growth = page_growth;
+        self
+    }
+
+    /// Validate and create the [memory pool options](MemoryPoolOptions).
+    pub fn build(self) -> Result<MemoryPoolOptions, MemoryPoolOptionsError> {
+        let page_size = self
+            .page_size
+            .ok_or(MemoryPoolOptionsError::MissingPageSize)?;
+
+        if let PoolType::SlicedPages { max_slice_size } = self.pool_type {
+            if max_slice_size > page_size {
+                return Err(MemoryPoolOptionsError::SliceSizeExceedsPageSize {
+                    max_slice_size,
+                    page_size,
+                });
+            }
+        }
+
+        if let Some(alignment) = self.alignment {
+            if !alignment.is_power_of_two() {
+                return Err(MemoryPoolOptionsError::InvalidAlignment { alignment });
+            }
+
+            if page_size % alignment != 0 {
+                return Err(MemoryPoolOptionsError::UnalignedPageSize {
+                    page_size,
+                    alignment,
+                });
+            }
+        }
+
+        if let PageGrowth::Geometric {
+            factor,
+            max_page_size,
+        } = self.page_growth
+        {
+            if factor.is_nan() || factor < 1.0 || max_page_size < page_size {
+                return Err(MemoryPoolOptionsError::InvalidPageGrowth);
+            }
+        }
+
+        Ok(MemoryPoolOptions {
+            pool_type: self.pool_type,
+            page_size,
+            chunk_num_prealloc: self.chunk_num_prealloc,
+            dealloc_period: self.dealloc_period,
+            alignment: self.alignment,
+            page_growth: self.page_growth,
+        })
+    }
 }
 
 /// High level configuration of memory management.
@@ -53,8 +272,54 @@ pub enum MemoryConfiguration {
     /// Default preset using only exclusive pages.
     /// This can be necessary when backends don't support sub-slices.
     ExclusivePages,
+    /// Preset for devices with little memory, such as integrated GPUs.
+    ///
+    /// Uses exclusive pages sized closely to the allocations, without preallocation, and returns
+    /// unused pages to the system much sooner than the other presets.
+    Conservative,
     /// Customize each pool individually.
     Custom(Vec<MemoryPoolOptions>),
+    /// Use the pools of another configuration, allocating at most
+    /// [max_pages](AllocationThrottle::max_pages) new pages per
+    /// [period](AllocationThrottle::period).
+    ///
+    /// A reservation needing a page past the budget fails with [MemoryError::Throttled], and the
+    /// client retries it once the period ends, so bursts of allocations grow the memory gradually
+    /// instead of all at once, leaving room for other processes on the device. The client waits
+    /// without holding the server, and reservations reusing pages are never delayed. The throttle
+    /// can be bypassed for a burst that has to be fast, and is ignored on platforms without
+    /// threads.
+    Throttled {
+        /// The configuration of the pools.
+        config: Box<MemoryConfiguration>,
+        /// How fast the pools can grow.
+        throttle: AllocationThrottle,
+    },
+}
+
+impl MemoryConfiguration {
+    /// Whether some memory pools place several allocations on the same page.
+    ///
+    /// When they do, different bindings of a kernel may refer to the same buffer.
+    pub fn has_sub_slices(&self) -> bool {
+        match self {
+            #[cfg(not(exclusive_memory_only))]
+            MemoryConfiguration::SubSlices => true,
+            MemoryConfiguration::ExclusivePages | MemoryConfiguration::Conservative => false,
+            MemoryConfiguration::Custom(pools) => pools
+                .iter()
+                .any(|pool| matches!(pool.pool_type, PoolType::SlicedPages { .. })),
+            MemoryConfiguration::Throttled { config, .. } => config.has_sub_slices(),
+        }
+    }
+
+    /// The growth limit of the pools, when the configuration is [throttled](Self::Throttled).
+    pub fn throttle(&self) -> Option<AllocationThrottle> {
+        match self {
+            MemoryConfiguration::Throttled { throttle, .. } => Some(*throttle),
+            _ => None,
+        }
+    }
 }
 
 #[allow(clippy::derivable_impls)]
@@ -78,6 +343,27 @@ pub struct MemoryDeviceProperties {
     pub max_page_size: u64,
     /// The required memory offset alignment in bytes.
     pub alignment: u64,
+    /// The total amount of memory of the device in bytes, when the backend can query it.
+    pub total_memory: Option<u64>,
+}
+
+impl MemoryDeviceProperties {
+    /// The page size of the biggest pool created by the default [memory configuration](MemoryConfiguration).
+    ///
+    /// It's a fraction of the total memory of the device, so small devices don't waste memory on
+    /// pages they can't fill and big devices don't need as many allocations. When the total memory
+    /// is unknown, the maximum page size is used.
+    pub fn default_page_size(&self) -> u64 {
+        // Sixteen pages of the biggest pool fill the device.
+        const TOTAL_MEMORY_FRACTION: u64 = 16;
+
+        let page_size = match self.total_memory {
+            Some(total) => u64::min(total / TOTAL_MEMORY_FRACTION, self.max_page_size),
+            None => self.max_page_size,
+        };
+
+        u64::max(page_size / self.alignment * self.alignment, self.alignment)
+    }
 }
 
 /// Properties of the device related to the accelerator hardware.
@@ -94,12 +380,133 @@ pub struct MemoryDeviceProperties {
 /// For Intel GPUs, this is variable based on the number of registers used in the kernel. No way to
 /// query this at compile time is currently available. As a result, the minimum value should usually
 /// be assumed.
+///
+/// When the runtime can measure it, the plane size actually used by kernels is available in
+/// `plane_size_actual`.
+///
+/// The plane size can't be chosen per launch within these bounds: CUDA and HIP have a fixed warp
+/// size, and `wgpu` has no way to set a required subgroup size on a pipeline.
 #[derive(Debug, Clone)]
 pub struct HardwareProperties {
     /// The minimum size of a plane on this device
     pub plane_size_min: u32,
     /// The maximum size of a plane on this device
     pub plane_size_max: u32,
-    /// minimum number of bindings for a kernel that can be used at once.
+    /// The plane size measured at runtime on this device, if it could be queried.
+    pub plane_size_actual: Option<u32>,
+    /// Maximum number of bindings a kernel can use at once.
     pub max_bindings: u32,
+    /// Maximum amount of shared memory a cube can use, in bytes.
+    pub max_shared_memory_bytes: u32,
+    /// Maximum number of cubes that can be launched in each dimension.
+    pub max_cube_count: (u32, u32, u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_page_size_follows_total_memory() {
+        let mut properties = MemoryDeviceProperties {
+            max_page_size: 1024 * 1024 * 1024,
+            alignment: 256,
+            total_memory: None,
+        };
+        assert_eq!(properties.default_page_size(), 1024 * 1024 * 1024);
+
+        properties.total_memory = Some(4 * 1024 * 1024 * 1024);
+        assert_eq!(properties.default_page_size(), 256 * 1024 * 1024);
+
+        properties.total_memory = Some(64 * 1024 * 1024 * 1024);
+        assert_eq!(properties.default_page_size(), 1024 * 1024 * 1024);
+    }
+
+    #[test]
+    fn builder_uses_defaults() {
+        let options = MemoryPoolOptions::builder()
+            .page_size(1024)
+            .build()
+            .unwrap();
+
+        assert!(matches!(options.pool_type, PoolType::ExclusivePages));
+        assert_eq!(options.page_size, 1024);
+        assert_eq!(options.chunk_num_prealloc, 0);
+        assert_eq!(options.dealloc_period, None);
+    }
+
+    #[test]
+    fn builder_requires_page_size() {
+        let result = MemoryPoolOptions::builder().build();
+
+        assert_eq!(result.unwrap_err(), MemoryPoolOptionsError::MissingPageSize);
+    }
+
+    #[test]
+    fn builder_rejects_slice_bigger_than_page() {
+        let result = MemoryPoolOptions::builder()
+            .pool_type(PoolType::SlicedPages {
+                max_slice_size: 2048,
+            })
+            .page_size(1024)
+            .build();
+
+        assert_eq!(
+            result.unwrap_err(),
+            MemoryPoolOptionsError::SliceSizeExceedsPageSize {
+                max_slice_size: 2048,
+                page_size: 1024,
+            }
+        );
+    }
+
+    #[test]
+    fn builder_rejects_unaligned_page() {
+        let result = MemoryPoolOptions::builder()
+            .page_size(1000)
+            .alignment(256)
+            .build();
+
+        assert_eq!(
+            result.unwrap_err(),
+            MemoryPoolOptionsError::UnalignedPageSize {
+                page_size: 1000,
+                alignment: 256,
+            }
+        );
+    }
+
+    #[test]
+    fn builder_rejects_invalid_alignment() {
+        for alignment in [0, 96] {
+            let result = MemoryPoolOptions::builder()
+                .page_size(96 * 1024)
+                .alignment(alignment)
+                .build();
+
+            assert_eq!(
+                result.unwrap_err(),
+                MemoryPoolOptionsError::InvalidAlignment { alignment }
+            );
+        }
+    }
+
+    #[test]
+    fn builder_rejects_shrinking_pages() {
+        let result = MemoryPoolOptions::builder()
+            .pool_type(PoolType::SlicedPages {
+                max_slice_size: 1024,
+            })
+            .page_size(1024)
+            .page_growth(PageGrowth::Geometric {
+                factor: 0.5,
+                max_page_size: 4096,
+            })
+            .build();
+
+        assert_eq!(
+            result.unwrap_err(),
+            MemoryPoolOptionsError::InvalidPageGrowth
+        );
+    }
 }
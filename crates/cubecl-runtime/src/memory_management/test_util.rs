@@ -0,0 +1,93 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use super::{memory_pool::SliceHandle, MemoryManagement};
+use crate::storage::{ComputeStorage, StorageId};
+
+/// Reserve and release `steps` allocations of random sizes up to `max_size` bytes, and panic as
+/// soon as a live allocation isn't aligned to `alignment` bytes or overlaps with another one.
+///
+/// The sequence only depends on `seed`, so a failure can be replayed. Runtimes can call it with
+/// the memory management of their own storage instead of writing their own conformance test.
+pub fn check_allocations<Storage: ComputeStorage>(
+    memory_management: &mut MemoryManagement<Storage>,
+    alignment: u64,
+    max_size: u64,
+    steps: usize,
+    seed: u64,
+) {
+    let mut rng = XorShift(seed.max(1));
+    let mut live = Vec::<(SliceHandle, u64)>::new();
+
+    for step in 0..steps {
+        // Release a third of the time, so allocations pile up but free slices get reused.
+        let roll = rng.next() % 3;
+        if !live.is_empty() && roll == 0 {
+            let index = rng.next() % live.len() as u64;
+            live.swap_remove(index as usize);
+            continue;
+        }
+
+        let size = rng.next() % max_size + 1;
+        let handle = memory_management
+            .reserve(size, None)
+            .unwrap_or_else(|err| panic!("Step {step}: unable to reserve {size} bytes: {err:?}"));
+        live.push((handle, size));
+
+        check_live_allocations(memory_management, &live, alignment, step);
+    }
+}
+
+fn check_live_allocations<Storage: ComputeStorage>(
+    memory_management: &mut MemoryManagement<Storage>,
+    live: &[(SliceHandle, u64)],
+    alignment: u64,
+    step: usize,
+) {
+    let mut ranges = BTreeMap::<StorageId, Vec<(u64, u64)>>::new();
+
+    for (handle, size) in live {
+        let storage = memory_management.get(handle.clone().binding());
+        let offset = storage.offset();
+
+        assert_eq!(
+            offset.next_multiple_of(alignment),
+            offset,
+            "Step {step}: the slice of {size} bytes at offset {offset} isn't aligned to {alignment} bytes"
+        );
+        assert!(
+            storage.size() >= *size,
+            "Step {step}: the slice at offset {offset} holds {} bytes instead of {size}",
+            storage.size()
+        );
+
+        ranges
+            .entry(storage.id)
+            .or_default()
+            .push((offset, offset + size));
+    }
+
+    for (id, ranges) in ranges.iter_mut() {
+        ranges.sort_unstable();
+
+        for pair in ranges.windows(2) {
+            assert!(
+                pair[0].1 <= pair[1].0,
+                "Step {step}: the slices {:?} and {:?} of {id:?} overlap",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+}
+
+/// Small deterministic generator, so the helper doesn't depend on `rand`.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
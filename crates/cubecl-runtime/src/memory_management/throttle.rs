@@ -0,0 +1,86 @@
+use core::time::Duration;
+
+/// Limits how fast the memory pools grow, see [MemoryConfiguration::Throttled](super::MemoryConfiguration::Throttled).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationThrottle {
+    /// The most pages allocated on the storage during a period.
+    pub max_pages: u64,
+    /// The length of a period.
+    pub period: Duration,
+}
+
+/// The pages allocated during the current period of a [throttle](AllocationThrottle).
+pub(crate) struct ThrottleState {
+    throttle: AllocationThrottle,
+    #[cfg(allocation_throttle)]
+    period_start: Option<std::time::Instant>,
+    #[cfg(allocation_throttle)]
+    pages: u64,
+}
+
+impl ThrottleState {
+    pub(crate) fn new(throttle: AllocationThrottle) -> Self {
+        #[cfg(not(allocation_throttle))]
+        log::warn!("Throttling allocations requires threads, the memory pools grow freely");
+
+        Self {
+            throttle,
+            #[cfg(allocation_throttle)]
+            period_start: None,
+            #[cfg(allocation_throttle)]
+            pages: 0,
+        }
+    }
+
+    pub(crate) fn throttle(&self) -> AllocationThrottle {
+        self.throttle
+    }
+
+    /// The pages reservations can still allocate during the current period, or `None` when the
+    /// pools can grow freely.
+    pub(crate) fn allowance(&mut self) -> Option<PageAllowance> {
+        #[cfg(allocation_throttle)]
+        {
+            let elapsed = self.period_start.map(|start| start.elapsed());
+            let elapsed = match elapsed.filter(|elapsed| *elapsed < self.throttle.period) {
+                Some(elapsed) => elapsed,
+                None => {
+                    self.period_start = None;
+                    self.pages = 0;
+                    Duration::ZERO
+                }
+            };
+
+            Some(PageAllowance {
+                pages: self.throttle.max_pages.saturating_sub(self.pages),
+                retry_after: self.throttle.period - elapsed,
+            })
+        }
+
+        #[cfg(not(allocation_throttle))]
+        None
+    }
+
+    /// Record the pages allocated by a reservation, starting a period if none is running.
+    pub(crate) fn record_pages(&mut self, pages: u64) {
+        if pages == 0 {
+            return;
+        }
+
+        #[cfg(allocation_throttle)]
+        {
+            self.period_start
+                .get_or_insert_with(std::time::Instant::now);
+            self.pages += pages;
+        }
+    }
+}
+
+/// The pages a [throttle](AllocationThrottle) lets a reservation allocate.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PageAllowance {
+    /// The pages left in the current period.
+    pub(crate) pages: u64,
+    /// The time until the next period, when the pages run out.
+    pub(crate) retry_after: Duration,
+}
@@ -0,0 +1,119 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use super::{MemoryConfiguration, MemoryDeviceProperties, MemoryPoolOptions, PageGrowth, PoolType};
+
+/// The most pools created from a trace. Past it, sizes are grouped in power of two buckets, since
+/// every reservation looks for the smallest pool that fits.
+const MAX_TRACED_POOLS: usize = 32;
+
+/// The allocations done by a [memory management](super::MemoryManagement) while recording, used
+/// to size the pools of the next run with [MemoryConfiguration::from_trace].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllocationTrace {
+    /// The statistics of every reserved size, in bytes.
+    pub sizes: BTreeMap<u64, TracedSize>,
+}
+
+/// How often a size was reserved in an [allocation trace](AllocationTrace).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TracedSize {
+    /// The number of reservations of this size.
+    pub reservations: u64,
+    /// The highest number of allocations of this size alive at once.
+    pub peak_live: u64,
+}
+
+impl AllocationTrace {
+    /// Record a reservation of `size` bytes, while `live` allocations of that size are in use,
+    /// including the new one.
+    pub fn record(&mut self, size: u64, live: u64) {
+        let traced = self.sizes.entry(size).or_default();
+        traced.reservations += 1;
+        traced.peak_live = u64::max(traced.peak_live, live);
+    }
+}
+
+impl MemoryConfiguration {
+    /// Create pools sized for the allocations of a recorded [trace](AllocationTrace).
+    ///
+    /// Every traced size gets an exclusive pool with enough preallocated pages for the most
+    /// allocations of that size alive at once, so a repeatable workload doesn't allocate while
+    /// warming up. A last pool of the maximum page size handles the sizes missing from the trace.
+    pub fn from_trace(trace: &AllocationTrace, properties: &MemoryDeviceProperties) -> Self {
+        let alignment = properties.alignment;
+        let max_page_size = properties.max_page_size / alignment * alignment;
+        let page_size = |size: u64| u64::min(size.next_multiple_of(alignment), max_page_size);
+
+        let mut buckets = BTreeMap::<u64, u64>::new();
+        for (size, traced) in trace.sizes.iter() {
+            *buckets.entry(page_size(*size)).or_default() += traced.peak_live;
+        }
+
+        if buckets.len() > MAX_TRACED_POOLS {
+            let sizes = core::mem::take(&mut buckets);
+            for (size, pages) in sizes {
+                *buckets
+                    .entry(page_size(size.next_power_of_two()))
+                    .or_default() += pages;
+            }
+        }
+
+        let mut pools: Vec<_> = buckets
+            .into_iter()
+            .map(|(page_size, pages)| MemoryPoolOptions {
+                pool_type: PoolType::ExclusivePages,
+                page_size,
+                chunk_num_prealloc: pages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            })
+            .collect();
+
+        if pools.last().map(|pool| pool.page_size) != Some(max_page_size) {
+            pools.push(MemoryPoolOptions {
+                pool_type: PoolType::ExclusivePages,
+                page_size: max_page_size,
+                chunk_num_prealloc: 0,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            });
+        }
+
+        MemoryConfiguration::Custom(pools)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pools_hold_the_peak_of_every_size() {
+        let mut trace = AllocationTrace::default();
+        trace.record(100, 1);
+        trace.record(100, 2);
+        trace.record(100, 1);
+        trace.record(4096, 1);
+
+        let properties = MemoryDeviceProperties {
+            max_page_size: 1024 * 1024,
+            alignment: 32,
+            total_memory: None,
+        };
+        let MemoryConfiguration::Custom(pools) =
+            MemoryConfiguration::from_trace(&trace, &properties)
+        else {
+            panic!("Expected custom pools");
+        };
+
+        let pools: Vec<_> = pools
+            .iter()
+            .map(|pool| (pool.page_size, pool.chunk_num_prealloc))
+            .collect();
+        assert_eq!(pools, vec![(128, 2), (4096, 1), (1024 * 1024, 0)]);
+    }
+}
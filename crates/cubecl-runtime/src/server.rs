@@ -1,15 +1,19 @@
 use crate::{
     memory_management::{
         memory_pool::{SliceBinding, SliceHandle},
-        MemoryHandle, MemoryUsage,
+        AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError, MemoryHandle,
+        MemoryUsage,
     },
     storage::{BindingResource, ComputeStorage},
     ExecutionMode,
 };
-use alloc::vec::Vec;
-use core::{fmt::Debug, future::Future};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::{fmt::Debug, future::Future, time::Duration};
 use cubecl_common::benchmark::TimestampsResult;
 
+/// The total GPU execution time of every kernel, keyed by kernel name.
+pub type KernelTimings = BTreeMap<String, Duration>;
+
 /// The compute server is responsible for handling resources and computations over resources.
 ///
 /// Everything in the server is mutable, therefore it should be solely accessed through the
@@ -26,16 +30,52 @@ where
     type Feature: Ord + Copy + Debug + Send + Sync;
 
     /// Given a handle, returns the owned resource as bytes.
+    ///
+    /// The copy has to be enqueued before returning, so the data is the one written by the work
+    /// submitted before the call, even if more work is submitted before the future is polled.
     fn read(&mut self, binding: Binding) -> impl Future<Output = Vec<u8>> + Send + 'static;
 
     /// Given a resource handle, returns the storage resource.
     fn get_resource(&mut self, binding: Binding) -> BindingResource<Self>;
 
     /// Given a resource as bytes, stores it and returns the memory handle.
-    fn create(&mut self, data: &[u8]) -> Handle;
+    fn create(&mut self, data: &[u8]) -> Result<Handle, MemoryError>;
 
     /// Reserves `size` bytes in the storage, and returns a handle over them.
-    fn empty(&mut self, size: usize) -> Handle;
+    fn empty(&mut self, size: usize) -> Result<Handle, MemoryError>;
+
+    /// Set every byte of the given binding to `value`, after the work already submitted.
+    fn memset(&mut self, binding: Binding, value: u8);
+
+    /// Copy every byte of `src` to `dst`, which has the same size, after the work already
+    /// submitted.
+    fn copy(&mut self, src: Binding, dst: Binding);
+
+    /// Eagerly allocates memory so that a later allocation of `size` bytes doesn't have to.
+    fn preallocate(&mut self, size: u64) -> Result<(), MemoryError>;
+
+    /// Limit the number of bytes kept reserved by the memory pools, or remove the limit with `None`.
+    fn set_residency_budget(&mut self, budget: Option<u64>);
+
+    /// Let allocations ignore the growth limit of a throttled memory configuration while
+    /// `bypass` is true.
+    fn bypass_allocation_throttle(&mut self, bypass: bool);
+
+    /// Replace the memory pools with the ones of a new [configuration](MemoryConfiguration),
+    /// after waiting for the work using the current pools.
+    ///
+    /// Fails with [MemoryError::LiveAllocations] when some handles are still alive.
+    fn reconfigure_memory(
+        &mut self,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError>;
+
+    /// Start recording the reservations of the memory management in a new trace.
+    fn start_allocation_trace(&mut self);
+
+    /// Stop recording the reservations and return the trace, if one was started.
+    fn stop_allocation_trace(&mut self) -> Option<AllocationTrace>;
 
     /// Executes the `kernel` over the given memory `handles`.
     ///
@@ -53,6 +93,15 @@ where
         kind: ExecutionMode,
     );
 
+    /// Compile the given kernels ahead of time, without executing them, so their first
+    /// execution doesn't have to.
+    ///
+    /// Kernels that were already compiled are skipped. The default implementation does nothing,
+    /// which is fine for servers that don't compile kernels.
+    fn precompile(&mut self, kernels: Vec<Self::Kernel>, mode: ExecutionMode) {
+        let _ = (kernels, mode);
+    }
+
     /// Flush all outstanding tasks in the server.
     fn flush(&mut self);
 
@@ -67,13 +116,80 @@ where
     /// The current memory usage of the server.
     fn memory_usage(&self) -> MemoryUsage;
 
+    /// Whether the memory management places several allocations on the same page, so that
+    /// different handles may share a buffer.
+    fn uses_sub_slices(&self) -> bool;
+
+    /// Whether the server is still able to execute work.
+    ///
+    /// Returns an error once the server can't recover, for instance after its device was lost.
+    fn status(&self) -> Result<(), ServerError> {
+        Ok(())
+    }
+
+    /// Take the error of the last kernel that couldn't be created since the previous call, if
+    /// any.
+    ///
+    /// Unlike a lost device, the failure only affects that kernel, which wasn't executed, so the
+    /// caller can retry with a different one. The default implementation never reports an error.
+    fn take_kernel_error(&mut self) -> Option<ServerError> {
+        None
+    }
+
     /// Enable collecting timestamps.
     fn enable_timestamps(&mut self);
 
     /// Disable collecting timestamps.
     fn disable_timestamps(&mut self);
+
+    /// Wait for the completion of every task in the server and return the execution time of
+    /// every kernel profiled since the last call.
+    ///
+    /// Returns `None` when kernel profiling isn't enabled or supported by the server.
+    fn read_timings(&mut self) -> impl Future<Output = Option<KernelTimings>> + Send + 'static {
+        async { None }
+    }
+}
+
+/// Errors making a [compute server](ComputeServer) unusable.
+#[derive(Clone, PartialEq, Eq)]
+pub enum ServerError {
+    /// The device was lost, for instance after a driver reset, and every operation on it fails.
+    DeviceLost {
+        /// The reason given by the driver.
+        reason: String,
+    },
+    /// A kernel couldn't be created, because it needs more resources, such as registers, than
+    /// the device can give it. The kernel wasn't executed, but the server is still usable.
+    OutOfResources {
+        /// The name of the kernel.
+        kernel: String,
+        /// The reason given by the driver.
+        reason: String,
+    },
+}
+
+impl Debug for ServerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ServerError::DeviceLost { reason } => write!(f, "The device was lost: {reason}"),
+            ServerError::OutOfResources { kernel, reason } => write!(
+                f,
+                "The kernel {kernel} needs more resources than the device has: {reason}"
+            ),
+        }
+    }
 }
 
+impl core::fmt::Display for ServerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ServerError {}
+
 /// Server handle containing the [memory handle](MemoryManagement::Handle).
 #[derive(new, Debug)]
 pub struct Handle {
@@ -1,4 +1,5 @@
 use crate::{
+    memory_management::MemoryError,
     server::{Binding, ComputeServer},
     storage_id_type,
 };
@@ -75,7 +76,11 @@ pub trait ComputeStorage: Send {
     fn get(&mut self, handle: &StorageHandle) -> Self::Resource;
 
     /// Allocates `size` units of memory and returns a handle to it
-    fn alloc(&mut self, size: u64) -> StorageHandle;
+    ///
+    /// # Errors
+    ///
+    /// Returns [MemoryError::OutOfMemory] when the device can't allocate the memory.
+    fn alloc(&mut self, size: u64) -> Result<StorageHandle, MemoryError>;
 
     /// Deallocates the memory pointed by the given storage id.
     fn dealloc(&mut self, id: StorageId);
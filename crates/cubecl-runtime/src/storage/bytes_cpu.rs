@@ -1,11 +1,28 @@
 use super::{ComputeStorage, StorageHandle, StorageId, StorageUtilization};
-use alloc::alloc::{alloc, dealloc, Layout};
+use crate::memory_management::MemoryError;
+use alloc::{
+    alloc::{alloc, dealloc, Layout},
+    string::ToString,
+};
 use hashbrown::HashMap;
 
 /// The bytes storage maps ids to pointers of bytes in a contiguous layout.
 #[derive(Default)]
 pub struct BytesStorage {
     memory: HashMap<StorageId, AllocatedBytes>,
+    limit: Option<u64>,
+    allocated: u64,
+}
+
+impl BytesStorage {
+    /// Creates a storage that fails to allocate more than `limit` bytes in total, simulating a
+    /// device running out of memory.
+    pub fn with_limit(limit: u64) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Default::default()
+        }
+    }
 }
 
 impl core::fmt::Debug for BytesStorage {
@@ -69,7 +86,18 @@ impl ComputeStorage for BytesStorage {
         }
     }
 
-    fn alloc(&mut self, size: u64) -> StorageHandle {
+    fn alloc(&mut self, size: u64) -> Result<StorageHandle, MemoryError> {
+        let out_of_memory = |reason: &str| MemoryError::OutOfMemory {
+            size,
+            reason: reason.to_string(),
+        };
+
+        if let Some(limit) = self.limit {
+            if self.allocated + size > limit {
+                return Err(out_of_memory("storage limit reached"));
+            }
+        }
+
         let id = StorageId::new();
         let handle = StorageHandle {
             id,
@@ -77,18 +105,24 @@ impl ComputeStorage for BytesStorage {
         };
 
         unsafe {
-            let layout = Layout::array::<u8>(size as usize).unwrap();
+            let layout =
+                Layout::array::<u8>(size as usize).map_err(|_| out_of_memory("invalid layout"))?;
             let ptr = alloc(layout);
+            if ptr.is_null() {
+                return Err(out_of_memory("allocation failed"));
+            }
             let memory = AllocatedBytes { ptr, layout };
 
             self.memory.insert(id, memory);
         }
+        self.allocated += size;
 
-        handle
+        Ok(handle)
     }
 
     fn dealloc(&mut self, id: StorageId) {
         if let Some(memory) = self.memory.remove(&id) {
+            self.allocated -= memory.layout.size() as u64;
             unsafe {
                 dealloc(memory.ptr, memory.layout);
             }
@@ -103,7 +137,7 @@ mod tests {
     #[test]
     fn test_can_alloc_and_dealloc() {
         let mut storage = BytesStorage::default();
-        let handle_1 = storage.alloc(64);
+        let handle_1 = storage.alloc(64).unwrap();
 
         assert_eq!(handle_1.size(), 64);
         storage.dealloc(handle_1.id);
@@ -112,7 +146,7 @@ mod tests {
     #[test]
     fn test_slices() {
         let mut storage = BytesStorage::default();
-        let handle_1 = storage.alloc(64);
+        let handle_1 = storage.alloc(64).unwrap();
         let handle_2 = StorageHandle::new(
             handle_1.id,
             StorageUtilization {
@@ -134,4 +168,18 @@ mod tests {
         storage.dealloc(handle_1.id);
         assert_eq!(bytes, &[24, 25, 26, 27, 28, 29, 30, 31]);
     }
+
+    #[test]
+    fn test_limit_reports_out_of_memory() {
+        let mut storage = BytesStorage::with_limit(128);
+        let handle_1 = storage.alloc(96).unwrap();
+
+        assert!(matches!(
+            storage.alloc(64),
+            Err(MemoryError::OutOfMemory { size: 64, .. })
+        ));
+
+        storage.dealloc(handle_1.id);
+        assert!(storage.alloc(64).is_ok());
+    }
 }
@@ -28,21 +28,29 @@ pub fn autotune_execute(
 }
 
 pub fn init_client() -> ComputeClient<DummyServer, MutexComputeChannel<DummyServer>> {
-    let storage = BytesStorage::default();
+    init_client_with(BytesStorage::default(), MemoryConfiguration::default())
+}
+
+/// Creates a client over the given storage and memory configuration.
+pub fn init_client_with(
+    storage: BytesStorage,
+    config: MemoryConfiguration,
+) -> ComputeClient<DummyServer, MutexComputeChannel<DummyServer>> {
     let mem_properties = MemoryDeviceProperties {
         max_page_size: 1024 * 1024 * 512,
         alignment: 32,
+        total_memory: None,
     };
     let topology = HardwareProperties {
         plane_size_min: 32,
         plane_size_max: 32,
+        plane_size_actual: None,
         max_bindings: 32,
+        max_shared_memory_bytes: 48 * 1024,
+        max_cube_count: (u16::MAX as u32, u16::MAX as u32, u16::MAX as u32),
     };
-    let memory_management = MemoryManagement::from_configuration(
-        storage,
-        mem_properties.clone(),
-        MemoryConfiguration::default(),
-    );
+    let memory_management =
+        MemoryManagement::from_configuration(storage, mem_properties.clone(), config);
     let server = DummyServer::new(memory_management);
     let channel = MutexComputeChannel::new(server);
     ComputeClient::new(
@@ -3,6 +3,23 @@ use cubecl_runtime::storage::BytesResource;
 /// The DummyKernel trait should be implemented for every supported operation
 pub trait DummyKernel: Sync + Send + 'static + core::fmt::Debug {
     fn compute(&self, resources: &mut [&BytesResource]);
+
+    /// Whether executing the kernel loses the device, like a kernel hanging the GPU.
+    fn loses_device(&self) -> bool {
+        false
+    }
+}
+
+/// A kernel losing the device when executed.
+#[derive(Debug)]
+pub struct DummyDeviceLoss;
+
+impl DummyKernel for DummyDeviceLoss {
+    fn compute(&self, _resources: &mut [&BytesResource]) {}
+
+    fn loses_device(&self) -> bool {
+        true
+    }
 }
 
 /// Contains the algorithm for element-wise addition
@@ -4,12 +4,14 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use super::DummyKernel;
-use cubecl_runtime::memory_management::MemoryUsage;
+use cubecl_runtime::memory_management::{
+    AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError, MemoryUsage,
+};
 use cubecl_runtime::server::CubeCount;
 use cubecl_runtime::storage::{BindingResource, ComputeStorage};
 use cubecl_runtime::{
     memory_management::MemoryManagement,
-    server::{Binding, ComputeServer, Handle},
+    server::{Binding, ComputeServer, Handle, ServerError},
     storage::BytesStorage,
     ExecutionMode,
 };
@@ -20,6 +22,7 @@ use cubecl_runtime::{
 pub struct DummyServer {
     memory_management: MemoryManagement<BytesStorage>,
     timestamps: KernelTimestamps,
+    device_lost: bool,
 }
 
 #[derive(Debug)]
@@ -50,9 +53,13 @@ impl ComputeServer for DummyServer {
     type Feature = ();
 
     fn read(&mut self, binding: Binding) -> impl Future<Output = Vec<u8>> + 'static {
-        let bytes_handle = self.memory_management.get(binding.memory);
-        let bytes = self.memory_management.storage().get(&bytes_handle);
-        async move { bytes.read().to_vec() }
+        let bytes = self.memory_management.get_resource(
+            binding.memory,
+            binding.offset_start,
+            binding.offset_end,
+        );
+        let data = bytes.read().to_vec();
+        async move { data }
     }
 
     fn get_resource(&mut self, binding: Binding) -> BindingResource<Self> {
@@ -60,24 +67,71 @@ impl ComputeServer for DummyServer {
         BindingResource::new(binding, self.memory_management.storage().get(&handle))
     }
 
-    fn create(&mut self, data: &[u8]) -> Handle {
-        let handle = self.empty(data.len());
+    fn create(&mut self, data: &[u8]) -> Result<Handle, MemoryError> {
+        let handle = self.empty(data.len())?;
         let resource = self.get_resource(handle.clone().binding());
         let bytes = resource.resource().write();
         for (i, val) in data.iter().enumerate() {
             bytes[i] = *val;
         }
 
-        handle
+        Ok(handle)
     }
 
-    fn empty(&mut self, size: usize) -> Handle {
-        Handle::new(
-            self.memory_management.reserve(size as u64, None),
+    fn empty(&mut self, size: usize) -> Result<Handle, MemoryError> {
+        Ok(Handle::new(
+            self.memory_management.reserve(size as u64, None)?,
             None,
             None,
             size as u64,
-        )
+        ))
+    }
+
+    fn memset(&mut self, binding: Binding, value: u8) {
+        let bytes = self.memory_management.get_resource(
+            binding.memory,
+            binding.offset_start,
+            binding.offset_end,
+        );
+        bytes.write().fill(value);
+    }
+
+    fn copy(&mut self, src: Binding, dst: Binding) {
+        let src = self
+            .memory_management
+            .get_resource(src.memory, src.offset_start, src.offset_end);
+        let dst = self
+            .memory_management
+            .get_resource(dst.memory, dst.offset_start, dst.offset_end);
+        dst.write().copy_from_slice(src.read());
+    }
+
+    fn preallocate(&mut self, size: u64) -> Result<(), MemoryError> {
+        self.memory_management.preallocate(size)
+    }
+
+    fn set_residency_budget(&mut self, budget: Option<u64>) {
+        self.memory_management.set_residency_budget(budget);
+    }
+
+    fn bypass_allocation_throttle(&mut self, bypass: bool) {
+        self.memory_management.bypass_throttle(bypass);
+    }
+
+    fn reconfigure_memory(
+        &mut self,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError> {
+        self.memory_management.reconfigure(&properties, config)
+    }
+
+    fn start_allocation_trace(&mut self) {
+        self.memory_management.start_trace();
+    }
+
+    fn stop_allocation_trace(&mut self) -> Option<AllocationTrace> {
+        self.memory_management.stop_trace()
     }
 
     unsafe fn execute(
@@ -95,6 +149,7 @@ impl ComputeServer for DummyServer {
         let mut resources: Vec<_> = bind_resources.iter().map(|x| x.resource()).collect();
 
         kernel.compute(&mut resources);
+        self.device_lost |= kernel.loses_device();
     }
 
     fn flush(&mut self) {
@@ -124,6 +179,20 @@ impl ComputeServer for DummyServer {
         self.memory_management.memory_usage()
     }
 
+    fn uses_sub_slices(&self) -> bool {
+        self.memory_management.uses_sub_slices()
+    }
+
+    fn status(&self) -> Result<(), ServerError> {
+        if self.device_lost {
+            Err(ServerError::DeviceLost {
+                reason: "lost by a dummy kernel".to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
     fn enable_timestamps(&mut self) {
         self.timestamps.enable();
     }
@@ -138,6 +207,7 @@ impl DummyServer {
         Self {
             memory_management,
             timestamps: KernelTimestamps::Disabled,
+            device_lost: false,
         }
     }
 }
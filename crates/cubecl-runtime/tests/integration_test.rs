@@ -1,15 +1,22 @@
 mod dummy;
 
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
 
 use crate::dummy::autotune_execute;
 use crate::dummy::TEST_TUNER;
-use crate::dummy::{client, DummyDevice, DummyElementwiseAddition};
+use crate::dummy::{client, DummyDevice, DummyDeviceLoss, DummyElementwiseAddition};
 
 #[cfg(autotune_persistent_cache)]
 use crate::dummy::{TUNER_DEVICE_ID, TUNER_PREFIX};
 
-use cubecl_runtime::server::CubeCount;
+use cubecl_runtime::memory_management::{
+    AllocationThrottle, MemoryConfiguration, MemoryError, MemoryPoolOptions, PageGrowth, PoolType,
+};
+use cubecl_runtime::server::{CubeCount, ServerError};
+use cubecl_runtime::storage::BytesStorage;
 use cubecl_runtime::ComputeRuntime;
 
 #[allow(unused)]
@@ -38,6 +45,309 @@ fn empty_allocates_memory() {
     assert_eq!(empty_resource.len(), 4);
 }
 
+#[test]
+fn memset_sets_every_byte() {
+    let client = client(&DummyDevice);
+    let handle = client.create(&[0, 1, 2, 3, 4, 5]);
+
+    client.memset(&handle, 7);
+
+    assert_eq!(client.read(handle.binding()), vec![7; 6]);
+}
+
+#[test]
+fn copy_copies_the_first_bytes() {
+    let client = client(&DummyDevice);
+    let src = client.create(&[0, 1, 2, 3, 4, 5, 6, 7]);
+    let dst = client.create(&[9; 12]);
+
+    client.copy(&src, &dst, 4);
+
+    assert_eq!(
+        client.read(dst.binding()),
+        vec![0, 1, 2, 3, 9, 9, 9, 9, 9, 9, 9, 9]
+    );
+}
+
+#[test]
+fn copy_sees_the_writes_submitted_before() {
+    let client = client(&DummyDevice);
+    let src = client.create(&[0, 1, 2, 3]);
+    let dst = client.empty(4);
+
+    client.memset(&src, 7);
+    client.copy(&src, &dst, 4);
+    client.memset(&src, 0);
+
+    assert_eq!(client.read(dst.binding()), vec![7; 4]);
+}
+
+#[test]
+#[should_panic(expected = "overlapping")]
+fn copy_rejects_overlapping_ranges() {
+    let client = client(&DummyDevice);
+    let handle = client.create(&[0; 12]);
+
+    client.copy(&handle, &handle.clone().offset_start(4), 8);
+}
+
+#[test]
+fn copy_between_handles_of_the_same_page() {
+    // A single page fits in the storage, so both handles are slices of it and share a buffer.
+    let client = dummy::init_client_with(
+        BytesStorage::with_limit(1024),
+        MemoryConfiguration::Custom(vec![MemoryPoolOptions {
+            page_size: 1024,
+            chunk_num_prealloc: 0,
+            pool_type: PoolType::SlicedPages {
+                max_slice_size: 256,
+            },
+            dealloc_period: None,
+            alignment: None,
+            page_growth: PageGrowth::Constant,
+        }]),
+    );
+    let src = client.create(&[0, 1, 2, 3]);
+    let dst = client.create(&[9; 4]);
+
+    client.copy(&src, &dst, 4);
+
+    assert_eq!(client.read(dst.binding()), vec![0, 1, 2, 3]);
+    assert_eq!(client.read(src.binding()), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn read_async_is_ordered_before_later_writes() {
+    let client = client(&DummyDevice);
+    let handle = client.create(&[0, 1, 2, 3]);
+
+    let read = client.read_async(handle.clone().binding());
+    client.memset(&handle, 7);
+
+    assert_eq!(cubecl_common::future::block_on(read), vec![0, 1, 2, 3]);
+    assert_eq!(client.read(handle.binding()), vec![7; 4]);
+}
+
+#[test]
+fn read_range_returns_part_of_the_resource() {
+    let client = client(&DummyDevice);
+    let handle = client.create(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+    assert_eq!(client.read_range(&handle, 0, 3), vec![0, 1, 2]);
+    assert_eq!(client.read_range(&handle, 5, 4), vec![5, 6, 7, 8]);
+}
+
+#[test]
+fn read_range_of_zero_bytes_is_empty() {
+    let client = client(&DummyDevice);
+    let handle = client.create(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+    assert_eq!(client.read_range(&handle, 0, 0), Vec::<u8>::new());
+    assert_eq!(client.read_range(&handle, 6, 0), Vec::<u8>::new());
+    assert_eq!(client.read_range(&handle, 10, 0), Vec::<u8>::new());
+}
+
+#[test]
+#[should_panic]
+fn read_range_past_the_end_panics() {
+    let client = client(&DummyDevice);
+    let handle = client.create(&[0, 1, 2, 3]);
+
+    client.read_range(&handle, 2, 3);
+}
+
+#[test]
+fn empty_async_allocates_memory() {
+    let client = client(&DummyDevice);
+    let handle = cubecl_common::future::block_on(client.empty_async(4));
+    let empty_resource = client.read(handle.binding());
+
+    assert_eq!(empty_resource.len(), 4);
+}
+
+#[test]
+fn oom_callback_is_called_outside_the_server_lock() {
+    // A single page fits in the storage, so a second one runs out of memory.
+    let client = dummy::init_client_with(
+        BytesStorage::with_limit(1024),
+        MemoryConfiguration::Custom(vec![MemoryPoolOptions {
+            page_size: 1024,
+            chunk_num_prealloc: 0,
+            pool_type: PoolType::ExclusivePages,
+            dealloc_period: None,
+            alignment: None,
+            page_growth: PageGrowth::Constant,
+        }]),
+    );
+    let calls = Arc::new(AtomicU64::new(0));
+    let cached = Arc::new(Mutex::new(Some(client.empty(1024))));
+
+    let callback_client = client.clone();
+    let callback_calls = calls.clone();
+    let callback_cached = cached.clone();
+    client.set_oom_callback(Box::new(move |requested| {
+        callback_calls.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(requested, 1024);
+        // Calling back into the client mustn't deadlock.
+        callback_client.memory_usage();
+        // Releasing the cached handle frees its page for the retry.
+        callback_cached.lock().unwrap().take().is_some()
+    }));
+
+    let result = client.try_empty(1024);
+
+    assert!(result.is_ok());
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn oom_callback_is_not_called_for_too_large_pages() {
+    let client = dummy::init_client();
+    let calls = Arc::new(AtomicU64::new(0));
+
+    let callback_calls = calls.clone();
+    client.set_oom_callback(Box::new(move |_| {
+        callback_calls.fetch_add(1, Ordering::Relaxed);
+        true
+    }));
+
+    // Bigger than the maximum page size, releasing memory can't help.
+    let result = client.try_empty(1024 * 1024 * 1024);
+
+    assert!(matches!(result, Err(MemoryError::PageTooLarge { .. })));
+    assert_eq!(calls.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+#[cfg(allocation_throttle)]
+fn throttled_allocations_wait_outside_the_server() {
+    let period = std::time::Duration::from_millis(200);
+    let client = dummy::init_client_with(
+        BytesStorage::default(),
+        MemoryConfiguration::Throttled {
+            config: Box::new(MemoryConfiguration::Custom(vec![MemoryPoolOptions {
+                page_size: 1024,
+                chunk_num_prealloc: 0,
+                pool_type: PoolType::ExclusivePages,
+                dealloc_period: None,
+                alignment: None,
+                page_growth: PageGrowth::Constant,
+            }])),
+            throttle: AllocationThrottle {
+                max_pages: 1,
+                period,
+            },
+        },
+    );
+
+    let start = std::time::Instant::now();
+    let _first = client.empty(1024);
+    let throttled_client = client.clone();
+    let throttled = std::thread::spawn(move || throttled_client.empty(1024));
+
+    // The server stays available while the second allocation waits for the next period.
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    client.memory_usage();
+    assert!(!throttled.is_finished());
+
+    let _second = throttled.join().unwrap();
+    assert!(start.elapsed() >= period);
+}
+
+#[test]
+fn reserve_preallocates_memory_only_once() {
+    let client = dummy::init_client_with(
+        BytesStorage::default(),
+        MemoryConfiguration::Custom(vec![MemoryPoolOptions {
+            page_size: 1024,
+            chunk_num_prealloc: 0,
+            pool_type: PoolType::ExclusivePages,
+            dealloc_period: None,
+            alignment: None,
+            page_growth: PageGrowth::Constant,
+        }]),
+    );
+
+    client.reserve(1024).unwrap();
+    client.reserve(1024).unwrap();
+    assert_eq!(client.memory_usage().bytes_reserved, 1024);
+
+    let _handle = client.empty(1024);
+    assert_eq!(client.memory_usage().bytes_reserved, 1024);
+}
+
+#[test]
+fn sync_and_read_report_a_lost_device() {
+    let client = dummy::init_client();
+    let handle = client.create(&[0, 1, 2]);
+
+    client.execute(
+        Arc::new(DummyDeviceLoss),
+        CubeCount::Static(1, 1, 1),
+        Vec::new(),
+    );
+
+    assert!(matches!(
+        cubecl_common::future::block_on(client.try_sync()),
+        Err(ServerError::DeviceLost { .. })
+    ));
+    assert!(matches!(
+        client.try_read(handle.binding()),
+        Err(ServerError::DeviceLost { .. })
+    ));
+}
+
+#[test]
+fn scratch_reuses_released_buffers() {
+    let client = dummy::init_client();
+
+    let first = client.scratch(64);
+    drop(first);
+    let second = client.scratch(64);
+    assert_eq!(client.memory_usage().number_allocs, 1);
+
+    // The second buffer is still used, so a new one is created.
+    let _third = client.scratch(64);
+    assert_eq!(client.memory_usage().number_allocs, 2);
+
+    drop(second);
+    client.clear_scratch();
+    assert_eq!(client.memory_usage().number_allocs, 1);
+}
+
+#[test]
+fn memory_scope_releases_its_handles_when_dropped() {
+    let client = dummy::init_client();
+
+    let scope = client.memory_scope();
+    let lhs = scope.create(&[0, 1, 2]);
+    let rhs = scope.empty(3);
+    assert_eq!(client.memory_usage().number_allocs, 2);
+    assert_eq!(client.read(lhs.clone().binding()).len(), 3);
+    assert_eq!(client.read(rhs.clone().binding()).len(), 3);
+
+    drop(scope);
+    assert_eq!(client.memory_usage().number_allocs, 0);
+
+    let value = client.scope(|scope| client.read(scope.create(&[4]).clone().binding()));
+    assert_eq!(value, vec![4]);
+    assert_eq!(client.memory_usage().number_allocs, 0);
+}
+
+#[test]
+fn memory_scope_handles_stay_valid_while_it_grows() {
+    let client = dummy::init_client();
+    let scope = client.memory_scope();
+
+    let first = scope.create(&[7]);
+    for _ in 0..1000 {
+        scope.empty(1);
+    }
+
+    assert_eq!(client.read(first.clone().binding()), vec![7]);
+    assert_eq!(client.memory_usage().number_allocs, 1001);
+}
+
 #[test]
 fn execute_elementwise_addition() {
     let client = client(&DummyDevice);
@@ -1,5 +1,5 @@
 use cubecl_core::ir::{AtomicOp, Variable};
-use rspirv::spirv::{MemorySemantics, Scope};
+use rspirv::spirv::{Capability, MemorySemantics, Scope};
 
 use crate::{item::Elem, SpirvCompiler, SpirvTarget};
 
@@ -98,8 +98,16 @@ impl<T: SpirvTarget> SpirvCompiler<T> {
                 let memory = self.const_u32(Scope::Device as u32);
                 let semantics = self.const_u32(MemorySemantics::UNIFORM_MEMORY.bits());
 
-                self.atomic_i_add(ty, Some(out_id), lhs_id, memory, semantics, rhs_id)
-                    .unwrap();
+                match out_ty.elem() {
+                    Elem::Float(_) => {
+                        self.capabilities.insert(Capability::AtomicFloat32AddEXT);
+                        self.atomic_f_add_ext(ty, Some(out_id), lhs_id, memory, semantics, rhs_id)
+                            .unwrap()
+                    }
+                    _ => self
+                        .atomic_i_add(ty, Some(out_id), lhs_id, memory, semantics, rhs_id)
+                        .unwrap(),
+                };
                 self.write(&out, out_id);
             }
             AtomicOp::Sub(op) => {
@@ -116,8 +124,18 @@ impl<T: SpirvTarget> SpirvCompiler<T> {
                 let memory = self.const_u32(Scope::Device as u32);
                 let semantics = self.const_u32(MemorySemantics::UNIFORM_MEMORY.bits());
 
-                self.atomic_i_sub(ty, Some(out_id), lhs_id, memory, semantics, rhs_id)
-                    .unwrap();
+                match out_ty.elem() {
+                    // There's no float atomic sub, so the negated value is added instead.
+                    Elem::Float(_) => {
+                        self.capabilities.insert(Capability::AtomicFloat32AddEXT);
+                        let rhs_id = self.f_negate(ty, None, rhs_id).unwrap();
+                        self.atomic_f_add_ext(ty, Some(out_id), lhs_id, memory, semantics, rhs_id)
+                            .unwrap()
+                    }
+                    _ => self
+                        .atomic_i_sub(ty, Some(out_id), lhs_id, memory, semantics, rhs_id)
+                        .unwrap(),
+                };
                 self.write(&out, out_id);
             }
             AtomicOp::Max(op) => {
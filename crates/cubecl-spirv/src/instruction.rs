@@ -324,8 +324,13 @@ impl<T: SpirvTarget> SpirvCompiler<T> {
                     let rhs_id = self.read(&rhs);
                     let out_id = self.write_id(&out);
 
-                    if matches!(lhs.elem(), Elem::Int(_, _)) {
+                    if let Elem::Int(width, _) = lhs.elem() {
+                        let input = match lhs.item() {
+                            Item::Vector(_, 4) if width == 8 => Capability::DotProductInput4x8Bit,
+                            _ => Capability::DotProductInputAll,
+                        };
                         self.capabilities.insert(Capability::DotProduct);
+                        self.capabilities.insert(input);
                     }
 
                     match (lhs.elem(), rhs.elem()) {
@@ -364,6 +364,8 @@ impl<T: SpirvTarget> SpirvCompiler<T> {
                 self.capabilities.insert(Capability::Int64);
                 Elem::Int(64, true)
             }
+            core::Elem::AtomicFloat(FloatKind::F32) => Elem::Float(32),
+            core::Elem::AtomicFloat(kind) => panic!("atomic<{kind:?}> isn't supported yet"),
             core::Elem::AtomicInt(IntKind::I8) => {
                 self.capabilities.insert(Capability::Int8);
                 Elem::Int(8, true)
@@ -1,10 +1,11 @@
 use std::fmt::{Debug, Display};
 
-use cubecl_core::{ir::Binding, CompilerRepresentation};
+use cubecl_core::{compute::CompiledKernel, ir::Binding, CompilerRepresentation};
 use cubecl_opt::Optimizer;
 use rspirv::{
     binary::{Assemble, Disassemble},
-    dr::Module,
+    dr::{Module, Operand},
+    spirv::Capability,
 };
 
 mod atomic;
@@ -46,7 +47,30 @@ impl Display for SpirvKernel {
 }
 
 impl SpirvKernel {
+    /// The SPIR-V binary of the module, as given to the driver.
     pub fn assemble(&self) -> Vec<u32> {
         self.module.assemble()
     }
+
+    /// Whether the module declares the given capability.
+    pub fn has_capability(&self, capability: Capability) -> bool {
+        self.module.capabilities.iter().any(|inst| {
+            matches!(inst.operands.first(), Some(Operand::Capability(cap)) if *cap == capability)
+        })
+    }
+}
+
+/// Access to the SPIR-V binary of a [compiled kernel](CompiledKernel), for tooling such as
+/// disassemblers or kernel explorers.
+///
+/// Kernels can be compiled without a device with [CubeTask](cubecl_core::prelude::CubeTask).
+pub trait SpirvBinary {
+    /// The SPIR-V words of the kernel, or `None` when it has no SPIR-V representation.
+    fn spirv_words(&self) -> Option<Vec<u32>>;
+}
+
+impl<T: SpirvTarget> SpirvBinary for CompiledKernel<SpirvCompiler<T>> {
+    fn spirv_words(&self) -> Option<Vec<u32>> {
+        self.repr.as_ref().map(SpirvKernel::assemble)
+    }
 }
@@ -64,6 +64,12 @@ impl SpirvTarget for GLCompute {
         if caps.contains(&Capability::CooperativeMatrixKHR) {
             b.extension("SPV_KHR_cooperative_matrix");
         }
+        if caps.contains(&Capability::DotProduct) {
+            b.extension("SPV_KHR_integer_dot_product");
+        }
+        if caps.contains(&Capability::AtomicFloat32AddEXT) {
+            b.extension("SPV_EXT_shader_atomic_float_add");
+        }
 
         b.memory_model(AddressingModel::Logical, MemoryModel::Vulkan);
         b.entry_point(ExecutionModel::GLCompute, main, "main", interface);
@@ -1 +1,2 @@
+pub mod persistent;
 pub mod reduce;
@@ -0,0 +1,68 @@
+use cubecl_core as cubecl;
+use cubecl_core::prelude::*;
+use cubecl_runtime::server::Handle;
+
+/// Dispatch of a persistent kernel, where a fixed number of cubes loop over the work items
+/// instead of launching one cube per item.
+///
+/// Every cube takes the next item with [next_work_item] until they are all processed, so the
+/// cubes that finish early pick up the remaining work. This helps irregular workloads, where
+/// items take different amounts of time, and avoids relaunching kernels over many small tiles.
+pub struct PersistentDispatch {
+    /// The cubes to launch.
+    pub cube_count: CubeCount,
+    /// The number of work items to process.
+    pub num_work_items: u32,
+    counter: Handle,
+}
+
+impl PersistentDispatch {
+    /// Allocate the work counter for `num_work_items` items, processed by at most `max_cubes`
+    /// cubes.
+    ///
+    /// Launch as many cubes as the device can run at once, since the extra cubes only wait for
+    /// the others to finish. When `max_cubes` is `None`, the maximum cube count of the device is
+    /// used, which is usually more than it can run at once.
+    pub fn new<R: Runtime>(
+        client: &ComputeClient<R::Server, R::Channel>,
+        num_work_items: u32,
+        max_cubes: Option<u32>,
+    ) -> Self {
+        let max_cubes =
+            max_cubes.unwrap_or(client.properties().hardware_properties().max_cube_count.0);
+        let num_cubes = Ord::max(Ord::min(num_work_items, max_cubes), 1);
+
+        Self {
+            cube_count: CubeCount::Static(num_cubes, 1, 1),
+            num_work_items,
+            counter: client.create(u32::as_bytes(&[0])),
+        }
+    }
+
+    /// The argument of the work counter, passed to [next_work_item].
+    ///
+    /// The counter isn't reset by the kernel, so a dispatch can only be launched once.
+    pub fn counter_arg<R: Runtime>(&self) -> TensorArg<'_, R> {
+        unsafe { TensorArg::from_raw_parts::<AtomicU32>(&self.counter, &[1], &[1], 1) }
+    }
+}
+
+/// Take the next work item for the current cube, shared by all its units.
+///
+/// Items are taken in increasing order, so the loop ends once the returned item is at least the
+/// [number of work items](PersistentDispatch::num_work_items).
+#[cube]
+pub fn next_work_item(counter: &Tensor<AtomicU32>) -> u32 {
+    let mut item = SharedMemory::<u32>::new(1);
+
+    if UNIT_POS == 0 {
+        item[0] = AtomicU32::add(&counter[0], 1);
+    }
+    sync_units();
+
+    let next = item[0];
+    // The next item can't be written before every unit has read this one.
+    sync_units();
+
+    next
+}
@@ -0,0 +1,5 @@
+pub mod test;
+
+mod base;
+
+pub use base::*;
@@ -0,0 +1,60 @@
+#![allow(missing_docs)]
+
+use cubecl_core as cubecl;
+use cubecl_core::prelude::*;
+
+use crate::persistent::{next_work_item, PersistentDispatch};
+
+#[macro_export]
+macro_rules! testgen_persistent {
+    () => {
+        use cubecl_std::persistent::test::test_persistent_dispatch;
+
+        #[test]
+        pub fn persistent_dispatch_processes_every_item() {
+            test_persistent_dispatch::<TestRuntime>(&Default::default(), 100, 4);
+        }
+
+        #[test]
+        pub fn persistent_dispatch_with_more_cubes_than_items() {
+            test_persistent_dispatch::<TestRuntime>(&Default::default(), 3, 8);
+        }
+    };
+}
+
+#[cube(launch_unchecked)]
+fn kernel_persistent_double(counter: &Tensor<AtomicU32>, output: &mut Tensor<u32>, num_items: u32) {
+    loop {
+        let item = next_work_item(counter);
+        if item >= num_items {
+            break;
+        }
+
+        if UNIT_POS == 0 {
+            output[item] = item * 2;
+        }
+    }
+}
+
+pub fn test_persistent_dispatch<R: Runtime>(device: &R::Device, num_items: u32, max_cubes: u32) {
+    let client = R::client(device);
+    let dispatch = PersistentDispatch::new::<R>(&client, num_items, Some(max_cubes));
+    let output = client.empty(num_items as usize * core::mem::size_of::<u32>());
+
+    unsafe {
+        kernel_persistent_double::launch_unchecked::<R>(
+            &client,
+            dispatch.cube_count.clone(),
+            CubeDim::new(32, 1, 1),
+            dispatch.counter_arg(),
+            TensorArg::from_raw_parts::<u32>(&output, &[1], &[num_items as usize], 1),
+            ScalarArg::new(num_items),
+        );
+    }
+
+    let actual = client.read(output.binding());
+    let actual = u32::from_bytes(&actual);
+    let expected: Vec<u32> = (0..num_items).map(|item| item * 2).collect();
+
+    assert_eq!(actual, &expected);
+}
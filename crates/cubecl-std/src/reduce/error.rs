@@ -0,0 +1,42 @@
+use cubecl_core::ir::Elem;
+use std::fmt::{Debug, Display};
+
+/// Reasons why a reduction can't be launched.
+pub enum ReduceLaunchError {
+    /// Plane operations are not supported.
+    PlaneOperationsUnavailable,
+    /// The element type is not supported.
+    TypeUnavailable(Elem),
+    /// The line size isn't supported by the runtime, or doesn't divide the number of elements of
+    /// the input.
+    InvalidLineSize { line_size: u32, num_elems: usize },
+}
+
+impl Debug for ReduceLaunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReduceLaunchError::PlaneOperationsUnavailable => {
+                write!(f, "Unable to launch reduce because plane operations are not supported.")
+            }
+            ReduceLaunchError::TypeUnavailable(elem) => write!(
+                f,
+                "Unable to launch reduce because type {elem:?} is not supported."
+            ),
+            ReduceLaunchError::InvalidLineSize {
+                line_size,
+                num_elems,
+            } => write!(
+                f,
+                "Unable to launch reduce because the line size {line_size} is not supported or does not divide the {num_elems} elements of the input."
+            ),
+        }
+    }
+}
+
+impl Display for ReduceLaunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for ReduceLaunchError {}
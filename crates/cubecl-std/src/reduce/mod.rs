@@ -1,3 +1,6 @@
 pub mod test;
 
+mod error;
 pub mod sum;
+
+pub use error::*;
@@ -1,11 +1,22 @@
 use cubecl_core as cubecl;
 use cubecl_core::prelude::*;
+use cubecl_core::Feature;
+
+use super::ReduceLaunchError;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ReduceConfig {
     pub line_size: u32,
     pub plane_size: u32,
     pub num_planes: u32,
+    /// Add the elements in a fixed order, so the result is bitwise identical across runs and
+    /// devices.
+    ///
+    /// Floating-point addition isn't associative, so the result of the plane reduction depends
+    /// on how the device combines the values of a plane. The deterministic path instead adds
+    /// partial sums pairwise through shared memory, which needs a synchronization per level of
+    /// the tree and is slower. It doesn't require plane operations.
+    pub deterministic: bool,
 }
 
 /// Compute the sum of all elements of `input` and write it to the first element of `output`.
@@ -17,7 +28,69 @@ pub fn reduce_sum<N: Numeric>(
     output: &mut Tensor<Line<N>>,
     #[comptime] config: ReduceConfig,
 ) {
-    reduce_sum_vector(&input.to_slice(), &mut output.to_slice_mut(), config);
+    if config.deterministic {
+        reduce_sum_tree(&input.to_slice(), &mut output.to_slice_mut(), config);
+    } else {
+        reduce_sum_vector(&input.to_slice(), &mut output.to_slice_mut(), config);
+    }
+}
+
+/// Launch [reduce_sum] with one cube of `config.num_planes` planes, after checking that the
+/// device and the input support it.
+pub fn launch_reduce_sum<R: Runtime, N: Numeric>(
+    client: &ComputeClient<R::Server, R::Channel>,
+    input: TensorHandleRef<'_, R>,
+    output: TensorHandleRef<'_, R>,
+    config: ReduceConfig,
+) -> Result<(), ReduceLaunchError> {
+    let num_elems = input.shape.iter().product();
+    check_availability::<R, N>(client, config, num_elems)?;
+
+    let line_size = config.line_size as u8;
+    unsafe {
+        reduce_sum::launch_unchecked::<N, R>(
+            client,
+            CubeCount::Static(1, 1, 1),
+            CubeDim::new(config.plane_size, config.num_planes, 1),
+            input.as_tensor_arg(line_size),
+            output.as_tensor_arg(line_size),
+            config,
+        );
+    }
+
+    Ok(())
+}
+
+/// Check that [reduce_sum] can run on the device with the given config, for an input of
+/// `num_elems` elements.
+pub fn check_availability<R: Runtime, N: Numeric>(
+    client: &ComputeClient<R::Server, R::Channel>,
+    config: ReduceConfig,
+    num_elems: usize,
+) -> Result<(), ReduceLaunchError> {
+    let properties = client.properties();
+
+    if !config.deterministic && !properties.feature_enabled(Feature::Plane) {
+        return Err(ReduceLaunchError::PlaneOperationsUnavailable);
+    }
+
+    let elem = N::as_elem();
+    if !properties.feature_enabled(Feature::Type(elem)) {
+        return Err(ReduceLaunchError::TypeUnavailable(elem));
+    }
+
+    let line_size = config.line_size as usize;
+    let supported = R::supported_line_sizes()
+        .iter()
+        .any(|size| *size as usize == line_size);
+    if !supported || num_elems.next_multiple_of(line_size) != num_elems {
+        return Err(ReduceLaunchError::InvalidLineSize {
+            line_size: config.line_size,
+            num_elems,
+        });
+    }
+
+    Ok(())
 }
 
 #[cube]
@@ -59,3 +132,37 @@ fn reduce_sum_vector<N: Numeric>(
         output[0] = sum;
     }
 }
+
+#[cube]
+fn reduce_sum_tree<N: Numeric>(
+    input: &Slice<Line<N>>,
+    output: &mut SliceMut<Line<N>>,
+    #[comptime] config: ReduceConfig,
+) {
+    let num_units = config.plane_size * config.num_planes;
+
+    // Every unit sums the same strided elements of the input on every run.
+    let mut sum = Line::empty(config.line_size).fill(N::from_int(0));
+    for i in range_stepped(UNIT_POS, input.len(), num_units) {
+        sum += input[i];
+    }
+
+    let mut memory = SharedMemory::new_lined(num_units, config.line_size);
+    memory[UNIT_POS] = sum;
+    sync_units();
+
+    // Add the partial sums pairwise, halving the number of sums at every level of the tree.
+    let mut stride = comptime!(num_units.next_power_of_two() / 2).runtime();
+    while stride > 0 {
+        if UNIT_POS < stride && UNIT_POS + stride < num_units {
+            let other = memory[UNIT_POS + stride];
+            memory[UNIT_POS] += other;
+        }
+        sync_units();
+        stride /= 2;
+    }
+
+    if UNIT_POS == 0 {
+        output[0] = memory[0];
+    }
+}
@@ -2,14 +2,18 @@
 
 use cubecl_core::{prelude::*, Feature};
 
-use crate::reduce::sum::{reduce_sum, ReduceConfig};
+use crate::reduce::sum::{check_availability, launch_reduce_sum, reduce_sum, ReduceConfig};
+use crate::reduce::ReduceLaunchError;
 
 #[macro_export]
 macro_rules! testgen_reduce {
     () => {
         use super::*;
         use cubecl_core::{CubeCount, CubeDim};
-        use cubecl_std::reduce::test::{impl_reduce_sum_test, TestCase, TestTensorParts};
+        use cubecl_std::reduce::test::{
+            impl_reduce_sum_deterministic_test, impl_reduce_sum_invalid_line_size_test,
+            impl_reduce_sum_test, TestCase, TestTensorParts,
+        };
 
         #[test]
         pub fn reduce_sum_vector_single_plane() {
@@ -210,6 +214,16 @@ macro_rules! testgen_reduce {
                 },
             )
         }
+
+        #[test]
+        pub fn reduce_sum_invalid_line_size() {
+            impl_reduce_sum_invalid_line_size_test::<TestRuntime, u32>(&Default::default())
+        }
+
+        #[test]
+        pub fn reduce_sum_deterministic_is_reproducible() {
+            impl_reduce_sum_deterministic_test::<TestRuntime>(&Default::default())
+        }
     };
 }
 
@@ -249,6 +263,7 @@ pub fn impl_reduce_sum_test<R: Runtime, N: Numeric + CubeElement + std::fmt::Dis
         line_size: test.line_size as u32,
         plane_size: test.cube_dim.x,
         num_planes: test.cube_dim.y,
+        deterministic: false,
     };
 
     unsafe {
@@ -285,6 +300,66 @@ pub fn impl_reduce_sum_test<R: Runtime, N: Numeric + CubeElement + std::fmt::Dis
     }
 }
 
+pub fn impl_reduce_sum_invalid_line_size_test<R: Runtime, N: Numeric>(device: &R::Device) {
+    let client = R::client(device);
+    if !client.properties().feature_enabled(Feature::Plane) {
+        // Can't execute the test.
+        return;
+    }
+
+    let config = ReduceConfig {
+        line_size: 4,
+        plane_size: 32,
+        num_planes: 1,
+        deterministic: false,
+    };
+
+    let result = check_availability::<R, N>(&client, config, 30);
+    assert!(matches!(
+        result,
+        Err(ReduceLaunchError::InvalidLineSize {
+            line_size: 4,
+            num_elems: 30
+        })
+    ));
+}
+
+pub fn impl_reduce_sum_deterministic_test<R: Runtime>(device: &R::Device) {
+    let client = R::client(device);
+
+    // Values of very different magnitudes, so the result depends on the order of the additions.
+    let values: Vec<f32> = (0..1000)
+        .map(|n| (n as f32 * 0.37).sin() * 10f32.powi(n % 7))
+        .collect();
+    let expected: f64 = values.iter().map(|value| *value as f64).sum();
+    let input = client.create(f32::as_bytes(&values));
+
+    let config = ReduceConfig {
+        line_size: 1,
+        plane_size: 32,
+        num_planes: 3,
+        deterministic: true,
+    };
+
+    let run = || {
+        let output = client.empty(core::mem::size_of::<f32>());
+        launch_reduce_sum::<R, f32>(
+            &client,
+            unsafe { TensorHandleRef::from_raw_parts(&input, &[1], &[values.len()], 4) },
+            unsafe { TensorHandleRef::from_raw_parts(&output, &[1], &[1], 4) },
+            config,
+        )
+        .unwrap();
+        f32::from_bytes(&client.read(output.binding()))[0]
+    };
+
+    let first = run();
+    for _ in 0..4 {
+        assert_eq!(run().to_bits(), first.to_bits());
+    }
+    assert!(((first as f64 - expected) / expected).abs() < 1e-4);
+}
+
 pub fn assert_approx_equal_abs<N: Numeric>(actual: &[N], expected: &[N], epsilon: f32) {
     for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
         let a = a.to_f32().unwrap();
@@ -1,27 +1,106 @@
 use std::sync::Arc;
 
 use cubecl_core::{
-    prelude::CompiledKernel, server::ComputeServer, Compiler, ExecutionMode, Feature,
+    prelude::CompiledKernel, server::ComputeServer, Compiler, ExecutionMode, Feature, KernelId,
 };
 use cubecl_runtime::DeviceProperties;
 use wgpu::{Adapter, ComputePipeline, Device, Queue};
 
-use crate::WgpuServer;
+use crate::{RuntimeOptions, WgpuServer, WgpuSetupError};
 
 pub trait WgpuCompiler: Compiler {
+    /// The execution mode kernels are actually compiled with when `mode` is requested.
+    ///
+    /// Compiled kernels and pipelines are keyed by this mode, so a cached kernel always has the
+    /// bound checks it was requested with.
+    fn execution_mode(_server: &WgpuServer<Self>, mode: ExecutionMode) -> ExecutionMode {
+        mode
+    }
+
     fn compile(
         server: &mut WgpuServer<Self>,
         kernel: <WgpuServer<Self> as ComputeServer>::Kernel,
         mode: ExecutionMode,
     ) -> CompiledKernel<Self>;
 
+    /// Create the pipeline of a compiled kernel.
+    ///
+    /// The `label` names every `wgpu` object created for the pipeline, see [kernel_label].
     fn create_pipeline(
         server: &mut WgpuServer<Self>,
         kernel: CompiledKernel<Self>,
+        label: &str,
         mode: ExecutionMode,
     ) -> Arc<ComputePipeline>;
 
+    /// Create a pipeline from a kernel previously stored in the pipeline cache of the server.
+    ///
+    /// Returns `None` when the compiler doesn't support it or the kernel isn't cached.
+    fn load_pipeline(
+        _server: &mut WgpuServer<Self>,
+        _kernel_id: &KernelId,
+        _label: &str,
+    ) -> Option<Arc<ComputePipeline>> {
+        None
+    }
+
     #[allow(async_fn_in_trait)]
-    async fn request_device(adapter: &Adapter) -> (Device, Queue);
+    async fn request_device(
+        adapter: &Adapter,
+        options: &RuntimeOptions,
+    ) -> Result<(Device, Queue), WgpuSetupError>;
     fn register_features(adapter: &Adapter, device: &Device, props: &mut DeviceProperties<Feature>);
 }
+
+/// Debug label of the `wgpu` objects created for a kernel, shown by graphics debuggers.
+///
+/// The name is shortened to the kernel type and its generics, and suffixed with a hash of the
+/// kernel id to tell different instances of the same kernel apart.
+pub(crate) fn kernel_label(name: &str, id: &KernelId) -> String {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    let name = name
+        .split("<")
+        .take_while(|it| !it.ends_with("Runtime"))
+        .map(|it| it.split(">").next().unwrap())
+        .map(|it| it.split("::").last().unwrap())
+        .collect::<Vec<_>>()
+        .join("_");
+    let mut hash = DefaultHasher::new();
+    id.hash(&mut hash);
+    let id = hash.finish();
+    format!("{name}_{id:#x}")
+}
+
+/// File name, without extension, used to dump a compiled kernel for debugging.
+///
+/// It's the [label](kernel_label) of the kernel, so dumps can be matched with the objects of a
+/// GPU capture.
+#[cfg(any(feature = "spirv-dump", feature = "wgsl-dump", feature = "debug-ir"))]
+pub(crate) fn dump_file_name(name: &str, id: &KernelId) -> String {
+    sanitize_filename::sanitize_with_options(
+        kernel_label(name, id),
+        sanitize_filename::Options {
+            replacement: "_",
+            ..Default::default()
+        },
+    )
+}
+
+/// Write the optimizer IR of a kernel to the `CUBECL_DEBUG_IR` directory, if set.
+///
+/// The IR doesn't depend on the target, so it's dumped the same way for every compiler.
+#[cfg(feature = "debug-ir")]
+pub(crate) fn dump_ir<C: Compiler>(
+    kernel: &impl cubecl_core::compute::CubeTask<C>,
+    mode: ExecutionMode,
+) {
+    if let Ok(dir) = std::env::var("CUBECL_DEBUG_IR") {
+        let Some(definition) = kernel.define() else {
+            return;
+        };
+        let name = dump_file_name(kernel.name(), &kernel.id());
+        let optimizer = cubecl_opt::Optimizer::new(definition.body, definition.cube_dim, mode);
+        std::fs::write(format!("{dir}/{name}.ir.txt"), format!("{optimizer}")).unwrap();
+    }
+}
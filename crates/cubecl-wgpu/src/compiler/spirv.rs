@@ -3,111 +3,89 @@ use std::{borrow::Cow, sync::Arc};
 use ash::{
     khr::cooperative_matrix,
     vk::{
-        ComponentTypeKHR, DeviceCreateInfo, DeviceQueueCreateInfo,
+        self, ComponentTypeKHR, DeviceCreateInfo, DeviceQueueCreateInfo,
         PhysicalDevice16BitStorageFeatures, PhysicalDeviceCooperativeMatrixFeaturesKHR,
-        PhysicalDeviceShaderFloat16Int8Features, PhysicalDeviceVulkanMemoryModelFeatures, ScopeKHR,
-        EXT_ROBUSTNESS2_NAME, KHR_COOPERATIVE_MATRIX_NAME,
+        PhysicalDeviceFeatures2, PhysicalDeviceShaderAtomicFloatFeaturesEXT,
+        PhysicalDeviceShaderFloat16Int8Features, PhysicalDeviceShaderIntegerDotProductFeatures,
+        PhysicalDeviceVulkanMemoryModelFeatures, QueueFamilyProperties, QueueFlags, ScopeKHR,
+        EXT_ROBUSTNESS2_NAME, EXT_SHADER_ATOMIC_FLOAT_NAME, KHR_COOPERATIVE_MATRIX_NAME,
+        KHR_SHADER_INTEGER_DOT_PRODUCT_NAME,
     },
 };
 use cubecl_core::{
-    channel::MutexComputeChannel,
     client::ComputeClient,
     future,
-    ir::{Elem, FloatKind, IntKind, UIntKind},
+    ir::{Elem, FloatKind, IntKind, UIntKind, Visibility},
     prelude::CompiledKernel,
     server::ComputeServer,
-    ExecutionMode, Feature, Runtime,
+    CmmaScope, ExecutionMode, Feature, KernelId, Runtime,
 };
 use cubecl_runtime::{ComputeRuntime, DeviceProperties};
+use rspirv::spirv::Capability;
 use wgpu::{
     hal::{self, vulkan},
     BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BufferBindingType,
-    ComputePipeline, DeviceDescriptor, Features, Limits, PipelineLayoutDescriptor,
-    ShaderModuleDescriptorSpirV, ShaderStages,
+    ComputePipeline, DeviceDescriptor, Features, Limits, PipelineLayout, PipelineLayoutDescriptor,
+    ShaderModule, ShaderModuleDescriptorSpirV, ShaderStages,
 };
 
 use crate::{
-    create_client_on_setup, create_setup_for_device, RuntimeOptions, Vulkan, WgpuDevice,
-    WgpuRuntime, WgpuServer,
+    create_client_on_setup, create_setup_for_device, RuntimeOptions, Vulkan, WgpuChannel,
+    WgpuDevice, WgpuRuntime, WgpuServer, WgpuSetupError,
 };
 
 use super::base::WgpuCompiler;
 
-pub use cubecl_spirv::{GLCompute, SpirvCompiler};
+pub use cubecl_spirv::{GLCompute, SpirvBinary, SpirvCompiler, SpirvKernel};
 pub type VkSpirvCompiler = SpirvCompiler<GLCompute>;
 
 type Server = WgpuServer<SpirvCompiler<GLCompute>>;
 
 /// The compute instance is shared across all [wgpu runtimes](WgpuRuntime).
-static RUNTIME: ComputeRuntime<WgpuDevice, Server, MutexComputeChannel<Server>> =
-    ComputeRuntime::new();
+static RUNTIME: ComputeRuntime<WgpuDevice, Server, WgpuChannel<Server>> = ComputeRuntime::new();
 
 impl WgpuCompiler for SpirvCompiler<GLCompute> {
+    fn execution_mode(server: &WgpuServer<Self>, mode: ExecutionMode) -> ExecutionMode {
+        // `wgpu` currently always enables `robustness2` on Vulkan if available, so default to
+        // unchecked execution if robustness is enabled and let Vulkan handle it, unless the
+        // requested mode should be respected.
+        if !server.respect_execution_mode && is_robust(&server.device) {
+            ExecutionMode::Unchecked
+        } else {
+            mode
+        }
+    }
+
     fn create_pipeline(
         server: &mut WgpuServer<Self>,
         kernel: CompiledKernel<Self>,
+        label: &str,
         mode: ExecutionMode,
     ) -> Arc<ComputePipeline> {
         let (module, layout) = kernel
             .repr
             .map(|repr| {
-                let bindings = repr
+                let read_only = repr
                     .bindings
                     .iter()
-                    .enumerate()
-                    .map(|(i, _binding)| BindGroupLayoutEntry {
-                        binding: i as u32,
-                        visibility: ShaderStages::COMPUTE,
-                        ty: BindingType::Buffer {
-                            #[cfg(not(exclusive_memory_only))]
-                            ty: BufferBindingType::Storage { read_only: false },
-                            #[cfg(exclusive_memory_only)]
-                            ty: BufferBindingType::Storage {
-                                read_only: matches!(
-                                    _binding.visibility,
-                                    cubecl_core::ir::Visibility::Read
-                                ),
-                            },
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    })
+                    .map(|binding| is_read_only(server, binding.visibility))
                     .collect::<Vec<_>>();
-                let layout = server
-                    .device
-                    .create_bind_group_layout(&BindGroupLayoutDescriptor {
-                        label: None,
-                        entries: &bindings,
-                    });
-                let layout = server
-                    .device
-                    .create_pipeline_layout(&PipelineLayoutDescriptor {
-                        label: None,
-                        bind_group_layouts: &[&layout],
-                        push_constant_ranges: &[],
-                    });
-
+                let layout = create_layout(server, &read_only, label);
                 let spirv = repr.assemble();
-
-                let module = unsafe {
-                    server
-                        .device
-                        .create_shader_module_spirv(&ShaderModuleDescriptorSpirV {
-                            label: None,
-                            source: Cow::Borrowed(&spirv),
-                        })
-                };
+                #[cfg(feature = "validate-spirv")]
+                validate_spirv(kernel.name.unwrap_or("unnamed"), &spirv);
+                let module = create_spirv_module(server, &spirv, label);
                 (module, Some(layout))
             })
             .unwrap_or_else(|| {
                 let source = &kernel.source;
+                let module_label = format!("{label} shader module");
                 let module = match mode {
                     ExecutionMode::Checked => {
                         server
                             .device
                             .create_shader_module(wgpu::ShaderModuleDescriptor {
-                                label: None,
+                                label: Some(&module_label),
                                 source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(source)),
                             })
                     }
@@ -115,7 +93,7 @@ impl WgpuCompiler for SpirvCompiler<GLCompute> {
                         server
                             .device
                             .create_shader_module_unchecked(wgpu::ShaderModuleDescriptor {
-                                label: None,
+                                label: Some(&module_label),
                                 source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(source)),
                             })
                     },
@@ -123,21 +101,33 @@ impl WgpuCompiler for SpirvCompiler<GLCompute> {
                 (module, None)
             });
 
-        Arc::new(
-            server
-                .device
-                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-                    label: None,
-                    layout: layout.as_ref(),
-                    module: &module,
-                    entry_point: "main",
-                    compilation_options: wgpu::PipelineCompilationOptions {
-                        zero_initialize_workgroup_memory: false,
-                        ..Default::default()
-                    },
-                    cache: None,
-                }),
-        )
+        create_compute_pipeline(server, layout.as_ref(), &module, label)
+    }
+
+    fn load_pipeline(
+        server: &mut WgpuServer<Self>,
+        kernel_id: &KernelId,
+        label: &str,
+    ) -> Option<Arc<ComputePipeline>> {
+        let data = server.pipeline_cache.as_ref()?.load_kernel(kernel_id)?;
+        let (read_only, spirv) = decode_cached_kernel(&data)?;
+        // The memory configuration may have changed since the kernel was cached.
+        let read_only = read_only
+            .into_iter()
+            .map(|read_only| read_only && server.read_only_bindings)
+            .collect::<Vec<_>>();
+
+        log::debug!("Loaded kernel {kernel_id} from the pipeline cache");
+        let layout = create_layout(server, &read_only, label);
+        #[cfg(feature = "validate-spirv")]
+        validate_spirv(&kernel_id.to_string(), &spirv);
+        let module = create_spirv_module(server, &spirv, label);
+        Some(create_compute_pipeline(
+            server,
+            Some(&layout),
+            &module,
+            label,
+        ))
     }
 
     fn compile(
@@ -145,36 +135,91 @@ impl WgpuCompiler for SpirvCompiler<GLCompute> {
         kernel: <WgpuServer<Self> as ComputeServer>::Kernel,
         mode: ExecutionMode,
     ) -> CompiledKernel<Self> {
-        // `wgpu` currently always enables `robustness2` on Vulkan if available, so default to
-        // unchecked execution if robustness is enabled and let Vulkan handle it
-        let mode = if is_robust(&server.device) {
-            ExecutionMode::Unchecked
-        } else {
-            mode
-        };
+        // The mode was already resolved with `execution_mode`, so the cache key matches the bound
+        // checks of the compiled kernel.
+        let mut kernel_id = kernel.id();
+        kernel_id.mode(mode);
+
         log::debug!("Compiling {}", kernel.name());
         let compiled = kernel.compile(mode);
+        if let Some(repr) = &compiled.repr {
+            if repr.has_capability(Capability::Float64) && !has_f64(&server.device) {
+                panic!(
+                    "Kernel {} uses f64, which isn't supported by this device. It requires the \
+                     Vulkan `shaderFloat64` feature.",
+                    kernel.name()
+                );
+            }
+            if repr.has_capability(Capability::DotProduct) && !has_dot_product(&server.device) {
+                panic!(
+                    "Kernel {} uses integer dot products, which aren't supported by this device. \
+                     It requires the Vulkan `VK_KHR_shader_integer_dot_product` extension.",
+                    kernel.name()
+                );
+            }
+            if repr.has_capability(Capability::AtomicFloat32AddEXT)
+                && !has_atomic_float(&server.device)
+            {
+                panic!(
+                    "Kernel {} uses f32 atomics, which aren't supported by this device. It \
+                     requires the Vulkan `VK_EXT_shader_atomic_float` extension.",
+                    kernel.name()
+                );
+            }
+        }
         #[cfg(feature = "spirv-dump")]
         dump_spirv(&compiled, kernel.name(), kernel.id());
+
+        if let (Some(cache), Some(repr)) = (&server.pipeline_cache, &compiled.repr) {
+            let read_only = repr
+                .bindings
+                .iter()
+                .map(|binding| is_read_only(server, binding.visibility))
+                .collect::<Vec<_>>();
+            cache.store_kernel(
+                &kernel_id,
+                &encode_cached_kernel(&read_only, &repr.assemble()),
+            );
+        }
+
         compiled
     }
 
-    async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+    async fn request_device(
+        adapter: &wgpu::Adapter,
+        options: &RuntimeOptions,
+    ) -> Result<(wgpu::Device, wgpu::Queue), WgpuSetupError> {
         let limits = adapter.limits();
         let features = adapter.features();
         unsafe {
             adapter.as_hal::<hal::api::Vulkan, _, _>(|hal_adapter| {
-                request_device(adapter, hal_adapter.unwrap(), features, limits)
+                let hal_adapter =
+                    hal_adapter.ok_or_else(|| WgpuSetupError::UnsupportedBackend {
+                        reason: format!(
+                            "Can only use SPIR-V with Vulkan, but the adapter uses {:?}",
+                            adapter.get_info().backend
+                        ),
+                    })?;
+                request_device(
+                    adapter,
+                    hal_adapter,
+                    features,
+                    limits,
+                    options.vulkan_queue_family,
+                    &options.memory_hints,
+                )
             })
         }
     }
 
     fn register_features(
         adapter: &wgpu::Adapter,
-        _device: &wgpu::Device,
+        device: &wgpu::Device,
         props: &mut cubecl_runtime::DeviceProperties<cubecl_core::Feature>,
     ) {
-        register_types(props);
+        let has_f64 = has_f64(device);
+        let has_atomic_f32 = has_atomic_float(device);
+        register_types(props, has_f64, has_atomic_f32);
         let cmma = unsafe {
             adapter.as_hal::<hal::api::Vulkan, _, _>(|adapter| {
                 let adapter = adapter.expect("Can only use SPIR-V with Vulkan");
@@ -186,19 +231,23 @@ impl WgpuCompiler for SpirvCompiler<GLCompute> {
                     .unwrap();
                 properties
                     .into_iter()
-                    .filter(|it| {
-                        it.saturating_accumulation == 0
-                            && it.result_type == it.c_type
-                            && it.scope == ScopeKHR::SUBGROUP
-                    })
+                    .filter(|it| it.saturating_accumulation == 0)
                     .filter_map(|it| {
+                        let scope = match it.scope {
+                            ScopeKHR::SUBGROUP => CmmaScope::Plane,
+                            ScopeKHR::WORKGROUP => CmmaScope::Cube,
+                            _ => return None,
+                        };
                         Some(Feature::Cmma {
                             a: conv_type(it.a_type)?,
                             b: conv_type(it.b_type)?,
                             c: conv_type(it.c_type)?,
-                            m: it.m_size as u8,
-                            k: it.k_size as u8,
-                            n: it.n_size as u8,
+                            d: conv_type(it.result_type)?,
+                            // Shapes too large for the feature can't be used anyway.
+                            m: u8::try_from(it.m_size).ok()?,
+                            k: u8::try_from(it.k_size).ok()?,
+                            n: u8::try_from(it.n_size).ok()?,
+                            scope,
                         })
                     })
                     .collect::<Vec<_>>()
@@ -207,7 +256,137 @@ impl WgpuCompiler for SpirvCompiler<GLCompute> {
         for size in cmma {
             props.register_feature(size);
         }
+        if has_dot_product(device) {
+            props.register_feature(Feature::DotProduct);
+        }
+    }
+}
+
+fn is_read_only(server: &WgpuServer<VkSpirvCompiler>, visibility: Visibility) -> bool {
+    server.read_only_bindings && matches!(visibility, Visibility::Read)
+}
+
+fn create_layout(
+    server: &WgpuServer<VkSpirvCompiler>,
+    read_only: &[bool],
+    label: &str,
+) -> PipelineLayout {
+    let bindings = read_only
+        .iter()
+        .enumerate()
+        .map(|(i, read_only)| BindGroupLayoutEntry {
+            binding: i as u32,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Storage {
+                    read_only: *read_only,
+                },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        })
+        .collect::<Vec<_>>();
+    let layout = server
+        .device
+        .create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some(&format!("{label} bind group layout")),
+            entries: &bindings,
+        });
+    server
+        .device
+        .create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some(&format!("{label} pipeline layout")),
+            bind_group_layouts: &[&layout],
+            // Push constants aren't supported, even when the device has `PUSH_CONSTANTS`. Scalars
+            // reach the server as storage buffers created by the kernel launcher, and the SPIR-V
+            // kernels read them from these bindings. Supporting them would need the scalar bytes
+            // in `ComputeServer::execute`, a push constant block in the compiled kernel, and
+            // `set_push_constants` on the compute pass.
+            push_constant_ranges: &[],
+        })
+}
+
+fn create_spirv_module(
+    server: &WgpuServer<VkSpirvCompiler>,
+    spirv: &[u32],
+    label: &str,
+) -> ShaderModule {
+    unsafe {
+        server
+            .device
+            .create_shader_module_spirv(&ShaderModuleDescriptorSpirV {
+                label: Some(&format!("{label} shader module")),
+                source: Cow::Borrowed(spirv),
+            })
+    }
+}
+
+/// Create the compute pipeline of a kernel.
+///
+/// The plane size of the pipeline can't be fixed here: `wgpu` enables
+/// `VK_EXT_subgroup_size_control` along with subgroups, but creates every stage with
+/// `ALLOW_VARYING_SUBGROUP_SIZE` and has no way to chain a required subgroup size to the stage
+/// create info. Kernels relying on the plane size should use the measured
+/// [plane_size_actual](cubecl_runtime::memory_management::HardwareProperties::plane_size_actual).
+///
+/// For the same reason, a plane size can't be requested per launch: a launch option within
+/// `[plane_size_min, plane_size_max]` could be validated, but never applied to the pipeline.
+fn create_compute_pipeline(
+    server: &WgpuServer<VkSpirvCompiler>,
+    layout: Option<&PipelineLayout>,
+    module: &ShaderModule,
+    label: &str,
+) -> Arc<ComputePipeline> {
+    Arc::new(
+        server
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout,
+                module,
+                entry_point: "main",
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    zero_initialize_workgroup_memory: server.zero_initialize_workgroup_memory,
+                    ..Default::default()
+                },
+                cache: server
+                    .pipeline_cache
+                    .as_ref()
+                    .and_then(|cache| cache.wgpu_cache()),
+            }),
+    )
+}
+
+/// Encode a kernel for the pipeline cache as the number of bindings, whether each binding is
+/// read-only, followed by the SPIR-V words.
+fn encode_cached_kernel(read_only: &[bool], spirv: &[u32]) -> Vec<u8> {
+    core::iter::once(read_only.len() as u32)
+        .chain(read_only.iter().map(|read_only| *read_only as u32))
+        .chain(spirv.iter().copied())
+        .flat_map(|word| word.to_le_bytes())
+        .collect()
+}
+
+fn decode_cached_kernel(data: &[u8]) -> Option<(Vec<bool>, Vec<u32>)> {
+    if data.len() % 4 != 0 {
+        return None;
+    }
+    let words = data
+        .chunks_exact(4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect::<Vec<_>>();
+    let (num_bindings, words) = words.split_first()?;
+    let num_bindings = *num_bindings as usize;
+    if words.len() < num_bindings {
+        return None;
     }
+    let (read_only, spirv) = words.split_at(num_bindings);
+
+    Some((
+        read_only.iter().map(|it| *it != 0).collect(),
+        spirv.to_vec(),
+    ))
 }
 
 /// Request device with required features, plus CMMA if available.
@@ -216,7 +395,9 @@ fn request_device(
     adapter: &vulkan::Adapter,
     mut features: Features,
     limits: Limits,
-) -> (wgpu::Device, wgpu::Queue) {
+    queue_family: Option<u32>,
+    memory_hints: &wgpu::MemoryHints,
+) -> Result<(wgpu::Device, wgpu::Queue), WgpuSetupError> {
     // This registers only f16 but not u8/i8, so remove so we can manually add them
     features.remove(Features::SHADER_F16);
 
@@ -233,12 +414,22 @@ fn request_device(
         .shader_int8(true);
     let mut buf_16 =
         PhysicalDevice16BitStorageFeatures::default().storage_buffer16_bit_access(true);
+    let mut dot_product = supported_dot_product_features(adapter);
+    let mut atomic_float = supported_atomic_float_features(adapter);
 
     if has_cmma {
         device_extensions.push(KHR_COOPERATIVE_MATRIX_NAME);
         cmma = Some(PhysicalDeviceCooperativeMatrixFeaturesKHR::default().cooperative_matrix(true))
     }
 
+    if dot_product.is_some() {
+        device_extensions.push(KHR_SHADER_INTEGER_DOT_PRODUCT_NAME);
+    }
+
+    if atomic_float.is_some() {
+        device_extensions.push(EXT_SHADER_ATOMIC_FLOAT_NAME);
+    }
+
     let mut phys_features = adapter.physical_device_features(&device_extensions, features);
     let ash = adapter.shared_instance();
 
@@ -247,7 +438,14 @@ fn request_device(
             .get_physical_device_features(adapter.raw_physical_device())
     };
 
-    let family_index = 0; //TODO
+    let family_index = queue_family.unwrap_or_else(|| {
+        let families = unsafe {
+            ash.raw_instance()
+                .get_physical_device_queue_family_properties(adapter.raw_physical_device())
+        };
+        select_queue_family(&families)
+    });
+    log::info!("Using Vulkan queue family {family_index}");
     let family_info = DeviceQueueCreateInfo::default()
         .queue_family_index(family_index)
         .queue_priorities(&[1.0]);
@@ -273,11 +471,19 @@ fn request_device(
     if let Some(cmma) = &mut cmma {
         info = info.push_next(cmma);
     }
+    if let Some(dot_product) = &mut dot_product {
+        info = info.push_next(dot_product);
+    }
+    if let Some(atomic_float) = &mut atomic_float {
+        info = info.push_next(atomic_float);
+    }
 
     let vk_device = unsafe {
         ash.raw_instance()
             .create_device(adapter.raw_physical_device(), &info, None)
-            .expect("Failed to create Vulkan device")
+            .map_err(|err| WgpuSetupError::DeviceCreationFailed {
+                reason: format!("Failed to create Vulkan device: {err}"),
+            })?
     };
 
     let device = unsafe {
@@ -287,31 +493,110 @@ fn request_device(
                 true,
                 &device_extensions,
                 features,
-                &wgpu::MemoryHints::MemoryUsage,
+                memory_hints,
                 family_info.queue_family_index,
                 0,
             )
-            .expect("Failed to create HAL device")
+            .map_err(|err| WgpuSetupError::DeviceCreationFailed {
+                reason: format!("Failed to create HAL device: {err}"),
+            })?
     };
 
     let descriptor = DeviceDescriptor {
         label: None,
         required_features: features,
         required_limits: limits,
-        // The default is MemoryHints::Performance, which tries to do some bigger
-        // block allocations. However, we already batch allocations, so we
-        // can use MemoryHints::MemoryUsage to lower memory usage.
-        memory_hints: wgpu::MemoryHints::MemoryUsage,
+        memory_hints: memory_hints.clone(),
     };
 
     unsafe {
         wgpu_adapter
             .create_device_from_hal(device, &descriptor, None)
-            .expect("Failed to create wgpu device")
+            .map_err(|err| WgpuSetupError::DeviceCreationFailed {
+                reason: format!("Failed to create wgpu device: {err}"),
+            })
+    }
+}
+
+/// Select the queue family to submit compute work to.
+///
+/// A compute-only family is preferred since it can run concurrently with graphics work. Otherwise
+/// the first family supporting compute is used, falling back to family 0.
+fn select_queue_family(families: &[QueueFamilyProperties]) -> u32 {
+    let supports_compute =
+        |family: &QueueFamilyProperties| family.queue_flags.contains(QueueFlags::COMPUTE);
+    let compute_only = families.iter().position(|family| {
+        supports_compute(family) && !family.queue_flags.contains(QueueFlags::GRAPHICS)
+    });
+
+    compute_only
+        .or_else(|| families.iter().position(supports_compute))
+        .unwrap_or(0) as u32
+}
+
+/// Query the integer dot product features supported by the adapter, or `None` if integer dot
+/// products can't be used in shaders.
+fn supported_dot_product_features(
+    adapter: &vulkan::Adapter,
+) -> Option<PhysicalDeviceShaderIntegerDotProductFeatures<'static>> {
+    if !adapter
+        .physical_device_capabilities()
+        .supports_extension(KHR_SHADER_INTEGER_DOT_PRODUCT_NAME)
+    {
+        return None;
+    }
+
+    let mut dot_product = PhysicalDeviceShaderIntegerDotProductFeatures::default();
+    let mut features = PhysicalDeviceFeatures2::default().push_next(&mut dot_product);
+    unsafe {
+        adapter
+            .shared_instance()
+            .raw_instance()
+            .get_physical_device_features2(adapter.raw_physical_device(), &mut features);
+    }
+
+    (dot_product.shader_integer_dot_product == vk::TRUE).then(|| {
+        PhysicalDeviceShaderIntegerDotProductFeatures::default().shader_integer_dot_product(true)
+    })
+}
+
+/// Query the float atomic features supported by the adapter, or `None` if `f32` atomics can't be
+/// loaded, stored and added on storage buffers. Shared memory atomics are enabled when they're
+/// also supported.
+fn supported_atomic_float_features(
+    adapter: &vulkan::Adapter,
+) -> Option<PhysicalDeviceShaderAtomicFloatFeaturesEXT<'static>> {
+    if !adapter
+        .physical_device_capabilities()
+        .supports_extension(EXT_SHADER_ATOMIC_FLOAT_NAME)
+    {
+        return None;
+    }
+
+    let mut atomic_float = PhysicalDeviceShaderAtomicFloatFeaturesEXT::default();
+    let mut features = PhysicalDeviceFeatures2::default().push_next(&mut atomic_float);
+    unsafe {
+        adapter
+            .shared_instance()
+            .raw_instance()
+            .get_physical_device_features2(adapter.raw_physical_device(), &mut features);
     }
+
+    let supported = atomic_float.shader_buffer_float32_atomics == vk::TRUE
+        && atomic_float.shader_buffer_float32_atomic_add == vk::TRUE;
+
+    supported.then(|| {
+        PhysicalDeviceShaderAtomicFloatFeaturesEXT::default()
+            .shader_buffer_float32_atomics(true)
+            .shader_buffer_float32_atomic_add(true)
+            .shader_shared_float32_atomics(atomic_float.shader_shared_float32_atomics == vk::TRUE)
+            .shader_shared_float32_atomic_add(
+                atomic_float.shader_shared_float32_atomic_add == vk::TRUE,
+            )
+    })
 }
 
-fn register_types(props: &mut DeviceProperties<Feature>) {
+fn register_types(props: &mut DeviceProperties<Feature>, has_f64: bool, has_atomic_f32: bool) {
     use cubecl_core::ir::{Elem, FloatKind, IntKind};
 
     let supported_types = [
@@ -329,13 +614,20 @@ fn register_types(props: &mut DeviceProperties<Feature>) {
         Elem::AtomicUInt(UIntKind::U64),
         Elem::Float(FloatKind::F16),
         Elem::Float(FloatKind::F32),
-        //Elem::Float(FloatKind::F64),
         Elem::Bool,
     ];
 
     for ty in supported_types {
         props.register_feature(Feature::Type(ty));
     }
+
+    if has_f64 {
+        props.register_feature(Feature::Type(Elem::Float(FloatKind::F64)));
+    }
+
+    if has_atomic_f32 {
+        props.register_feature(Feature::Type(Elem::AtomicFloat(FloatKind::F32)));
+    }
 }
 
 fn conv_type(vk_ty: ComponentTypeKHR) -> Option<Elem> {
@@ -351,11 +643,62 @@ fn conv_type(vk_ty: ComponentTypeKHR) -> Option<Elem> {
         ComponentTypeKHR::UINT16 => Elem::UInt(UIntKind::U16),
         ComponentTypeKHR::UINT32 => Elem::UInt(UIntKind::U32),
         ComponentTypeKHR::UINT64 => Elem::UInt(UIntKind::U64),
+        // Packed 4-bit integers (`SINT8_PACKED_NV`/`UINT8_PACKED_NV`) are skipped, since the IR has
+        // no sub-byte elements to represent them and `Elem::size` is counted in whole bytes.
         _ => None?,
     };
     Some(ty)
 }
 
+/// Whether integer dot products were enabled on the device.
+fn has_dot_product(device: &wgpu::Device) -> bool {
+    fn has_dot_product(device: &vulkan::Device) -> bool {
+        device
+            .enabled_device_extensions()
+            .contains(&KHR_SHADER_INTEGER_DOT_PRODUCT_NAME)
+    }
+    unsafe {
+        device
+            .as_hal::<hal::api::Vulkan, _, _>(|device| device.map(has_dot_product).unwrap_or(false))
+            .unwrap_or(false)
+    }
+}
+
+/// Whether `f32` atomics were enabled on the device.
+fn has_atomic_float(device: &wgpu::Device) -> bool {
+    fn has_atomic_float(device: &vulkan::Device) -> bool {
+        device
+            .enabled_device_extensions()
+            .contains(&EXT_SHADER_ATOMIC_FLOAT_NAME)
+    }
+    unsafe {
+        device
+            .as_hal::<hal::api::Vulkan, _, _>(|device| {
+                device.map(has_atomic_float).unwrap_or(false)
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Whether `f64` is usable in shaders. Every feature supported by the physical device is enabled
+/// in [request_device], so this is the same as `shaderFloat64` being supported.
+fn has_f64(device: &wgpu::Device) -> bool {
+    fn has_f64(device: &vulkan::Device) -> bool {
+        let features = unsafe {
+            device
+                .shared_instance()
+                .raw_instance()
+                .get_physical_device_features(device.raw_physical_device())
+        };
+        features.shader_float64 == vk::TRUE
+    }
+    unsafe {
+        device
+            .as_hal::<hal::api::Vulkan, _, _>(|device| device.map(has_f64).unwrap_or(false))
+            .unwrap_or(false)
+    }
+}
+
 fn is_robust(device: &wgpu::Device) -> bool {
     fn is_robust(device: &vulkan::Device) -> bool {
         device
@@ -373,14 +716,17 @@ impl Runtime for WgpuRuntime<VkSpirvCompiler> {
     type Compiler = VkSpirvCompiler;
     type Server = WgpuServer<VkSpirvCompiler>;
 
-    type Channel = MutexComputeChannel<WgpuServer<VkSpirvCompiler>>;
+    type Channel = WgpuChannel<WgpuServer<VkSpirvCompiler>>;
     type Device = WgpuDevice;
 
     fn client(device: &Self::Device) -> ComputeClient<Self::Server, Self::Channel> {
         RUNTIME.client(device, move || {
-            let setup =
-                future::block_on(create_setup_for_device::<Vulkan, VkSpirvCompiler>(device));
-            create_client_on_setup(setup, RuntimeOptions::default())
+            let options = RuntimeOptions::default();
+            let setup = future::block_on(create_setup_for_device::<Vulkan, VkSpirvCompiler>(
+                device, &options,
+            ))
+            .unwrap();
+            create_client_on_setup(setup, options)
         })
     }
 
@@ -393,35 +739,33 @@ impl Runtime for WgpuRuntime<VkSpirvCompiler> {
     }
 }
 
+/// Validate the module with the SPIR-V tools before it reaches the driver, which may crash or lose
+/// the device on an invalid module instead of reporting a useful error.
+#[cfg(feature = "validate-spirv")]
+fn validate_spirv(name: &str, spirv: &[u32]) {
+    use spirv_tools::val::{self, Validator};
+
+    let validator = val::create(Some(spirv_tools::TargetEnv::Vulkan_1_3));
+    if let Err(err) = validator.validate(spirv, None) {
+        log::error!("Kernel {name} compiled to an invalid SPIR-V module: {err}");
+        panic!("Kernel {name} compiled to an invalid SPIR-V module: {err}");
+    }
+}
+
 #[cfg(feature = "spirv-dump")]
 fn dump_spirv(compiled: &CompiledKernel<VkSpirvCompiler>, name: &str, id: cubecl_core::KernelId) {
-    use std::{
-        fs,
-        hash::{DefaultHasher, Hash, Hasher},
-    };
+    use std::fs;
 
     if let Ok(dir) = std::env::var("CUBECL_DEBUG_SPIRV") {
-        let name = name
-            .split("<")
-            .take_while(|it| !it.ends_with("Runtime"))
-            .map(|it| it.split(">").next().unwrap())
-            .map(|it| it.split("::").last().unwrap())
-            .collect::<Vec<_>>()
-            .join("_");
-        let mut hash = DefaultHasher::new();
-        id.hash(&mut hash);
-        let id = hash.finish();
-        let name = sanitize_filename::sanitize_with_options(
-            format!("{name}_{id:#x}"),
-            sanitize_filename::Options {
-                replacement: "_",
-                ..Default::default()
-            },
-        );
+        let name = super::base::dump_file_name(name, &id);
         let repr = compiled.repr.as_ref().unwrap();
-        let kernel = repr.assemble().into_iter();
-        let kernel = kernel.flat_map(|it| it.to_le_bytes()).collect::<Vec<_>>();
+        let words = compiled.spirv_words().unwrap();
+        let kernel = words
+            .iter()
+            .flat_map(|it| it.to_le_bytes())
+            .collect::<Vec<_>>();
         fs::write(format!("{dir}/{name}.spv"), kernel).unwrap();
+        fs::write(format!("{dir}/{name}.spvasm"), disassemble_spirv(&words)).unwrap();
         fs::write(
             format!("{dir}/{name}.ir.txt"),
             format!("{}", repr.optimizer),
@@ -429,3 +773,14 @@ fn dump_spirv(compiled: &CompiledKernel<VkSpirvCompiler>, name: &str, id: cubecl
         .unwrap();
     }
 }
+
+/// Disassemble SPIR-V words, returning the parse error instead when the module is invalid.
+#[cfg(feature = "spirv-dump")]
+fn disassemble_spirv(words: &[u32]) -> String {
+    use rspirv::binary::Disassemble;
+
+    match rspirv::dr::load_words(words) {
+        Ok(module) => module.disassemble(),
+        Err(err) => format!("Failed to disassemble SPIR-V module: {err:?}"),
+    }
+}
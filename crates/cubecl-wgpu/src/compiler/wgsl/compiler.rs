@@ -4,7 +4,7 @@ use super::{shader::ComputeShader, ConstantArray, Item, SharedMemory};
 use super::{LocalArray, Subgroup};
 use crate::{
     compiler::{base::WgpuCompiler, wgsl},
-    WgpuServer,
+    RuntimeOptions, WgpuServer, WgpuSetupError,
 };
 use cubecl_core::{
     ir::{self as cube, HybridAllocator, UIntKind},
@@ -19,6 +19,15 @@ use wgpu::{
     ShaderStages,
 };
 
+/// Write the WGSL source of a kernel to the `CUBECL_DEBUG_WGSL` directory, if set.
+#[cfg(feature = "wgsl-dump")]
+fn dump_wgsl(source: &str, name: &str, id: &cubecl_core::KernelId) {
+    if let Ok(dir) = std::env::var("CUBECL_DEBUG_WGSL") {
+        let name = crate::compiler::base::dump_file_name(name, id);
+        std::fs::write(format!("{dir}/{name}.wgsl"), source).unwrap();
+    }
+}
+
 /// Wgsl Compiler.
 #[derive(Clone, Default)]
 pub struct WgslCompiler {
@@ -72,19 +81,21 @@ impl WgpuCompiler for WgslCompiler {
     fn create_pipeline(
         server: &mut WgpuServer<Self>,
         kernel: CompiledKernel<Self>,
+        label: &str,
         mode: ExecutionMode,
     ) -> Arc<ComputePipeline> {
         let source = &kernel.source;
+        let module_label = format!("{label} shader module");
         let module = match mode {
             ExecutionMode::Checked => server.device.create_shader_module(ShaderModuleDescriptor {
-                label: None,
+                label: Some(&module_label),
                 source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(source)),
             }),
             ExecutionMode::Unchecked => unsafe {
                 server
                     .device
                     .create_shader_module_unchecked(ShaderModuleDescriptor {
-                        label: None,
+                        label: Some(&module_label),
                         source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(source)),
                     })
             },
@@ -98,18 +109,12 @@ impl WgpuCompiler for WgslCompiler {
                 .chain(repr.named.iter().map(|it| &it.1))
                 .enumerate();
             let bindings = bindings
-                .map(|(i, _binding)| BindGroupLayoutEntry {
+                .map(|(i, binding)| BindGroupLayoutEntry {
                     binding: i as u32,
                     visibility: ShaderStages::COMPUTE,
                     ty: BindingType::Buffer {
-                        #[cfg(not(exclusive_memory_only))]
-                        ty: BufferBindingType::Storage { read_only: false },
-                        #[cfg(exclusive_memory_only)]
                         ty: BufferBindingType::Storage {
-                            read_only: matches!(
-                                _binding.visibility,
-                                super::shader::Visibility::Read
-                            ),
+                            read_only: repr.is_read_only(binding),
                         },
                         has_dynamic_offset: false,
                         min_binding_size: None,
@@ -120,13 +125,13 @@ impl WgpuCompiler for WgslCompiler {
             let layout = server
                 .device
                 .create_bind_group_layout(&BindGroupLayoutDescriptor {
-                    label: None,
+                    label: Some(&format!("{label} bind group layout")),
                     entries: &bindings,
                 });
             server
                 .device
                 .create_pipeline_layout(&PipelineLayoutDescriptor {
-                    label: None,
+                    label: Some(&format!("{label} pipeline layout")),
                     bind_group_layouts: &[&layout],
                     push_constant_ranges: &[],
                 })
@@ -136,28 +141,48 @@ impl WgpuCompiler for WgslCompiler {
             server
                 .device
                 .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-                    label: None,
+                    label: Some(label),
                     layout: layout.as_ref(),
                     module: &module,
                     entry_point: "main",
                     compilation_options: wgpu::PipelineCompilationOptions {
-                        zero_initialize_workgroup_memory: false,
+                        zero_initialize_workgroup_memory: server.zero_initialize_workgroup_memory,
                         ..Default::default()
                     },
-                    cache: None,
+                    cache: server
+                        .pipeline_cache
+                        .as_ref()
+                        .and_then(|cache| cache.wgpu_cache()),
                 }),
         )
     }
 
     fn compile(
-        _server: &mut WgpuServer<Self>,
+        server: &mut WgpuServer<Self>,
         kernel: <WgpuServer<Self> as ComputeServer>::Kernel,
         mode: ExecutionMode,
     ) -> CompiledKernel<Self> {
-        kernel.compile(mode)
+        let mut compiled = kernel.compile(mode);
+
+        // The bindings declared in the source have to match the pipeline layout, so regenerate it
+        // when the server doesn't use the default.
+        if let Some(repr) = compiled.repr.as_mut() {
+            if repr.read_only_bindings != server.read_only_bindings {
+                repr.read_only_bindings = server.read_only_bindings;
+                compiled.source = repr.to_string();
+            }
+        }
+
+        #[cfg(feature = "wgsl-dump")]
+        dump_wgsl(&compiled.source, kernel.name(), &kernel.id());
+
+        compiled
     }
 
-    async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+    async fn request_device(
+        adapter: &wgpu::Adapter,
+        options: &RuntimeOptions,
+    ) -> Result<(wgpu::Device, wgpu::Queue), WgpuSetupError> {
         let limits = adapter.limits();
         adapter
             .request_device(
@@ -165,22 +190,18 @@ impl WgpuCompiler for WgslCompiler {
                     label: None,
                     required_features: adapter.features(),
                     required_limits: limits,
-                    // The default is MemoryHints::Performance, which tries to do some bigger
-                    // block allocations. However, we already batch allocations, so we
-                    // can use MemoryHints::MemoryUsage to lower memory usage.
-                    memory_hints: wgpu::MemoryHints::MemoryUsage,
+                    memory_hints: options.memory_hints.clone(),
                 },
                 None,
             )
             .await
-            .map_err(|err| {
-                format!(
+            .map_err(|err| WgpuSetupError::DeviceCreationFailed {
+                reason: format!(
                     "Unable to request the device with the adapter {:?}, err {:?}",
                     adapter.get_info(),
                     err
-                )
+                ),
             })
-            .unwrap()
     }
 
     fn register_features(
@@ -189,6 +210,8 @@ impl WgpuCompiler for WgslCompiler {
         props: &mut DeviceProperties<Feature>,
     ) {
         register_types(props);
+        // WGSL defines float to integer conversions as saturating.
+        props.register_feature(Feature::SaturatingCast);
     }
 }
 
@@ -269,6 +292,7 @@ impl WgslCompiler {
             num_workgroups_no_axis: self.num_workgroup_no_axis,
             workgroup_id_no_axis: self.workgroup_id_no_axis,
             workgroup_size_no_axis: self.workgroup_size_no_axis,
+            read_only_bindings: cfg!(exclusive_memory_only),
         }
     }
 
@@ -310,6 +334,9 @@ impl WgslCompiler {
                 cube::UIntKind::U32 => wgsl::Elem::AtomicU32,
                 kind => panic!("{kind:?} is not a valid WgpuElement"),
             },
+            cube::Elem::AtomicFloat(kind) => {
+                panic!("atomic<{kind:?}> is not a valid WgpuElement, WGSL only has integer atomics")
+            }
         }
     }
 
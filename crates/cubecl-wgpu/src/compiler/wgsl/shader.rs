@@ -88,6 +88,11 @@ pub struct ComputeShader {
     pub workgroup_size_no_axis: bool,
     pub body: Body,
     pub extensions: Vec<Extension>,
+    /// Whether bindings with the [read](Visibility::Read) visibility are declared read-only.
+    ///
+    /// A read-only binding can't use the same buffer as a writable binding in the same dispatch,
+    /// so this can only be enabled when memory isn't sub-sliced.
+    pub read_only_bindings: bool,
 }
 
 impl Display for ComputeShader {
@@ -97,11 +102,11 @@ impl Display for ComputeShader {
         #[cfg(target_family = "wasm")]
         f.write_str("enable subgroups;")?;
 
-        Self::format_bindings(f, "input", &self.inputs, 0)?;
-        Self::format_bindings(f, "output", &self.outputs, self.inputs.len())?;
+        self.format_bindings(f, "input", &self.inputs, 0)?;
+        self.format_bindings(f, "output", &self.outputs, self.inputs.len())?;
 
         for (i, (name, binding)) in self.named.iter().enumerate() {
-            Self::format_binding(
+            self.format_binding(
                 f,
                 name.as_str(),
                 binding,
@@ -210,14 +215,20 @@ fn main(
 }
 
 impl ComputeShader {
+    /// Whether the given binding is declared read-only.
+    pub fn is_read_only(&self, binding: &Binding) -> bool {
+        self.read_only_bindings && binding.visibility == Visibility::Read
+    }
+
     fn format_bindings(
+        &self,
         f: &mut core::fmt::Formatter<'_>,
         prefix: &str,
         bindings: &[Binding],
         num_entry: usize,
     ) -> core::fmt::Result {
         for (i, binding) in bindings.iter().enumerate() {
-            Self::format_binding(
+            self.format_binding(
                 f,
                 format!("{prefix}_{i}_global").as_str(),
                 binding,
@@ -229,6 +240,7 @@ impl ComputeShader {
     }
 
     fn format_binding(
+        &self,
         f: &mut core::fmt::Formatter<'_>,
         name: &str,
         binding: &Binding,
@@ -238,6 +250,10 @@ impl ComputeShader {
             Some(size) => format!("array<{}, {}>", binding.item, size),
             None => format!("array<{}>", binding.item),
         };
+        let visibility = match self.is_read_only(binding) {
+            true => Visibility::Read,
+            false => Visibility::ReadWrite,
+        };
 
         write!(
             f,
@@ -245,7 +261,7 @@ impl ComputeShader {
 @binding({})
 var<{}, {}> {}: {};
 \n",
-            num_entry, binding.location, binding.visibility, name, ty
+            num_entry, binding.location, visibility, name, ty
         )?;
 
         Ok(())
@@ -264,9 +280,8 @@ impl Display for Location {
 impl Display for Visibility {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            #[cfg(exclusive_memory_only)]
             Visibility::Read => f.write_str("read"),
-            _ => f.write_str("read_write"),
+            Visibility::ReadWrite => f.write_str("read_write"),
         }
     }
 }
@@ -0,0 +1,190 @@
+use std::{future::Future, pin::Pin};
+
+use cubecl_common::benchmark::TimestampsResult;
+use cubecl_runtime::{
+    channel::{ComputeChannel, MutexComputeChannel},
+    memory_management::{
+        AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError, MemoryUsage,
+    },
+    server::{Binding, ComputeServer, CubeCount, Handle, KernelTimings, ServerError},
+    storage::BindingResource,
+    ExecutionMode,
+};
+
+#[cfg(not(target_family = "wasm"))]
+use cubecl_runtime::channel::MpscComputeChannel;
+
+/// The channel between a [wgpu client](crate::WgpuRuntime) and its server.
+///
+/// By default, the server is locked by the thread submitting work. With
+/// [single_submission_thread](crate::RuntimeOptions::single_submission_thread), every operation
+/// is instead sent to a dedicated thread owning the server.
+#[derive(Debug)]
+pub enum WgpuChannel<Server: ComputeServer> {
+    /// The server is locked by the calling thread.
+    Mutex(MutexComputeChannel<Server>),
+    /// The server runs on its own thread.
+    #[cfg(not(target_family = "wasm"))]
+    Mpsc(MpscComputeChannel<Server>),
+}
+
+impl<Server: ComputeServer + 'static> WgpuChannel<Server> {
+    /// Create a channel to the given server, submitting from a dedicated thread when
+    /// `single_submission_thread` is set.
+    ///
+    /// Threads aren't available on wasm, where the server is always locked by the caller.
+    pub fn new(server: Server, single_submission_thread: bool) -> Self {
+        #[cfg(not(target_family = "wasm"))]
+        if single_submission_thread {
+            return Self::Mpsc(MpscComputeChannel::new(server));
+        }
+        #[cfg(target_family = "wasm")]
+        let _ = single_submission_thread;
+
+        Self::Mutex(MutexComputeChannel::new(server))
+    }
+}
+
+impl<Server: ComputeServer> Clone for WgpuChannel<Server> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Mutex(channel) => Self::Mutex(channel.clone()),
+            #[cfg(not(target_family = "wasm"))]
+            Self::Mpsc(channel) => Self::Mpsc(channel.clone()),
+        }
+    }
+}
+
+/// Forward a call to the channel of the current variant.
+macro_rules! dispatch {
+    ($self:ident, $channel:ident => $call:expr) => {
+        match $self {
+            WgpuChannel::Mutex($channel) => $call,
+            #[cfg(not(target_family = "wasm"))]
+            WgpuChannel::Mpsc($channel) => $call,
+        }
+    };
+}
+
+impl<Server: ComputeServer + 'static> ComputeChannel<Server> for WgpuChannel<Server> {
+    fn read(&self, binding: Binding) -> impl Future<Output = Result<Vec<u8>, ServerError>> + Send {
+        // The read is enqueued right away to keep the order of the channel, so the futures of
+        // both channels are boxed instead of awaited in an async block.
+        let future: Pin<Box<dyn Future<Output = Result<Vec<u8>, ServerError>> + Send>> =
+            dispatch!(self, channel => Box::pin(channel.read(binding)));
+        future
+    }
+
+    fn get_resource(&self, binding: Binding) -> BindingResource<Server> {
+        dispatch!(self, channel => channel.get_resource(binding))
+    }
+
+    fn create(&self, data: &[u8]) -> Result<Handle, MemoryError> {
+        dispatch!(self, channel => channel.create(data))
+    }
+
+    fn empty(&self, size: usize) -> Result<Handle, MemoryError> {
+        dispatch!(self, channel => channel.empty(size))
+    }
+
+    async fn empty_async(&self, size: usize) -> Result<Handle, MemoryError> {
+        dispatch!(self, channel => channel.empty_async(size).await)
+    }
+
+    fn memset(&self, binding: Binding, value: u8) {
+        dispatch!(self, channel => channel.memset(binding, value))
+    }
+
+    fn copy(&self, src: Binding, dst: Binding) {
+        dispatch!(self, channel => channel.copy(src, dst))
+    }
+
+    fn preallocate(&self, size: u64) -> Result<(), MemoryError> {
+        dispatch!(self, channel => channel.preallocate(size))
+    }
+
+    fn set_residency_budget(&self, budget: Option<u64>) {
+        dispatch!(self, channel => channel.set_residency_budget(budget))
+    }
+
+    fn bypass_allocation_throttle(&self, bypass: bool) {
+        dispatch!(self, channel => channel.bypass_allocation_throttle(bypass))
+    }
+
+    fn reconfigure_memory(
+        &self,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError> {
+        dispatch!(self, channel => channel.reconfigure_memory(properties, config))
+    }
+
+    fn start_allocation_trace(&self) {
+        dispatch!(self, channel => channel.start_allocation_trace())
+    }
+
+    fn stop_allocation_trace(&self) -> Option<AllocationTrace> {
+        dispatch!(self, channel => channel.stop_allocation_trace())
+    }
+
+    unsafe fn execute(
+        &self,
+        kernel: Server::Kernel,
+        count: CubeCount,
+        bindings: Vec<Binding>,
+        mode: ExecutionMode,
+    ) {
+        dispatch!(self, channel => channel.execute(kernel, count, bindings, mode))
+    }
+
+    fn precompile(&self, kernels: Vec<Server::Kernel>, mode: ExecutionMode) {
+        dispatch!(self, channel => channel.precompile(kernels, mode))
+    }
+
+    fn flush(&self) {
+        dispatch!(self, channel => channel.flush())
+    }
+
+    async fn sync(&self) -> Result<(), ServerError> {
+        dispatch!(self, channel => channel.sync().await)
+    }
+
+    async fn sync_elapsed(&self) -> TimestampsResult {
+        dispatch!(self, channel => channel.sync_elapsed().await)
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        dispatch!(self, channel => channel.memory_usage())
+    }
+
+    fn uses_sub_slices(&self) -> bool {
+        dispatch!(self, channel => channel.uses_sub_slices())
+    }
+
+    fn status(&self) -> Result<(), ServerError> {
+        dispatch!(self, channel => channel.status())
+    }
+
+    fn take_kernel_error(&self) -> Option<ServerError> {
+        dispatch!(self, channel => channel.take_kernel_error())
+    }
+
+    fn enable_timestamps(&self) {
+        dispatch!(self, channel => channel.enable_timestamps())
+    }
+
+    fn disable_timestamps(&self) {
+        dispatch!(self, channel => channel.disable_timestamps())
+    }
+
+    async fn read_timings(&self) -> Option<KernelTimings> {
+        dispatch!(self, channel => channel.read_timings().await)
+    }
+
+    fn with_server<R: Send + 'static>(
+        &self,
+        op: impl FnOnce(&mut Server) -> R + Send + 'static,
+    ) -> R {
+        dispatch!(self, channel => channel.with_server(op))
+    }
+}
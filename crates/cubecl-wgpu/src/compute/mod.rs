@@ -1,9 +1,15 @@
+pub(super) mod pipeline_cache;
 pub(super) mod poll;
+pub(super) mod profiler;
 pub(super) mod stream;
 pub(super) mod timestamps;
 
+mod channel;
 mod server;
 mod storage;
 
+pub use channel::*;
+pub use pipeline_cache::clear_pipeline_cache;
+pub use poll::PollStrategy;
 pub use server::*;
 pub use storage::*;
@@ -0,0 +1,344 @@
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use cubecl_core::KernelId;
+
+/// Persists compiled pipelines to disk so they can be reused across process starts.
+///
+/// Two things are stored in the cache directory:
+///
+/// - The [wgpu pipeline cache](wgpu::PipelineCache) blob, which holds the driver compiled
+///   pipelines. It is only available when the device supports [wgpu::Features::PIPELINE_CACHE].
+/// - The compiled kernels, keyed by their [kernel id](KernelId), so compilation can be skipped
+///   entirely on a cache hit.
+///
+/// Kernel ids are only stable within the same build, so the kernel entries are also keyed by the
+/// current executable. Rebuilding the application invalidates them.
+///
+/// Every entry starts with a checksum of its content and a fingerprint of the adapter and driver
+/// that wrote it. Corrupted entries, or entries written by another driver version, are deleted
+/// on load and compiled again, since handing a bad blob to the driver can crash it.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "spirv"), allow(dead_code))] // Kernels are only cached with SPIR-V
+pub(crate) struct PipelineCache {
+    dir: PathBuf,
+    blob_path: PathBuf,
+    build_id: Option<u64>,
+    driver_id: u64,
+    cache: Option<wgpu::PipelineCache>,
+    /// Whether pipelines were created since the blob was last written.
+    dirty: bool,
+}
+
+/// The prefix of the wgpu pipeline cache blobs.
+const BLOB_PREFIX: &str = "wgpu_pipeline_cache";
+/// The extension of the compiled kernel entries.
+const KERNEL_EXTENSION: &str = "kernel";
+/// The size of the header of every entry: the checksum of the content, then the driver id.
+const HEADER_SIZE: usize = 16;
+
+#[cfg_attr(not(feature = "spirv"), allow(dead_code))]
+impl PipelineCache {
+    /// Create a pipeline cache stored in the given directory, reloading any previous content.
+    ///
+    /// Returns `None` when the directory can't be created.
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        adapter_info: &wgpu::AdapterInfo,
+        dir: PathBuf,
+    ) -> Option<Self> {
+        if let Err(err) = fs::create_dir_all(&dir) {
+            log::warn!("Unable to create the pipeline cache directory {dir:?}: {err}");
+            return None;
+        }
+
+        let blob_name = wgpu::util::pipeline_cache_key(adapter_info)
+            .unwrap_or_else(|| format!("{BLOB_PREFIX}_{:?}", adapter_info.backend));
+        let blob_path = dir.join(blob_name);
+        let driver_id = driver_id(adapter_info);
+
+        let cache = if device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            let data = fs::read(&blob_path)
+                .ok()
+                .and_then(|entry| decode_entry(&blob_path, entry, driver_id));
+            // Safety: The data was written by `persist` from a previous pipeline cache, and
+            // `fallback` discards it if it's invalid for this device.
+            let cache = unsafe {
+                device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: Some("CubeCL pipeline cache"),
+                    data: data.as_deref(),
+                    fallback: true,
+                })
+            };
+            Some(cache)
+        } else {
+            None
+        };
+
+        Some(Self {
+            dir,
+            blob_path,
+            build_id: build_id(),
+            driver_id,
+            cache,
+            dirty: false,
+        })
+    }
+
+    /// The wgpu pipeline cache to use when creating pipelines, if supported by the device.
+    pub(crate) fn wgpu_cache(&self) -> Option<&wgpu::PipelineCache> {
+        self.cache.as_ref()
+    }
+
+    /// Load the compiled kernel stored for the given kernel id.
+    pub(crate) fn load_kernel(&self, kernel_id: &KernelId) -> Option<Vec<u8>> {
+        let path = self.kernel_path(kernel_id)?;
+        let entry = fs::read(&path).ok()?;
+        decode_entry(&path, entry, self.driver_id)
+    }
+
+    /// Store the compiled kernel for the given kernel id.
+    pub(crate) fn store_kernel(&self, kernel_id: &KernelId, data: &[u8]) {
+        if let Some(path) = self.kernel_path(kernel_id) {
+            if let Err(err) = fs::write(&path, encode_entry(data, self.driver_id)) {
+                log::warn!("Unable to write kernel to the pipeline cache {path:?}: {err}");
+            }
+        }
+    }
+
+    /// Record that a pipeline was created with the [wgpu cache](Self::wgpu_cache), so the blob
+    /// needs to be written again.
+    pub(crate) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Write the wgpu pipeline cache blob to disk, if pipelines were created since the last write.
+    ///
+    /// The whole blob is rewritten every time, so this is called on sync and when the cache is
+    /// dropped instead of after every pipeline.
+    pub(crate) fn persist(&mut self) {
+        if !core::mem::take(&mut self.dirty) {
+            return;
+        }
+        let Some(data) = self.cache.as_ref().and_then(|cache| cache.get_data()) else {
+            return;
+        };
+
+        // Write to a temporary file first so a crash never leaves a truncated cache behind.
+        let tmp_path = self.blob_path.with_extension("tmp");
+        let result = fs::write(&tmp_path, encode_entry(&data, self.driver_id))
+            .and_then(|_| fs::rename(&tmp_path, &self.blob_path));
+
+        if let Err(err) = result {
+            log::warn!(
+                "Unable to write the pipeline cache {:?}: {err}",
+                self.blob_path
+            );
+        }
+    }
+
+    fn kernel_path(&self, kernel_id: &KernelId) -> Option<PathBuf> {
+        let mut hasher = DefaultHasher::new();
+        self.build_id?.hash(&mut hasher);
+        kernel_id.hash(&mut hasher);
+
+        Some(
+            self.dir
+                .join(format!("{:016x}.{KERNEL_EXTENSION}", hasher.finish())),
+        )
+    }
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        self.persist();
+    }
+}
+
+/// Remove the entries of a pipeline cache stored in `dir`, as set with
+/// [pipeline_cache_dir](crate::RuntimeOptions::pipeline_cache_dir).
+///
+/// The cache writes two kinds of files in its directory: the wgpu pipeline cache blobs, named
+/// `wgpu_pipeline_cache_*`, and one `<hash>.kernel` file per compiled kernel. Only those are
+/// removed, other files in the directory are left untouched. A missing directory is already clear.
+pub fn clear_pipeline_cache(dir: &Path) -> std::io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    for entry in entries {
+        let path = entry?.path();
+        let is_blob = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(BLOB_PREFIX));
+        let is_kernel = path
+            .extension()
+            .is_some_and(|extension| extension == KERNEL_EXTENSION);
+
+        if is_blob || is_kernel {
+            fs::remove_file(path)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn encode_entry(data: &[u8], driver_id: u64) -> Vec<u8> {
+    let mut entry = Vec::with_capacity(HEADER_SIZE + data.len());
+    entry.extend_from_slice(&checksum(data).to_le_bytes());
+    entry.extend_from_slice(&driver_id.to_le_bytes());
+    entry.extend_from_slice(data);
+    entry
+}
+
+/// Returns the content of the entry, or deletes the file when the entry is corrupted or was
+/// written by another driver.
+fn decode_entry(path: &Path, mut entry: Vec<u8>, expected_driver_id: u64) -> Option<Vec<u8>> {
+    let header = entry.get(..HEADER_SIZE).map(|header| {
+        let (checksum, driver_id) = header.split_at(8);
+        (
+            u64::from_le_bytes(checksum.try_into().unwrap()),
+            u64::from_le_bytes(driver_id.try_into().unwrap()),
+        )
+    });
+
+    let reason = match header {
+        None => "truncated",
+        Some((_, driver_id)) if driver_id != expected_driver_id => "written by another driver",
+        Some((sum, _)) if sum != checksum(&entry[HEADER_SIZE..]) => "corrupted",
+        Some(_) => {
+            entry.drain(..HEADER_SIZE);
+            return Some(entry);
+        }
+    };
+
+    log::warn!("Discarding the pipeline cache entry {path:?}, it is {reason}");
+    if let Err(err) = fs::remove_file(path) {
+        log::warn!("Unable to remove the pipeline cache entry {path:?}: {err}");
+    }
+    None
+}
+
+/// Identify the adapter and its driver, so entries are discarded after a driver update.
+fn driver_id(adapter_info: &wgpu::AdapterInfo) -> u64 {
+    let description = format!(
+        "{:?}/{}/{}/{}/{}/{}",
+        adapter_info.backend,
+        adapter_info.vendor,
+        adapter_info.device,
+        adapter_info.name,
+        adapter_info.driver,
+        adapter_info.driver_info
+    );
+    checksum(description.as_bytes())
+}
+
+/// 64-bit FNV-1a, which unlike the std hasher is stable across Rust versions.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Identify the current build of the application, using the path and modification time of the
+/// executable.
+fn build_id() -> Option<u64> {
+    let exe = std::env::current_exe().ok()?;
+    let modified = fs::metadata(&exe).and_then(|meta| meta.modified()).ok()?;
+
+    let mut hasher = DefaultHasher::new();
+    exe.hash(&mut hasher);
+    modified.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A new empty directory, unique to the test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cubecl_pipeline_cache_{name}_{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Write the entry to a file and decode it back.
+    fn write_and_decode(name: &str, entry: Vec<u8>, driver_id: u64) -> (PathBuf, Option<Vec<u8>>) {
+        let path = test_dir(name).join("entry.kernel");
+        fs::write(&path, &entry).unwrap();
+        let data = decode_entry(&path, entry, driver_id);
+        (path, data)
+    }
+
+    #[test]
+    fn entry_round_trips() {
+        let data = b"compiled kernel".to_vec();
+        let (path, decoded) = write_and_decode("round_trip", encode_entry(&data, 7), 7);
+
+        assert_eq!(decoded, Some(data));
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn truncated_entry_is_deleted() {
+        let (path, decoded) = write_and_decode("truncated", vec![0; HEADER_SIZE - 1], 7);
+
+        assert_eq!(decoded, None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn corrupted_entry_is_deleted() {
+        let mut entry = encode_entry(b"compiled kernel", 7);
+        *entry.last_mut().unwrap() ^= 1;
+        let (path, decoded) = write_and_decode("corrupted", entry, 7);
+
+        assert_eq!(decoded, None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn entry_of_another_driver_is_deleted() {
+        let entry = encode_entry(b"compiled kernel", 7);
+        let (path, decoded) = write_and_decode("other_driver", entry, 8);
+
+        assert_eq!(decoded, None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn clear_only_removes_cache_entries() {
+        let dir = test_dir("clear");
+        let blob = dir.join(format!("{BLOB_PREFIX}_Vulkan"));
+        let kernel = dir.join(format!("0123456789abcdef.{KERNEL_EXTENSION}"));
+        let other = dir.join("notes.txt");
+        for path in [&blob, &kernel, &other] {
+            fs::write(path, b"content").unwrap();
+        }
+
+        clear_pipeline_cache(&dir).unwrap();
+
+        assert!(!blob.exists());
+        assert!(!kernel.exists());
+        assert!(other.exists());
+    }
+
+    #[test]
+    fn clearing_a_missing_directory_succeeds() {
+        let dir = test_dir("missing");
+        fs::remove_dir(&dir).unwrap();
+
+        clear_pipeline_cache(&dir).unwrap();
+    }
+}
@@ -1,5 +1,95 @@
+use core::time::Duration;
+
+/// How the server waits on the GPU, for buffer reads and submitted work.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PollStrategy {
+    /// Poll the device in a loop, yielding to other threads between polls.
+    ///
+    /// Has the lowest latency, but keeps a core busy while waiting.
+    Spin,
+    /// Poll the device, sleeping for `interval` between polls.
+    ///
+    /// Saves power for background work, but adds up to `interval` of latency to every wait.
+    Sleep {
+        /// The time between polls.
+        interval: Duration,
+    },
+    /// Block in the driver until the submitted work is done.
+    #[default]
+    Wait,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl PollStrategy {
+    /// Poll the device once, then wait according to the strategy.
+    fn poll(&self, device: &wgpu::Device) {
+        match self {
+            PollStrategy::Spin => {
+                device.poll(wgpu::MaintainBase::Poll);
+                std::thread::yield_now();
+            }
+            PollStrategy::Sleep { interval } => {
+                device.poll(wgpu::MaintainBase::Poll);
+                std::thread::sleep(*interval);
+            }
+            PollStrategy::Wait => {
+                device.poll(wgpu::MaintainBase::Wait);
+            }
+        }
+    }
+
+    /// Block until the work submitted to the queue so far is done.
+    pub(crate) fn wait_for_queue(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let done = SubmissionDone::new(queue);
+        while !done.is_done() {
+            self.poll(device);
+        }
+    }
+
+    /// Block until the submission at `index` is done.
+    ///
+    /// `done` must have been created right after the submission.
+    pub(crate) fn wait_for_submission(
+        &self,
+        device: &wgpu::Device,
+        index: wgpu::SubmissionIndex,
+        done: &SubmissionDone,
+    ) {
+        if let PollStrategy::Wait = self {
+            device.poll(wgpu::MaintainBase::WaitForSubmissionIndex(index));
+            return;
+        }
+
+        while !done.is_done() {
+            self.poll(device);
+        }
+    }
+}
+
+/// Set once the work submitted to a queue before its creation is done.
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug, Clone)]
+pub(crate) struct SubmissionDone(std::sync::Arc<core::sync::atomic::AtomicBool>);
+
+#[cfg(not(target_family = "wasm"))]
+impl SubmissionDone {
+    pub(crate) fn new(queue: &wgpu::Queue) -> Self {
+        let done = std::sync::Arc::new(core::sync::atomic::AtomicBool::new(false));
+        let flag = done.clone();
+        queue.on_submitted_work_done(move || {
+            flag.store(true, core::sync::atomic::Ordering::Release);
+        });
+        Self(done)
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.0.load(core::sync::atomic::Ordering::Acquire)
+    }
+}
+
 #[cfg(not(target_family = "wasm"))]
 mod _impl {
+    use super::PollStrategy;
     use std::thread::JoinHandle;
 
     #[derive(Debug)]
@@ -10,7 +100,7 @@ mod _impl {
     }
 
     impl WgpuPoll {
-        pub fn new(device: std::sync::Arc<wgpu::Device>) -> Self {
+        pub fn new(device: std::sync::Arc<wgpu::Device>, strategy: PollStrategy) -> Self {
             let active_handle = std::sync::Arc::new(());
             let thread_check = active_handle.clone();
 
@@ -19,7 +109,7 @@ mod _impl {
                 // Check whether the WgpuPoll, this thread, and something else is holding
                 // a handle.
                 if std::sync::Arc::strong_count(&thread_check) > 2 {
-                    device.poll(wgpu::MaintainBase::Poll);
+                    strategy.poll(&device);
                 } else {
                     // Do not cancel thread while someone still needs to poll.
                     if cancel_receiver.try_recv().is_ok() {
@@ -28,7 +118,6 @@ mod _impl {
 
                     std::thread::park();
                 }
-                std::thread::yield_now();
             });
 
             Self {
@@ -58,10 +147,12 @@ mod _impl {
 // On Wasm, the browser handles the polling loop, so we don't need anything.
 #[cfg(target_family = "wasm")]
 mod _impl {
+    use super::PollStrategy;
+
     #[derive(Debug)]
     pub struct WgpuPoll {}
     impl WgpuPoll {
-        pub fn new(_device: alloc::sync::Arc<wgpu::Device>) -> Self {
+        pub fn new(_device: alloc::sync::Arc<wgpu::Device>, _strategy: PollStrategy) -> Self {
             Self {}
         }
         pub fn start_polling(&self) -> alloc::sync::Arc<()> {
@@ -0,0 +1,110 @@
+use std::{future::Future, time::Duration};
+
+use cubecl_runtime::server::KernelTimings;
+use wgpu::{QuerySet, QuerySetDescriptor, QueryType};
+
+/// The number of kernels that can be profiled before the timestamps have to be resolved.
+const MAX_KERNELS: u32 = 256;
+
+/// Measures the GPU execution time of every kernel using timestamp queries.
+///
+/// Every profiled kernel runs in its own compute pass, which writes a timestamp at its beginning
+/// and its end. Once the query set is full, or when the timings are read, the timestamps are
+/// resolved into a buffer.
+#[derive(Debug)]
+pub struct KernelProfiler {
+    query_set: QuerySet,
+    /// Kernels profiled in the query set, which isn't resolved yet.
+    pending: Vec<String>,
+    /// Kernels along with the buffer their timestamps were resolved into.
+    resolved: Vec<(Vec<String>, wgpu::Buffer)>,
+}
+
+impl KernelProfiler {
+    /// Create a new profiler, or `None` when the device doesn't support timestamp queries.
+    pub fn new(device: &wgpu::Device) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            log::warn!("Kernel profiling is unavailable, the device doesn't support timestamps.");
+            return None;
+        }
+
+        let query_set = device.create_query_set(&QuerySetDescriptor {
+            label: Some("CubeCL kernel profiler queries"),
+            ty: QueryType::Timestamp,
+            count: 2 * MAX_KERNELS,
+        });
+
+        Some(Self {
+            query_set,
+            pending: Vec::new(),
+            resolved: Vec::new(),
+        })
+    }
+
+    /// Whether the query set is full and should be resolved before profiling another kernel.
+    pub fn is_full(&self) -> bool {
+        self.pending.len() as u32 >= MAX_KERNELS
+    }
+
+    /// Register a kernel and return the timestamp writes of the compute pass it runs in.
+    pub fn begin(&mut self, name: &str) -> wgpu::ComputePassTimestampWrites<'_> {
+        let index = 2 * self.pending.len() as u32;
+        self.pending.push(name.to_string());
+
+        wgpu::ComputePassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index),
+            end_of_pass_write_index: Some(index + 1),
+        }
+    }
+
+    /// Resolve the timestamps of the pending kernels into a buffer.
+    ///
+    /// The current compute pass has to be ended before calling this.
+    pub fn resolve(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let count = 2 * self.pending.len() as u32;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("CubeCL kernel profiler timestamps"),
+            size: count as u64 * size_of::<u64>() as u64,
+            usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::QUERY_RESOLVE,
+            mapped_at_creation: false,
+        });
+        encoder.resolve_query_set(&self.query_set, 0..count, &buffer, 0);
+
+        let names = core::mem::take(&mut self.pending);
+        self.resolved.push((names, buffer));
+    }
+
+    /// Take every resolved buffer, to be read back.
+    pub fn take_resolved(&mut self) -> Vec<(Vec<String>, wgpu::Buffer)> {
+        core::mem::take(&mut self.resolved)
+    }
+}
+
+/// Accumulate the timings of the kernels from their resolved timestamps.
+pub async fn collect_timings(
+    reads: Vec<(Vec<String>, impl Future<Output = Vec<u8>>)>,
+    period: f64,
+) -> KernelTimings {
+    let mut timings = KernelTimings::new();
+
+    for (names, read) in reads {
+        let data = read
+            .await
+            .chunks_exact(8)
+            .map(|x| u64::from_le_bytes(x.try_into().unwrap()))
+            .collect::<Vec<_>>();
+
+        for (name, stamps) in names.into_iter().zip(data.chunks_exact(2)) {
+            let delta = u64::checked_sub(stamps[1], stamps[0]).unwrap_or(0);
+            let duration = Duration::from_secs_f64(delta as f64 * period);
+            *timings.entry(name).or_default() += duration;
+        }
+    }
+
+    timings
+}
@@ -1,23 +1,29 @@
-use std::{future::Future, marker::PhantomData, num::NonZero, time::Duration};
+use std::{future::Future, marker::PhantomData, num::NonZero, sync::Mutex, time::Duration};
 
 use super::{
+    pipeline_cache::PipelineCache,
+    poll::PollStrategy,
+    profiler::KernelProfiler,
     stream::{PipelineDispatch, WgpuStream},
     WgpuStorage,
 };
-use crate::compiler::base::WgpuCompiler;
+use crate::compiler::base::{kernel_label, WgpuCompiler};
 use crate::timestamps::KernelTimestamps;
 use alloc::sync::Arc;
 use cubecl_common::future;
 use cubecl_core::{compute::DebugInformation, prelude::*, server::Handle, Feature, KernelId};
 use cubecl_runtime::{
     debug::{DebugLogger, ProfileLevel},
-    memory_management::{MemoryHandle, MemoryLock, MemoryManagement},
-    server::{self, ComputeServer},
+    memory_management::{
+        AllocationTrace, MemoryConfiguration, MemoryDeviceProperties, MemoryError, MemoryHandle,
+        MemoryLock, MemoryManagement,
+    },
+    server::{self, ComputeServer, KernelTimings, ServerError},
     storage::{BindingResource, ComputeStorage},
     ExecutionMode, TimestampsError, TimestampsResult,
 };
 use hashbrown::HashMap;
-use wgpu::ComputePipeline;
+use wgpu::{ComputePipeline, DeviceLostReason};
 
 /// Wgpu compute server.
 #[derive(Debug)]
@@ -26,10 +32,26 @@ pub struct WgpuServer<C: WgpuCompiler> {
     pub(crate) device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
     pipelines: HashMap<KernelId, Arc<ComputePipeline>>,
+    pub(crate) pipeline_cache: Option<PipelineCache>,
+    /// Compile kernels with the requested execution mode, even when the device is robust.
+    pub(crate) respect_execution_mode: bool,
+    /// Zero the shared memory of every cube before a kernel starts.
+    pub(crate) zero_initialize_workgroup_memory: bool,
+    /// Whether bindings that are only read can be declared read-only, which requires that
+    /// different bindings never share a buffer.
+    pub(crate) read_only_bindings: bool,
+    /// Maximum number of cubes in each dimension of a dispatch.
+    max_cube_count: u32,
+    /// Maximum number of buffers bound to a kernel.
+    max_bindings: u32,
     logger: DebugLogger,
     storage_locked: MemoryLock,
     duration_profiled: Option<Duration>,
     stream: WgpuStream,
+    /// Set by the device lost callback, with the reason given by wgpu.
+    device_lost: Arc<Mutex<Option<String>>>,
+    /// The error of the last kernel whose pipeline couldn't be created, until it's taken.
+    kernel_error: Option<ServerError>,
     _compiler: PhantomData<C>,
 }
 
@@ -40,6 +62,9 @@ impl<C: WgpuCompiler> WgpuServer<C> {
         device: Arc<wgpu::Device>,
         queue: Arc<wgpu::Queue>,
         tasks_max: usize,
+        profiling: bool,
+        poll_strategy: PollStrategy,
+        max_in_flight_submissions: Option<usize>,
     ) -> Self {
         let logger = DebugLogger::default();
         let mut timestamps = KernelTimestamps::Disabled;
@@ -48,7 +73,34 @@ impl<C: WgpuCompiler> WgpuServer<C> {
             timestamps.enable(&device);
         }
 
-        let stream = WgpuStream::new(device.clone(), queue.clone(), timestamps, tasks_max);
+        let profiler = if profiling {
+            KernelProfiler::new(&device)
+        } else {
+            None
+        };
+
+        let device_lost = Arc::new(Mutex::new(None));
+        let lost = device_lost.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            // Dropping the device or replacing the callback also calls it, which isn't a loss.
+            if matches!(
+                reason,
+                DeviceLostReason::Unknown | DeviceLostReason::Destroyed
+            ) {
+                log::error!("The wgpu device was lost ({reason:?}): {message}");
+                *lost.lock().unwrap() = Some(message);
+            }
+        });
+
+        let stream = WgpuStream::new(
+            device.clone(),
+            queue.clone(),
+            timestamps,
+            profiler,
+            tasks_max,
+            poll_strategy,
+            max_in_flight_submissions,
+        );
 
         Self {
             memory_management,
@@ -56,25 +108,99 @@ impl<C: WgpuCompiler> WgpuServer<C> {
             queue: queue.clone(),
             storage_locked: MemoryLock::default(),
             pipelines: HashMap::new(),
+            pipeline_cache: None,
+            respect_execution_mode: false,
+            zero_initialize_workgroup_memory: false,
+            read_only_bindings: cfg!(exclusive_memory_only),
+            max_cube_count: device.limits().max_compute_workgroups_per_dimension,
+            max_bindings: device.limits().max_storage_buffers_per_shader_stage,
             logger,
             duration_profiled: None,
             stream,
+            device_lost,
+            kernel_error: None,
             _compiler: PhantomData,
         }
     }
 
+    /// Wrap a buffer created outside of CubeCL in a handle of `size` bytes, so kernels can use
+    /// it without a copy.
+    ///
+    /// The buffer has to come from the device of this server. It stays owned by the caller: the
+    /// memory management never reuses or destroys it, and only drops its own reference once
+    /// every handle is released.
+    ///
+    /// Interop only goes this way: handles can't be exported to other APIs, since wgpu allocates
+    /// the memory of its buffers without the Vulkan export flags, and a page of the memory pools
+    /// may be shared by several handles. Allocate the memory in the other API and import it in
+    /// wgpu instead.
+    ///
+    /// # Panics
+    ///
+    /// When the buffer lacks the `STORAGE`, `COPY_SRC` or `COPY_DST` usages, needed to bind, read
+    /// and write the handle like any other, or when `size` is zero, not a multiple of
+    /// [wgpu::COPY_BUFFER_ALIGNMENT] or larger than the buffer.
+    pub fn register_external_buffer(&mut self, buffer: wgpu::Buffer, size: u64) -> server::Handle {
+        let usages = wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::COPY_SRC
+            | wgpu::BufferUsages::COPY_DST;
+        assert!(
+            buffer.usage().contains(usages),
+            "External buffers need the {usages:?} usages, but the buffer only has {:?}",
+            buffer.usage()
+        );
+        assert!(
+            size > 0 && size.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT) == size,
+            "The size of an external buffer has to be a non-zero multiple of {} bytes, got {size}",
+            wgpu::COPY_BUFFER_ALIGNMENT
+        );
+        assert!(
+            size <= buffer.size(),
+            "Can't register {size} bytes of an external buffer of {} bytes",
+            buffer.size()
+        );
+
+        let storage = self
+            .memory_management
+            .storage()
+            .register_external(buffer, size);
+        let memory = self.memory_management.register_external(storage);
+        server::Handle::new(memory, None, None, size)
+    }
+
+    /// Get the pipeline of a kernel, creating it if needed.
+    ///
+    /// Returns `None` when the driver fails to create it, usually because the kernel needs more
+    /// registers than the device has, with the error kept for [take_kernel_error].
+    ///
+    /// [take_kernel_error]: ComputeServer::take_kernel_error
     fn pipeline(
         &mut self,
         kernel: <Self as ComputeServer>::Kernel,
         mode: ExecutionMode,
-    ) -> Arc<ComputePipeline> {
+    ) -> Option<Arc<ComputePipeline>> {
+        let mode = C::execution_mode(self, kernel.execution_mode().unwrap_or(mode));
         let mut kernel_id = kernel.id();
         kernel_id.mode(mode);
 
         if let Some(pipeline) = self.pipelines.get(&kernel_id) {
-            return pipeline.clone();
+            return Some(pipeline.clone());
+        }
+
+        // Only built when the pipeline is loaded or created, to keep cache hits cheap.
+        let label = kernel_label(kernel.name(), &kernel.id());
+
+        if let Some(pipeline) = C::load_pipeline(self, &kernel_id, &label) {
+            self.pipelines.insert(kernel_id, pipeline.clone());
+            return Some(pipeline);
         }
 
+        #[cfg(not(target_family = "wasm"))]
+        let kernel_name = kernel.name();
+
+        #[cfg(feature = "debug-ir")]
+        crate::compiler::base::dump_ir(&kernel, mode);
+
         let mut compile = <C as WgpuCompiler>::compile(self, kernel, mode);
 
         if self.logger.is_activated() {
@@ -82,18 +208,53 @@ impl<C: WgpuCompiler> WgpuServer<C> {
         }
 
         let compile = self.logger.debug(compile);
-        let pipeline = C::create_pipeline(self, compile, mode);
+
+        // The driver may only notice that the kernel doesn't fit on the device when creating its
+        // pipeline. Those errors would be fatal if left uncaptured, but only affect this kernel.
+        // Native error scopes are resolved as soon as they are popped, while WebGPU only reports
+        // them asynchronously, so they are left uncaptured there.
+        #[cfg(not(target_family = "wasm"))]
+        {
+            self.device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+            self.device.push_error_scope(wgpu::ErrorFilter::Internal);
+        }
+        let pipeline = C::create_pipeline(self, compile, &label, mode);
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let internal = future::block_on(self.device.pop_error_scope());
+            let out_of_memory = future::block_on(self.device.pop_error_scope());
+
+            if let Some(err) = internal.or(out_of_memory) {
+                log::warn!("Unable to create the pipeline of {kernel_name}: {err}");
+                self.kernel_error = Some(ServerError::OutOfResources {
+                    kernel: kernel_name.to_string(),
+                    reason: err.to_string(),
+                });
+                return None;
+            }
+        }
+
+        if let Some(cache) = &mut self.pipeline_cache {
+            cache.mark_dirty();
+        }
 
         self.pipelines.insert(kernel_id.clone(), pipeline.clone());
 
-        pipeline
+        Some(pipeline)
+    }
+
+    /// Write the pipelines created since the last sync to the pipeline cache.
+    fn persist_pipeline_cache(&mut self) {
+        if let Some(cache) = &mut self.pipeline_cache {
+            cache.persist();
+        }
     }
 
     fn on_flushed(&mut self) {
         self.storage_locked.clear_locked();
 
         // Cleanup allocations and deallocations.
-        self.memory_management.cleanup();
+        self.memory_management.cleanup(false, None);
         self.memory_management.storage().perform_deallocations();
     }
 }
@@ -140,7 +301,7 @@ impl<C: WgpuCompiler> ComputeServer for WgpuServer<C> {
     ///
     /// This is important, otherwise the compute passes are going to be too small and we won't be able to
     /// fully utilize the GPU.
-    fn create(&mut self, data: &[u8]) -> server::Handle {
+    fn create(&mut self, data: &[u8]) -> Result<server::Handle, MemoryError> {
         let num_bytes = data.len() as u64;
 
         // Copying into a buffer has to be 4 byte aligned. We can safely do so, as
@@ -152,7 +313,7 @@ impl<C: WgpuCompiler> ComputeServer for WgpuServer<C> {
         // or copying.
         let memory = self
             .memory_management
-            .reserve(aligned_len, Some(&self.storage_locked));
+            .reserve(aligned_len, Some(&self.storage_locked))?;
 
         if let Some(len) = NonZero::new(aligned_len) {
             let resource_handle = self.memory_management.get(memory.clone().binding());
@@ -170,16 +331,107 @@ impl<C: WgpuCompiler> ComputeServer for WgpuServer<C> {
                 .copy_from_slice(data);
         }
 
-        Handle::new(memory, None, None, aligned_len)
+        Ok(Handle::new(memory, None, None, aligned_len))
     }
 
-    fn empty(&mut self, size: usize) -> server::Handle {
-        server::Handle::new(
-            self.memory_management.reserve(size as u64, None),
+    fn empty(&mut self, size: usize) -> Result<server::Handle, MemoryError> {
+        Ok(server::Handle::new(
+            self.memory_management.reserve(size as u64, None)?,
             None,
             None,
             size as u64,
-        )
+        ))
+    }
+
+    /// Zeroing uses the native clear of the command encoder. Other values are written from the
+    /// host, which happens at the start of the next submission, so the pending work is submitted
+    /// first to keep the order of the operations.
+    fn memset(&mut self, binding: server::Binding, value: u8) {
+        if value != 0 {
+            self.flush();
+        }
+
+        let rb = self.get_resource(binding);
+        let resource = rb.resource();
+
+        // Clearing and writing a buffer has to be 4 byte aligned. We can safely do so, as
+        // memory is 32 bytes aligned (see WgpuStorage).
+        let align = wgpu::COPY_BUFFER_ALIGNMENT;
+        let aligned_len = resource.size().div_ceil(align) * align;
+
+        if value == 0 {
+            self.stream
+                .clear_buffer(&resource.buffer, resource.offset(), aligned_len);
+        } else {
+            self.queue.write_buffer(
+                &resource.buffer,
+                resource.offset(),
+                &vec![value; aligned_len as usize],
+            );
+        }
+    }
+
+    /// The copy is recorded in the same encoder as the kernels, so it runs after the work already
+    /// submitted. Handles from the same page are copied through a temporary buffer.
+    fn copy(&mut self, src: server::Binding, dst: server::Binding) {
+        let src = self.get_resource(src);
+        let dst = self.get_resource(dst);
+        let (src, dst) = (src.resource(), dst.resource());
+
+        self.stream.copy_buffer(
+            &src.buffer,
+            src.offset(),
+            &dst.buffer,
+            dst.offset(),
+            src.size(),
+        );
+    }
+
+    fn preallocate(&mut self, size: u64) -> Result<(), MemoryError> {
+        self.memory_management.preallocate(size)
+    }
+
+    fn set_residency_budget(&mut self, budget: Option<u64>) {
+        self.memory_management.set_residency_budget(budget);
+    }
+
+    fn bypass_allocation_throttle(&mut self, bypass: bool) {
+        self.memory_management.bypass_throttle(bypass);
+    }
+
+    fn reconfigure_memory(
+        &mut self,
+        properties: MemoryDeviceProperties,
+        config: MemoryConfiguration,
+    ) -> Result<(), MemoryError> {
+        // Buffers are only destroyed after the next flush, so submitting the pending work is
+        // enough for the old pages to outlive it.
+        self.flush();
+
+        let read_only_bindings = !config.has_sub_slices();
+        self.memory_management.reconfigure(&properties, config)?;
+
+        if read_only_bindings != self.read_only_bindings {
+            // The access of the bindings is part of the compiled kernels.
+            self.read_only_bindings = read_only_bindings;
+            self.pipelines.clear();
+        }
+
+        Ok(())
+    }
+
+    fn start_allocation_trace(&mut self) {
+        self.memory_management.start_trace();
+    }
+
+    fn stop_allocation_trace(&mut self) -> Option<AllocationTrace> {
+        self.memory_management.stop_trace()
+    }
+
+    fn precompile(&mut self, kernels: Vec<Self::Kernel>, mode: ExecutionMode) {
+        for kernel in kernels {
+            self.pipeline(kernel, mode);
+        }
     }
 
     unsafe fn execute(
@@ -209,7 +461,27 @@ impl<C: WgpuCompiler> ComputeServer for WgpuServer<C> {
         }
 
         // Start execution.
-        let pipeline = self.pipeline(kernel, mode);
+        let kernel_name = kernel.name();
+
+        // Creating the pipeline would fail with a driver error that doesn't mention the limit.
+        let max_bindings = self.max_bindings;
+        if bindings.len() > max_bindings as usize {
+            let reason = format!(
+                "the kernel needs {} bindings but the device supports at most {max_bindings}, \
+                 consider fusing some of its buffers into a single one",
+                bindings.len()
+            );
+            log::warn!("Unable to launch {kernel_name}: {reason}");
+            self.kernel_error = Some(ServerError::OutOfResources {
+                kernel: kernel_name.to_string(),
+                reason,
+            });
+            return;
+        }
+
+        let Some(pipeline) = self.pipeline(kernel, mode) else {
+            return;
+        };
 
         // Store all the resources we'll be using. This could be eliminated if
         // there was a way to tie the lifetime of the resource to the memory handle.
@@ -224,10 +496,21 @@ impl<C: WgpuCompiler> ComputeServer for WgpuServer<C> {
             CubeCount::Dynamic(binding) => {
                 PipelineDispatch::Dynamic(self.get_resource(binding).into_resource())
             }
-            CubeCount::Static(x, y, z) => PipelineDispatch::Static(x, y, z),
+            CubeCount::Static(x, y, z) => {
+                let max = self.max_cube_count;
+                assert!(
+                    x <= max && y <= max && z <= max,
+                    "Can't launch {kernel_name} with a cube count of ({x}, {y}, {z}), the device \
+                     supports at most {max} cubes per dimension"
+                );
+                PipelineDispatch::Static(x, y, z)
+            }
         };
 
-        if self.stream.register(pipeline, resources, dispatch) {
+        if self
+            .stream
+            .register(pipeline, resources, dispatch, kernel_name)
+        {
             self.on_flushed();
         }
 
@@ -269,6 +552,7 @@ impl<C: WgpuCompiler> ComputeServer for WgpuServer<C> {
     /// Returns the total time of GPU work this sync completes.
     fn sync(&mut self) -> impl Future<Output = ()> + 'static {
         self.logger.profile_summary();
+        self.persist_pipeline_cache();
         let fut = self.stream.sync();
         self.on_flushed();
 
@@ -278,6 +562,7 @@ impl<C: WgpuCompiler> ComputeServer for WgpuServer<C> {
     /// Returns the total time of GPU work this sync completes.
     fn sync_elapsed(&mut self) -> impl Future<Output = TimestampsResult> + 'static {
         self.logger.profile_summary();
+        self.persist_pipeline_cache();
 
         let fut = self.stream.sync_elapsed();
         self.on_flushed();
@@ -307,6 +592,23 @@ impl<C: WgpuCompiler> ComputeServer for WgpuServer<C> {
         self.memory_management.memory_usage()
     }
 
+    fn uses_sub_slices(&self) -> bool {
+        self.memory_management.uses_sub_slices()
+    }
+
+    fn status(&self) -> Result<(), ServerError> {
+        match self.device_lost.lock().unwrap().as_ref() {
+            Some(reason) => Err(ServerError::DeviceLost {
+                reason: reason.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn take_kernel_error(&mut self) -> Option<ServerError> {
+        self.kernel_error.take()
+    }
+
     fn enable_timestamps(&mut self) {
         self.stream.timestamps.enable(&self.device);
     }
@@ -317,4 +619,16 @@ impl<C: WgpuCompiler> ComputeServer for WgpuServer<C> {
             self.stream.timestamps.disable();
         }
     }
+
+    fn read_timings(&mut self) -> impl Future<Output = Option<KernelTimings>> + 'static {
+        let fut = self.stream.read_timings();
+        self.on_flushed();
+
+        async move {
+            match fut {
+                Some(fut) => Some(fut.await),
+                None => None,
+            }
+        }
+    }
 }
@@ -1,10 +1,17 @@
-use cubecl_runtime::storage::{ComputeStorage, StorageHandle, StorageId, StorageUtilization};
-use hashbrown::HashMap;
+#[cfg(not(target_family = "wasm"))]
+use cubecl_common::future;
+use cubecl_runtime::{
+    memory_management::MemoryError,
+    storage::{ComputeStorage, StorageHandle, StorageId, StorageUtilization},
+};
+use hashbrown::{HashMap, HashSet};
 use std::{num::NonZeroU64, sync::Arc};
 
 /// Buffer storage for wgpu.
 pub struct WgpuStorage {
     memory: HashMap<StorageId, Arc<wgpu::Buffer>>,
+    /// The buffers owned outside of the storage, which are released without being destroyed.
+    external: HashSet<StorageId>,
     deallocations: Vec<StorageId>,
     device: Arc<wgpu::Device>,
 }
@@ -55,6 +62,7 @@ impl WgpuStorage {
     pub fn new(device: Arc<wgpu::Device>) -> Self {
         Self {
             memory: HashMap::new(),
+            external: HashSet::new(),
             deallocations: Vec::new(),
             device,
         }
@@ -64,10 +72,22 @@ impl WgpuStorage {
     pub fn perform_deallocations(&mut self) {
         for id in self.deallocations.drain(..) {
             if let Some(buffer) = self.memory.remove(&id) {
-                buffer.destroy()
+                if !self.external.remove(&id) {
+                    buffer.destroy()
+                }
             }
         }
     }
+
+    /// Track a buffer created outside of the storage, using its first `size` bytes.
+    ///
+    /// Deallocating it only drops the reference of the storage, the buffer is never destroyed.
+    pub fn register_external(&mut self, buffer: wgpu::Buffer, size: u64) -> StorageHandle {
+        let id = StorageId::new();
+        self.memory.insert(id, Arc::new(buffer));
+        self.external.insert(id);
+        StorageHandle::new(id, StorageUtilization { offset: 0, size })
+    }
 }
 
 impl ComputeStorage for WgpuStorage {
@@ -84,9 +104,14 @@ impl ComputeStorage for WgpuStorage {
         WgpuResource::new(buffer.clone(), handle.offset(), handle.size())
     }
 
-    fn alloc(&mut self, size: u64) -> StorageHandle {
+    fn alloc(&mut self, size: u64) -> Result<StorageHandle, MemoryError> {
         let id = StorageId::new();
-        let buffer = Arc::new(self.device.create_buffer(&wgpu::BufferDescriptor {
+        // Running out of memory is fatal if left uncaptured, capture it so it can be reported to
+        // the client instead. WebGPU only reports the error asynchronously, so it can't be waited
+        // for here.
+        #[cfg(not(target_family = "wasm"))]
+        self.device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: None,
             size,
             usage: wgpu::BufferUsages::COPY_DST
@@ -94,10 +119,22 @@ impl ComputeStorage for WgpuStorage {
                 | wgpu::BufferUsages::COPY_SRC
                 | wgpu::BufferUsages::INDIRECT,
             mapped_at_creation: false,
-        }));
+        });
 
-        self.memory.insert(id, buffer);
-        StorageHandle::new(id, StorageUtilization { offset: 0, size })
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(err) = future::block_on(self.device.pop_error_scope()) {
+            buffer.destroy();
+            return Err(MemoryError::OutOfMemory {
+                size,
+                reason: err.to_string(),
+            });
+        }
+
+        self.memory.insert(id, Arc::new(buffer));
+        Ok(StorageHandle::new(
+            id,
+            StorageUtilization { offset: 0, size },
+        ))
     }
 
     fn dealloc(&mut self, id: StorageId) {
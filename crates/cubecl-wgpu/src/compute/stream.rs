@@ -1,8 +1,13 @@
 use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
 use web_time::Instant;
 
-use super::{poll::WgpuPoll, timestamps::KernelTimestamps, WgpuResource};
-use cubecl_runtime::{TimestampsError, TimestampsResult};
+use super::{
+    poll::{PollStrategy, WgpuPoll},
+    profiler::{collect_timings, KernelProfiler},
+    timestamps::KernelTimestamps,
+    WgpuResource,
+};
+use cubecl_runtime::{server::KernelTimings, TimestampsError, TimestampsResult};
 use wgpu::ComputePipeline;
 
 #[derive(Debug)]
@@ -10,13 +15,16 @@ pub struct WgpuStream {
     pass: Option<wgpu::ComputePass<'static>>,
     encoder: wgpu::CommandEncoder,
     pub timestamps: KernelTimestamps,
+    profiler: Option<KernelProfiler>,
     tasks_count: usize,
     tasks_max: usize,
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
     poll: WgpuPoll,
+    poll_strategy: PollStrategy,
     sync_buffer: Option<wgpu::Buffer>,
     submission_load: SubmissionLoad,
+    in_flight: InFlightSubmissions,
 }
 
 pub enum PipelineDispatch {
@@ -29,9 +37,12 @@ impl WgpuStream {
         device: Arc<wgpu::Device>,
         queue: Arc<wgpu::Queue>,
         timestamps: KernelTimestamps,
+        profiler: Option<KernelProfiler>,
         tasks_max: usize,
+        poll_strategy: PollStrategy,
+        max_in_flight_submissions: Option<usize>,
     ) -> Self {
-        let poll = WgpuPoll::new(device.clone());
+        let poll = WgpuPoll::new(device.clone(), poll_strategy);
         let encoder = create_encoder(&device);
 
         #[cfg(target_family = "wasm")]
@@ -50,14 +61,17 @@ impl WgpuStream {
         Self {
             pass: None,
             timestamps,
+            profiler,
             device,
             encoder,
             queue,
             tasks_count: 0,
             tasks_max,
             poll,
+            poll_strategy,
             sync_buffer,
             submission_load: SubmissionLoad::default(),
+            in_flight: InFlightSubmissions::new(max_in_flight_submissions),
         }
     }
 
@@ -66,25 +80,36 @@ impl WgpuStream {
         pipeline: Arc<ComputePipeline>,
         resources: Vec<WgpuResource>,
         dispatch: PipelineDispatch,
+        kernel_name: &str,
     ) -> bool {
+        if let Some(profiler) = &mut self.profiler {
+            // Every profiled kernel runs in its own compute pass.
+            self.pass = None;
+
+            if profiler.is_full() {
+                profiler.resolve(&self.device, &mut self.encoder);
+            }
+        }
+
         // Start a new compute pass if needed. The forget_lifetime allows
         // to store this with a 'static lifetime, but the compute pass must
         // be dropped before the encoder. This isn't unsafe - it's still checked at runtime.
         let pass = self.pass.get_or_insert_with(|| {
             // Write out timestamps. The first compute pass writes both a start and end timestamp.
             // the second timestamp writes out only an end stamp.
-            let timestamps =
-                if let KernelTimestamps::Native { query_set, init } = &mut self.timestamps {
-                    let result = Some(wgpu::ComputePassTimestampWrites {
-                        query_set,
-                        beginning_of_pass_write_index: if !*init { Some(0) } else { None },
-                        end_of_pass_write_index: Some(1),
-                    });
-                    *init = true;
-                    result
-                } else {
-                    None
-                };
+            let timestamps = if let Some(profiler) = &mut self.profiler {
+                Some(profiler.begin(kernel_name))
+            } else if let KernelTimestamps::Native { query_set, init } = &mut self.timestamps {
+                let result = Some(wgpu::ComputePassTimestampWrites {
+                    query_set,
+                    beginning_of_pass_write_index: if !*init { Some(0) } else { None },
+                    end_of_pass_write_index: Some(1),
+                });
+                *init = true;
+                result
+            } else {
+                None
+            };
 
             self.encoder
                 .begin_compute_pass(&wgpu::ComputePassDescriptor {
@@ -191,6 +216,43 @@ impl WgpuStream {
         }
     }
 
+    pub fn clear_buffer(&mut self, buffer: &wgpu::Buffer, offset: u64, size: u64) {
+        // Clearing has to happen outside of a compute pass.
+        self.pass = None;
+        self.encoder.clear_buffer(buffer, offset, Some(size));
+    }
+
+    pub fn copy_buffer(
+        &mut self,
+        src: &wgpu::Buffer,
+        src_offset: u64,
+        dst: &wgpu::Buffer,
+        dst_offset: u64,
+        size: u64,
+    ) {
+        // Copying has to happen outside of a compute pass.
+        self.pass = None;
+
+        if src.global_id() != dst.global_id() {
+            self.encoder
+                .copy_buffer_to_buffer(src, src_offset, dst, dst_offset, size);
+            return;
+        }
+
+        // Handles from the same page share a buffer, and wgpu rejects copies within one buffer,
+        // so the bytes go through a temporary buffer instead.
+        let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        self.encoder
+            .copy_buffer_to_buffer(src, src_offset, &staging_buffer, 0, size);
+        self.encoder
+            .copy_buffer_to_buffer(&staging_buffer, 0, dst, dst_offset, size);
+    }
+
     pub fn sync_elapsed(
         &mut self,
     ) -> Pin<Box<dyn Future<Output = TimestampsResult> + Send + 'static>> {
@@ -268,6 +330,27 @@ impl WgpuStream {
         }
     }
 
+    /// Read the execution time of every kernel profiled since the last call.
+    ///
+    /// Returns `None` when kernel profiling is disabled.
+    pub fn read_timings(
+        &mut self,
+    ) -> Option<Pin<Box<dyn Future<Output = KernelTimings> + Send + 'static>>> {
+        let profiler = self.profiler.as_mut()?;
+
+        self.pass = None;
+        profiler.resolve(&self.device, &mut self.encoder);
+        let resolved = profiler.take_resolved();
+
+        let period = self.queue.get_timestamp_period() as f64 * 1e-9;
+        let reads = resolved
+            .into_iter()
+            .map(|(names, buffer)| (names, self.read_buffer(&buffer, 0, buffer.size())))
+            .collect::<Vec<_>>();
+
+        Some(Box::pin(collect_timings(reads, period)))
+    }
+
     pub fn sync(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
         self.flush();
 
@@ -291,7 +374,7 @@ impl WgpuStream {
             None => {
                 #[cfg(not(target_family = "wasm"))]
                 {
-                    self.device.poll(wgpu::MaintainBase::Wait);
+                    self.poll_strategy.wait_for_queue(&self.device, &self.queue);
                     Box::pin(async move {})
                 }
                 #[cfg(target_family = "wasm")]
@@ -309,10 +392,18 @@ impl WgpuStream {
         let new_encoder = create_encoder(&self.device);
         let encoder = std::mem::replace(&mut self.encoder, new_encoder);
 
+        self.in_flight
+            .wait_for_slot(&self.device, self.poll_strategy);
         let index = self.queue.submit([encoder.finish()]);
+        self.in_flight.record(&self.queue, &index);
 
-        self.submission_load
-            .regulate(&self.device, self.tasks_count, index);
+        self.submission_load.regulate(
+            &self.device,
+            &self.queue,
+            self.poll_strategy,
+            self.tasks_count,
+            index,
+        );
 
         self.tasks_count = 0;
     }
@@ -320,11 +411,52 @@ impl WgpuStream {
 
 #[cfg(not(target_family = "wasm"))]
 mod __submission_load {
+    use crate::compute::poll::{PollStrategy, SubmissionDone};
+    use std::collections::VecDeque;
+
+    /// The submissions the GPU may still be working on, when their number is limited.
+    #[derive(Debug)]
+    pub struct InFlightSubmissions {
+        max: Option<usize>,
+        submissions: VecDeque<(wgpu::SubmissionIndex, SubmissionDone)>,
+    }
+
+    impl InFlightSubmissions {
+        pub fn new(max: Option<usize>) -> Self {
+            Self {
+                max: max.map(|max| max.max(1)),
+                submissions: VecDeque::new(),
+            }
+        }
+
+        /// Wait until one more submission fits in the limit.
+        pub fn wait_for_slot(&mut self, device: &wgpu::Device, strategy: PollStrategy) {
+            let Some(max) = self.max else {
+                return;
+            };
+
+            self.submissions.retain(|(_, done)| !done.is_done());
+            while self.submissions.len() >= max {
+                // Submissions complete in order, so the oldest one frees a slot first.
+                let (index, done) = self.submissions.pop_front().unwrap();
+                strategy.wait_for_submission(device, index, &done);
+            }
+        }
+
+        /// Track a submission, right after it was made.
+        pub fn record(&mut self, queue: &wgpu::Queue, index: &wgpu::SubmissionIndex) {
+            if self.max.is_some() {
+                self.submissions
+                    .push_back((index.clone(), SubmissionDone::new(queue)));
+            }
+        }
+    }
 
     #[derive(Default, Debug)]
     pub enum SubmissionLoad {
         Init {
             last_index: wgpu::SubmissionIndex,
+            last_done: SubmissionDone,
             tasks_count_submitted: usize,
         },
         #[default]
@@ -335,12 +467,17 @@ mod __submission_load {
         pub fn regulate(
             &mut self,
             device: &wgpu::Device,
+            queue: &wgpu::Queue,
+            strategy: PollStrategy,
             tasks_count: usize,
             mut index: wgpu::SubmissionIndex,
         ) {
+            let mut done = SubmissionDone::new(queue);
+
             match self {
                 SubmissionLoad::Init {
                     last_index,
+                    last_done,
                     tasks_count_submitted,
                 } => {
                     *tasks_count_submitted += tasks_count;
@@ -355,7 +492,8 @@ mod __submission_load {
 
                     if *tasks_count_submitted >= MAX_TOTAL_TASKS {
                         core::mem::swap(last_index, &mut index);
-                        device.poll(wgpu::MaintainBase::WaitForSubmissionIndex(index));
+                        core::mem::swap(last_done, &mut done);
+                        strategy.wait_for_submission(device, index, &done);
 
                         *tasks_count_submitted = 0;
                     }
@@ -363,6 +501,7 @@ mod __submission_load {
                 SubmissionLoad::Empty => {
                     *self = Self::Init {
                         last_index: index,
+                        last_done: done,
                         tasks_count_submitted: 0,
                     }
                 }
@@ -373,6 +512,27 @@ mod __submission_load {
 
 #[cfg(target_family = "wasm")]
 mod __submission_load_wasm {
+    #[derive(Debug)]
+    pub struct InFlightSubmissions;
+
+    impl InFlightSubmissions {
+        pub fn new(_max: Option<usize>) -> Self {
+            Self
+        }
+
+        pub fn wait_for_slot(
+            &mut self,
+            _device: &wgpu::Device,
+            _strategy: crate::compute::poll::PollStrategy,
+        ) {
+            // Nothing to do.
+        }
+
+        pub fn record(&mut self, _queue: &wgpu::Queue, _index: &wgpu::SubmissionIndex) {
+            // Nothing to do.
+        }
+    }
+
     #[derive(Default, Debug)]
     pub struct SubmissionLoad;
 
@@ -380,6 +540,8 @@ mod __submission_load_wasm {
         pub fn regulate(
             &mut self,
             _device: &wgpu::Device,
+            _queue: &wgpu::Queue,
+            _strategy: crate::compute::poll::PollStrategy,
             _tasks_count: usize,
             _index: wgpu::SubmissionIndex,
         ) {
@@ -0,0 +1,55 @@
+use std::{
+    fmt::{Debug, Display},
+    time::Duration,
+};
+
+/// Reasons why the [setup](crate::WgpuSetup) of a device can fail.
+pub enum WgpuSetupError {
+    /// No adapter matches the requested device.
+    AdapterNotFound { reason: String },
+    /// The adapter doesn't use the graphics API required by the compiler, such as SPIR-V on a
+    /// non-Vulkan adapter.
+    UnsupportedBackend { reason: String },
+    /// The adapter doesn't support some of the
+    /// [required features](crate::RuntimeOptions::required_features).
+    MissingFeatures { missing: wgpu::Features },
+    /// The device couldn't be created on the selected adapter.
+    DeviceCreationFailed { reason: String },
+    /// The device wasn't created before the [timeout](crate::RuntimeOptions::device_request_timeout).
+    Timeout(Duration),
+}
+
+impl Debug for WgpuSetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WgpuSetupError::AdapterNotFound { reason } => {
+                write!(f, "Unable to find an adapter for the device: {reason}")
+            }
+            WgpuSetupError::UnsupportedBackend { reason } => {
+                write!(f, "The adapter uses an unsupported graphics API: {reason}")
+            }
+            WgpuSetupError::MissingFeatures { missing } => {
+                write!(
+                    f,
+                    "The adapter doesn't support the required features {missing:?}"
+                )
+            }
+            WgpuSetupError::DeviceCreationFailed { reason } => {
+                write!(f, "Unable to create the device: {reason}")
+            }
+            WgpuSetupError::Timeout(timeout) => write!(
+                f,
+                "Device request timed out after {}s",
+                timeout.as_secs_f64()
+            ),
+        }
+    }
+}
+
+impl Display for WgpuSetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for WgpuSetupError {}
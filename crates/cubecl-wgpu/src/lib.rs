@@ -7,13 +7,16 @@ mod compiler;
 mod compute;
 mod device;
 mod element;
+mod error;
 mod graphics;
+mod probe;
 mod runtime;
 
 pub use compiler::wgsl::WgslCompiler;
 pub use compute::*;
 pub use device::*;
 pub use element::*;
+pub use error::*;
 pub use graphics::*;
 pub use runtime::*;
 
@@ -28,6 +31,7 @@ mod tests {
     cubecl_linalg::testgen_plane_mma!([flex32, f32], f32);
     cubecl_linalg::testgen_tiling2d!([flex32, f32]);
     cubecl_std::testgen_reduce!();
+    cubecl_std::testgen_persistent!();
 }
 
 #[cfg(all(test, feature = "spirv"))]
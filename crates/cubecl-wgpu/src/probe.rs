@@ -0,0 +1,28 @@
+use cubecl_core as cubecl;
+use cubecl_core::prelude::*;
+
+#[cube(launch)]
+fn plane_size_kernel(output: &mut Array<u32>) {
+    if UNIT_POS == 0 {
+        output[0] = PLANE_DIM;
+    }
+}
+
+/// Query the plane size the device actually uses by dispatching a tiny kernel that writes it
+/// back.
+///
+/// The plane size reported by the adapter is a range on some vendors, while kernels can only
+/// know the actual value at runtime.
+pub(crate) fn plane_size<R: Runtime>(client: &ComputeClient<R::Server, R::Channel>) -> u32 {
+    let handle = client.empty(core::mem::size_of::<u32>());
+
+    plane_size_kernel::launch::<R>(
+        client,
+        CubeCount::Static(1, 1, 1),
+        CubeDim::default(),
+        unsafe { ArrayArg::from_raw_parts::<u32>(&handle, 1, 1) },
+    );
+
+    let bytes = client.read(handle.binding());
+    u32::from_bytes(&bytes)[0]
+}
@@ -1,16 +1,18 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, path::PathBuf, sync::OnceLock, time::Duration};
 
 use crate::{
     compiler::{base::WgpuCompiler, wgsl::WgslCompiler},
-    compute::{WgpuServer, WgpuStorage},
-    AutoGraphicsApi, GraphicsApi, WgpuDevice,
+    compute::{pipeline_cache::PipelineCache, PollStrategy, WgpuChannel, WgpuServer, WgpuStorage},
+    probe, AutoGraphicsApi, GraphicsApi, WgpuDevice, WgpuSetupError,
 };
 use alloc::sync::Arc;
 use cubecl_common::future;
 use cubecl_core::{Feature, Runtime};
 pub use cubecl_runtime::memory_management::MemoryConfiguration;
-use cubecl_runtime::{channel::MutexComputeChannel, client::ComputeClient, ComputeRuntime};
-use cubecl_runtime::{memory_management::HardwareProperties, DeviceProperties};
+use cubecl_runtime::{client::ComputeClient, server::Handle, ComputeRuntime};
+use cubecl_runtime::{
+    memory_management::HardwareProperties, AdapterInfo, DeviceProperties, FeatureOverride,
+};
 use cubecl_runtime::{
     memory_management::{MemoryDeviceProperties, MemoryManagement},
     storage::ComputeStorage,
@@ -20,28 +22,36 @@ use wgpu::RequestAdapterOptions;
 /// Runtime that uses the [wgpu] crate with the wgsl compiler. This is used in the Wgpu backend.
 /// For advanced configuration, use [`init_sync`] to pass in runtime options or to select a
 /// specific graphics API.
+///
+/// Clients communicate with their server through a [WgpuChannel]. It locks the server from the
+/// calling thread, or sends every operation to a dedicated thread when
+/// [single_submission_thread](RuntimeOptions::single_submission_thread) is set. Code naming the
+/// [channel](Runtime::Channel) of the runtime, which used to be a
+/// [MutexComputeChannel](cubecl_runtime::channel::MutexComputeChannel), has to use
+/// [WgpuChannel] instead.
 #[derive(Debug)]
 pub struct WgpuRuntime<C: WgpuCompiler = WgslCompiler>(PhantomData<C>);
 
 type Server = WgpuServer<WgslCompiler>;
 
 /// The compute instance is shared across all [wgpu runtimes](WgpuRuntime).
-static RUNTIME: ComputeRuntime<WgpuDevice, Server, MutexComputeChannel<Server>> =
-    ComputeRuntime::new();
+static RUNTIME: ComputeRuntime<WgpuDevice, Server, WgpuChannel<Server>> = ComputeRuntime::new();
 
 impl Runtime for WgpuRuntime<WgslCompiler> {
     type Compiler = WgslCompiler;
     type Server = WgpuServer<WgslCompiler>;
 
-    type Channel = MutexComputeChannel<WgpuServer<WgslCompiler>>;
+    type Channel = WgpuChannel<WgpuServer<WgslCompiler>>;
     type Device = WgpuDevice;
 
     fn client(device: &Self::Device) -> ComputeClient<Self::Server, Self::Channel> {
         RUNTIME.client(device, move || {
+            let options = RuntimeOptions::default();
             let setup = future::block_on(create_setup_for_device::<AutoGraphicsApi, WgslCompiler>(
-                device,
-            ));
-            create_client_on_setup(setup, RuntimeOptions::default())
+                device, &options,
+            ))
+            .unwrap();
+            create_client_on_setup(setup, options)
         })
     }
 
@@ -54,12 +64,165 @@ impl Runtime for WgpuRuntime<WgslCompiler> {
     }
 }
 
+impl<C: WgpuCompiler> WgpuRuntime<C> {
+    /// List every device found with the [automatic graphics API](AutoGraphicsApi), along with
+    /// information identifying its adapter (name, backend, vendor, ...).
+    ///
+    /// The returned devices can be used to create a client on each adapter, for example to spread
+    /// work across multiple GPUs. The adapters are only enumerated once, so repeated calls are
+    /// cheap.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn list_devices() -> Vec<(WgpuDevice, wgpu::AdapterInfo)> {
+        static DEVICES: OnceLock<Vec<(WgpuDevice, wgpu::AdapterInfo)>> = OnceLock::new();
+
+        DEVICES
+            .get_or_init(|| {
+                let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+                    backends: AutoGraphicsApi::backend().into(),
+                    ..Default::default()
+                });
+                let adapters = instance
+                    .enumerate_adapters(AutoGraphicsApi::backend().into())
+                    .into_iter()
+                    .map(|adapter| adapter.get_info());
+
+                enumerate_devices(adapters)
+            })
+            .clone()
+    }
+
+    /// Wrap a buffer created outside of CubeCL in a handle of the `client`, so kernels can use it
+    /// without a copy. See [WgpuServer::register_external_buffer] for the requirements on the
+    /// buffer.
+    ///
+    /// The buffer has to come from the device of the client, for instance by creating the client
+    /// with [init_device] on the setup that created the buffer.
+    pub fn register_external_buffer(
+        client: &ComputeClient<WgpuServer<C>, WgpuChannel<WgpuServer<C>>>,
+        buffer: wgpu::Buffer,
+        size: u64,
+    ) -> Handle {
+        client.with_server(move |server| server.register_external_buffer(buffer, size))
+    }
+}
+
+/// Assign a [device](WgpuDevice) to each adapter, indexing adapters of the same type in the same
+/// order as when a device is selected.
+#[cfg(not(target_family = "wasm"))]
+fn enumerate_devices(
+    adapters: impl Iterator<Item = wgpu::AdapterInfo>,
+) -> Vec<(WgpuDevice, wgpu::AdapterInfo)> {
+    let mut num_discrete = 0;
+    let mut num_integrated = 0;
+    let mut num_virtual = 0;
+    let next = |counter: &mut usize| {
+        *counter += 1;
+        *counter - 1
+    };
+
+    adapters
+        .filter_map(|info| {
+            let device = match info.device_type {
+                wgpu::DeviceType::DiscreteGpu => WgpuDevice::DiscreteGpu(next(&mut num_discrete)),
+                wgpu::DeviceType::IntegratedGpu => {
+                    WgpuDevice::IntegratedGpu(next(&mut num_integrated))
+                }
+                wgpu::DeviceType::VirtualGpu => WgpuDevice::VirtualGpu(next(&mut num_virtual)),
+                wgpu::DeviceType::Cpu => WgpuDevice::Cpu,
+                // Adapters of unknown type can't be selected reliably.
+                wgpu::DeviceType::Other => return None,
+            };
+            Some((device, info))
+        })
+        .collect()
+}
+
 /// The values that control how a WGPU Runtime will perform its calculations.
+#[derive(Clone)]
 pub struct RuntimeOptions {
     /// Control the amount of compute tasks to be aggregated into a single GPU command.
     pub tasks_max: usize,
     /// Configures the memory management.
     pub memory_config: MemoryConfiguration,
+    /// Force the Vulkan queue family used to submit compute work.
+    ///
+    /// Only used by the SPIR-V compiler. When not set, a compute-only queue family is preferred,
+    /// falling back to the first family supporting compute.
+    pub vulkan_queue_family: Option<u32>,
+    /// Directory where compiled pipelines are persisted and reloaded from on startup.
+    ///
+    /// This reduces the warm-up time of applications using many kernels. Disabled when not set.
+    /// Entries are checked on load and recompiled when corrupted or written by another driver.
+    /// Clear the directory with [clear_pipeline_cache](crate::clear_pipeline_cache).
+    pub pipeline_cache_dir: Option<PathBuf>,
+    /// Measure the GPU execution time of every kernel, which can be read with
+    /// [read_timings](ComputeClient::read_timings).
+    ///
+    /// Every kernel runs in its own compute pass, which adds some overhead. Requires the adapter
+    /// to support timestamp queries, otherwise no timings are collected.
+    pub profiling: bool,
+    /// Always compile kernels with the [execution mode](cubecl_core::ExecutionMode) requested at
+    /// launch.
+    ///
+    /// By default, the SPIR-V compiler uses unchecked execution when the device enables
+    /// `robustness2`, since out-of-bounds accesses are already handled by Vulkan. Enabling this
+    /// keeps CubeCL's own bounds checks, which is useful when debugging but adds a check to every
+    /// memory access.
+    pub respect_execution_mode: bool,
+    /// Zero the shared memory of every cube before a kernel starts.
+    ///
+    /// Disabled by default, since it adds work to every launch. Enable it when kernels read
+    /// shared memory they didn't write, to get zeros instead of undefined values.
+    pub zero_initialize_workgroup_memory: bool,
+    /// Hints for the allocator of the device memory blocks backing the buffers.
+    ///
+    /// Defaults to [MemoryUsage](wgpu::MemoryHints::MemoryUsage), since CubeCL already batches
+    /// allocations in its own memory pools. [Performance](wgpu::MemoryHints::Performance)
+    /// allocates bigger blocks, which can help when CubeCL isn't the only memory consumer.
+    pub memory_hints: wgpu::MemoryHints,
+    /// The maximum time to wait for the adapter and device to be created.
+    ///
+    /// When the request takes longer, for example because the driver is in a bad state, device
+    /// setup fails with [WgpuSetupError::Timeout] instead of blocking forever: [try_init_setup]
+    /// returns the error, while [init_setup] panics with it. Waits indefinitely when not set. Not
+    /// supported on wasm, where the timeout is ignored.
+    pub device_request_timeout: Option<Duration>,
+    /// Changes applied to the detected features when the client is created.
+    ///
+    /// Useful to experiment with a feature the adapter under-reports, or to mask one that
+    /// misbehaves. Adding a feature the device doesn't support makes kernels using it fail.
+    pub feature_overrides: Vec<FeatureOverride<Feature>>,
+    /// Submit all the work of the client from a single dedicated thread.
+    ///
+    /// By default, the thread calling the client locks the server and submits to the queue
+    /// itself. When enabled, every operation is sent to a thread owning the server, so work from
+    /// different threads is never interleaved in the queue. This makes timing measurements more
+    /// stable, at the cost of a round trip through a channel. Ignored on wasm.
+    pub single_submission_thread: bool,
+    /// How the server waits on the GPU when reading buffers back or syncing.
+    ///
+    /// Defaults to [Wait](PollStrategy::Wait), which blocks in the driver without keeping a core
+    /// busy. Latency sensitive workloads can use [Spin](PollStrategy::Spin), and background
+    /// workloads [Sleep](PollStrategy::Sleep). Ignored on wasm, where the browser polls the device.
+    pub poll_strategy: PollStrategy,
+    /// The most submissions the GPU may still be working on when new work is submitted.
+    ///
+    /// Once the limit is reached, a submission first waits for the oldest one to complete, so
+    /// producers outpacing the GPU don't pile up command buffers. Unbounded when not set, and a
+    /// limit of 0 behaves like 1. Ignored on wasm, where submissions can't be waited on.
+    pub max_in_flight_submissions: Option<usize>,
+    /// The wgpu backends searched for an adapter, instead of the one of the
+    /// [graphics API](GraphicsApi) of the runtime.
+    ///
+    /// Useful to force a backend on machines supporting several, for example to avoid a driver
+    /// bug. The SPIR-V compiler only works with [Vulkan](wgpu::Backends::VULKAN). Uses the backend
+    /// of the graphics API when not set.
+    pub backends: Option<wgpu::Backends>,
+    /// The wgpu features the adapter must support for the setup to succeed.
+    ///
+    /// Setup fails with [WgpuSetupError::MissingFeatures] when the selected adapter lacks any of
+    /// them, instead of creating a device the application can't use. Empty by default.
+    pub required_features: wgpu::Features,
 }
 
 impl Default for RuntimeOptions {
@@ -79,6 +242,19 @@ impl Default for RuntimeOptions {
         Self {
             tasks_max,
             memory_config: MemoryConfiguration::default(),
+            vulkan_queue_family: None,
+            pipeline_cache_dir: None,
+            profiling: false,
+            respect_execution_mode: false,
+            zero_initialize_workgroup_memory: false,
+            memory_hints: wgpu::MemoryHints::MemoryUsage,
+            device_request_timeout: None,
+            feature_overrides: Vec::new(),
+            single_submission_thread: false,
+            poll_strategy: PollStrategy::default(),
+            max_in_flight_submissions: None,
+            backends: None,
+            required_features: wgpu::Features::empty(),
         }
     }
 }
@@ -109,13 +285,26 @@ pub fn init_device(setup: WgpuSetup, options: RuntimeOptions) -> WgpuDevice {
 
 /// Like [`init_setup_async`], but synchronous.
 /// On wasm, it is necessary to use [`init_setup_async`] instead.
+///
+/// # Panics
+///
+/// If the setup fails, see [`try_init_setup`] to handle the error instead.
 pub fn init_setup<G: GraphicsApi>(device: &WgpuDevice, options: RuntimeOptions) -> WgpuSetup {
+    try_init_setup::<G>(device, options).unwrap()
+}
+
+/// Like [`try_init_setup_async`], but synchronous.
+/// On wasm, it is necessary to use [`try_init_setup_async`] instead.
+pub fn try_init_setup<G: GraphicsApi>(
+    device: &WgpuDevice,
+    options: RuntimeOptions,
+) -> Result<WgpuSetup, WgpuSetupError> {
     cfg_if::cfg_if! {
         if #[cfg(target_family = "wasm")] {
             let _ = (device, options);
             panic!("Creating a wgpu setup synchronously is unsupported on wasm. Use init_async instead");
         } else {
-            future::block_on(init_setup_async::<G>(device, options))
+            future::block_on(try_init_setup_async::<G>(device, options))
         }
     }
 }
@@ -123,31 +312,57 @@ pub fn init_setup<G: GraphicsApi>(device: &WgpuDevice, options: RuntimeOptions)
 /// Initialize a client on the given device with the given options.
 /// This function is useful to configure the runtime options
 /// or to pick a different graphics API.
+///
+/// # Panics
+///
+/// If the setup fails, see [`try_init_setup_async`] to handle the error instead.
 pub async fn init_setup_async<G: GraphicsApi>(
     device: &WgpuDevice,
     options: RuntimeOptions,
 ) -> WgpuSetup {
-    let setup = create_setup_for_device::<G, WgslCompiler>(device).await;
+    try_init_setup_async::<G>(device, options).await.unwrap()
+}
+
+/// Like [`init_setup_async`], but returns an error when no adapter is found or the device can't
+/// be created, instead of panicking.
+pub async fn try_init_setup_async<G: GraphicsApi>(
+    device: &WgpuDevice,
+    options: RuntimeOptions,
+) -> Result<WgpuSetup, WgpuSetupError> {
+    let setup = create_setup_for_device::<G, WgslCompiler>(device, &options).await?;
     let return_setup = setup.clone();
     let client = create_client_on_setup(setup, options);
     RUNTIME.register(device, client);
-    return_setup
+    Ok(return_setup)
 }
 
 pub(crate) fn create_client_on_setup<C: WgpuCompiler>(
     setup: WgpuSetup,
     options: RuntimeOptions,
-) -> ComputeClient<WgpuServer<C>, MutexComputeChannel<WgpuServer<C>>> {
+) -> ComputeClient<WgpuServer<C>, WgpuChannel<WgpuServer<C>>>
+where
+    WgpuRuntime<C>: Runtime<Server = WgpuServer<C>, Channel = WgpuChannel<WgpuServer<C>>>,
+{
     let limits = setup.device.limits();
     let mem_props = MemoryDeviceProperties {
         max_page_size: limits.max_storage_buffer_binding_size as u64,
         alignment: WgpuStorage::ALIGNMENT.max(limits.min_storage_buffer_offset_alignment as u64),
+        // wgpu doesn't report the amount of memory of the adapter.
+        total_memory: None,
     };
-    let hardware_props = HardwareProperties {
+    let mut hardware_props = HardwareProperties {
         plane_size_min: setup.adapter.limits().min_subgroup_size,
         plane_size_max: setup.adapter.limits().max_subgroup_size,
-        max_bindings: limits.max_bind_groups,
+        plane_size_actual: None,
+        max_bindings: limits.max_storage_buffers_per_shader_stage,
+        max_shared_memory_bytes: limits.max_compute_workgroup_storage_size,
+        max_cube_count: (
+            limits.max_compute_workgroups_per_dimension,
+            limits.max_compute_workgroups_per_dimension,
+            limits.max_compute_workgroups_per_dimension,
+        ),
     };
+    let read_only_bindings = !options.memory_config.has_sub_slices();
     let memory_management = {
         let device = setup.device.clone();
         let mem_props = mem_props.clone();
@@ -155,32 +370,116 @@ pub(crate) fn create_client_on_setup<C: WgpuCompiler>(
         let storage = WgpuStorage::new(device.clone());
         MemoryManagement::from_configuration(storage, mem_props, config)
     };
-    let server = WgpuServer::new(
+    let mut server = WgpuServer::new(
         memory_management,
         setup.device.clone(),
         setup.queue,
         options.tasks_max,
+        options.profiling,
+        options.poll_strategy,
+        options.max_in_flight_submissions,
     );
-    let channel = MutexComputeChannel::new(server);
+    server.respect_execution_mode = options.respect_execution_mode;
+    server.zero_initialize_workgroup_memory = options.zero_initialize_workgroup_memory;
+    server.read_only_bindings = read_only_bindings;
+    server.pipeline_cache = options
+        .pipeline_cache_dir
+        .and_then(|dir| PipelineCache::new(&setup.device, &setup.adapter.get_info(), dir));
+    let channel = WgpuChannel::new(server, options.single_submission_thread);
 
     let features = setup.adapter.features();
+    let plane_supported = features.contains(wgpu::Features::SUBGROUP)
+        && setup.adapter.get_info().device_type != wgpu::DeviceType::Cpu;
+
+    if plane_supported {
+        // The actual plane size can only be known by running a kernel, so use a temporary
+        // client sharing the same channel to probe it.
+        let probe_props =
+            DeviceProperties::new(&[Feature::Plane], mem_props.clone(), hardware_props.clone());
+        let probe_client = ComputeClient::new(channel.clone(), probe_props);
+        let plane_size = probe::plane_size::<WgpuRuntime<C>>(&probe_client);
+        log::info!("Measured a plane size of {plane_size}");
+        hardware_props.plane_size_actual = Some(plane_size);
+    }
+
     let mut device_props = DeviceProperties::new(&[], mem_props, hardware_props);
 
-    if features.contains(wgpu::Features::SUBGROUP)
-        && setup.adapter.get_info().device_type != wgpu::DeviceType::Cpu
-    {
+    if plane_supported {
         device_props.register_feature(Feature::Plane);
     }
+    let info = setup.adapter.get_info();
+    device_props.set_adapter_info(AdapterInfo {
+        name: info.name,
+        vendor: info.vendor,
+        device: info.device,
+        driver: format!("{} {}", info.driver, info.driver_info)
+            .trim()
+            .to_string(),
+        backend: info.backend.to_str().to_string(),
+    });
     C::register_features(&setup.adapter, &setup.device, &mut device_props);
+    device_props.apply_overrides(&options.feature_overrides);
     ComputeClient::new(channel, device_props)
 }
 
 /// Select the wgpu device and queue based on the provided [device](WgpuDevice).
 pub(crate) async fn create_setup_for_device<G: GraphicsApi, C: WgpuCompiler>(
     device: &WgpuDevice,
-) -> WgpuSetup {
-    let (instance, adapter) = request_adapter::<G>(device).await;
-    let (device, queue) = C::request_device(&adapter).await;
+    options: &RuntimeOptions,
+) -> Result<WgpuSetup, WgpuSetupError> {
+    #[cfg(not(target_family = "wasm"))]
+    if let Some(timeout) = options.device_request_timeout {
+        return create_setup_with_timeout::<G, C>(device, options, timeout).await;
+    }
+
+    request_setup::<G, C>(device, options).await
+}
+
+/// Create the setup on a separate thread, so that a request that never completes only blocks that
+/// thread, and give up after the timeout.
+#[cfg(not(target_family = "wasm"))]
+async fn create_setup_with_timeout<G: GraphicsApi, C: WgpuCompiler>(
+    device: &WgpuDevice,
+    options: &RuntimeOptions,
+    timeout: Duration,
+) -> Result<WgpuSetup, WgpuSetupError> {
+    let (setup_sender, setup_receiver) = std::sync::mpsc::channel();
+    let (sender, receiver) = async_channel::bounded(1);
+    let device = device.clone();
+    let options = options.clone();
+
+    std::thread::spawn(move || {
+        // Forward panics to the caller.
+        let setup = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            future::block_on(request_setup::<G, C>(&device, &options))
+        }));
+        let _ = setup_sender.send(setup);
+    });
+    std::thread::spawn(move || {
+        let setup = setup_receiver.recv_timeout(timeout).ok();
+        let _ = sender.send_blocking(setup);
+    });
+
+    match receiver.recv().await.ok().flatten() {
+        Some(Ok(setup)) => setup,
+        Some(Err(payload)) => std::panic::resume_unwind(payload),
+        None => Err(WgpuSetupError::Timeout(timeout)),
+    }
+}
+
+async fn request_setup<G: GraphicsApi, C: WgpuCompiler>(
+    device: &WgpuDevice,
+    options: &RuntimeOptions,
+) -> Result<WgpuSetup, WgpuSetupError> {
+    let backends = options.backends.unwrap_or_else(|| G::backend().into());
+    let (instance, adapter) = request_adapter(device, backends).await?;
+
+    let missing = options.required_features.difference(adapter.features());
+    if !missing.is_empty() {
+        return Err(WgpuSetupError::MissingFeatures { missing });
+    }
+
+    let (device, queue) = C::request_device(&adapter, options).await?;
 
     log::info!(
         "Created wgpu compute server on device {:?} => {:?}",
@@ -188,17 +487,20 @@ pub(crate) async fn create_setup_for_device<G: GraphicsApi, C: WgpuCompiler>(
         adapter.get_info()
     );
 
-    WgpuSetup {
+    Ok(WgpuSetup {
         instance: Arc::new(instance),
         adapter: Arc::new(adapter),
         device: Arc::new(device),
         queue: Arc::new(queue),
-    }
+    })
 }
 
-async fn request_adapter<G: GraphicsApi>(device: &WgpuDevice) -> (wgpu::Instance, wgpu::Adapter) {
+async fn request_adapter(
+    device: &WgpuDevice,
+    backends: wgpu::Backends,
+) -> Result<(wgpu::Instance, wgpu::Adapter), WgpuSetupError> {
     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-        backends: G::backend().into(),
+        backends,
         ..Default::default()
     });
 
@@ -216,20 +518,32 @@ async fn request_adapter<G: GraphicsApi>(device: &WgpuDevice) -> (wgpu::Instance
 
     let adapter = match device {
         #[cfg(not(target_family = "wasm"))]
-        WgpuDevice::DiscreteGpu(num) => {
-            select_from_adapter_list::<G>(num, "No Discrete GPU device found", &instance, &device)
-        }
+        WgpuDevice::DiscreteGpu(num) => select_from_adapter_list(
+            num,
+            "No Discrete GPU device found",
+            &instance,
+            &device,
+            backends,
+        )?,
         #[cfg(not(target_family = "wasm"))]
-        WgpuDevice::IntegratedGpu(num) => {
-            select_from_adapter_list::<G>(num, "No Integrated GPU device found", &instance, &device)
-        }
+        WgpuDevice::IntegratedGpu(num) => select_from_adapter_list(
+            num,
+            "No Integrated GPU device found",
+            &instance,
+            &device,
+            backends,
+        )?,
         #[cfg(not(target_family = "wasm"))]
-        WgpuDevice::VirtualGpu(num) => {
-            select_from_adapter_list::<G>(num, "No Virtual GPU device found", &instance, &device)
-        }
+        WgpuDevice::VirtualGpu(num) => select_from_adapter_list(
+            num,
+            "No Virtual GPU device found",
+            &instance,
+            &device,
+            backends,
+        )?,
         #[cfg(not(target_family = "wasm"))]
         WgpuDevice::Cpu => {
-            select_from_adapter_list::<G>(0, "No CPU device found", &instance, &device)
+            select_from_adapter_list(0, "No CPU device found", &instance, &device, backends)?
         }
         WgpuDevice::Existing(_) => {
             unreachable!("Cannot select an adapter for an existing device.")
@@ -241,26 +555,29 @@ async fn request_adapter<G: GraphicsApi>(device: &WgpuDevice) -> (wgpu::Instance
                 compatible_surface: None,
             })
             .await
-            .expect("No possible adapter available for backend. Falling back to first available."),
+            .ok_or_else(|| WgpuSetupError::AdapterNotFound {
+                reason: format!("No adapter available for the backends {backends:?}"),
+            })?,
     };
 
     log::info!("Using adapter {:?}", adapter.get_info());
 
-    (instance, adapter)
+    Ok((instance, adapter))
 }
 
 #[cfg(not(target_family = "wasm"))]
-fn select_from_adapter_list<G: GraphicsApi>(
+fn select_from_adapter_list(
     num: usize,
     error: &str,
     instance: &wgpu::Instance,
     device: &WgpuDevice,
-) -> wgpu::Adapter {
+    backends: wgpu::Backends,
+) -> Result<wgpu::Adapter, WgpuSetupError> {
     let mut adapters_other = Vec::new();
     let mut adapters = Vec::new();
 
     instance
-        .enumerate_adapters(G::backend().into())
+        .enumerate_adapters(backends)
         .into_iter()
         .for_each(|adapter| {
             let device_type = adapter.get_info().device_type;
@@ -289,24 +606,26 @@ fn select_from_adapter_list<G: GraphicsApi>(
 
     if adapters.len() <= num {
         if adapters_other.len() <= num {
-            panic!(
-                "{}, adapters {:?}, other adapters {:?}",
-                error,
-                adapters
-                    .into_iter()
-                    .map(|adapter| adapter.get_info())
-                    .collect::<Vec<_>>(),
-                adapters_other
-                    .into_iter()
-                    .map(|adapter| adapter.get_info())
-                    .collect::<Vec<_>>(),
-            );
+            return Err(WgpuSetupError::AdapterNotFound {
+                reason: format!(
+                    "{}, adapters {:?}, other adapters {:?}",
+                    error,
+                    adapters
+                        .into_iter()
+                        .map(|adapter| adapter.get_info())
+                        .collect::<Vec<_>>(),
+                    adapters_other
+                        .into_iter()
+                        .map(|adapter| adapter.get_info())
+                        .collect::<Vec<_>>(),
+                ),
+            });
         }
 
-        return adapters_other.remove(num);
+        return Ok(adapters_other.remove(num));
     }
 
-    adapters.remove(num)
+    Ok(adapters.remove(num))
 }
 
 fn get_device_override() -> Option<WgpuDevice> {
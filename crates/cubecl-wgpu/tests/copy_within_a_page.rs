@@ -0,0 +1,16 @@
+use cubecl_core::prelude::*;
+use cubecl_wgpu::{WgpuDevice, WgpuRuntime};
+
+#[test]
+fn copy_between_handles_of_the_same_page() {
+    let client = WgpuRuntime::client(&WgpuDevice::DefaultDevice);
+
+    // Small handles are slices of the same page, so they share a buffer.
+    let src = client.create(f32::as_bytes(&[1.0, 2.0, 3.0, 4.0]));
+    let dst = client.create(f32::as_bytes(&[0.0; 4]));
+
+    client.copy(&src, &dst, 4 * core::mem::size_of::<f32>() as u64);
+
+    let actual = client.read(dst.binding());
+    assert_eq!(f32::from_bytes(&actual), &[1.0, 2.0, 3.0, 4.0]);
+}
@@ -0,0 +1,27 @@
+use cubecl_core::prelude::*;
+use cubecl_wgpu::{init_device, init_setup, AutoGraphicsApi, WgpuDevice, WgpuRuntime};
+
+#[test]
+fn external_buffer_is_read_without_a_copy() {
+    let setup = init_setup::<AutoGraphicsApi>(&WgpuDevice::DefaultDevice, Default::default());
+    let device = setup.device.clone();
+    let queue = setup.queue.clone();
+    let client = WgpuRuntime::client(&init_device(setup, Default::default()));
+
+    let data = [1.0f32, 2.0, 3.0, 4.0];
+    let size = core::mem::size_of_val(&data) as u64;
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("external"),
+        size,
+        usage: wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::COPY_SRC
+            | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    queue.write_buffer(&buffer, 0, f32::as_bytes(&data));
+
+    let handle = WgpuRuntime::register_external_buffer(&client, buffer, size);
+
+    let actual = client.read(handle.binding());
+    assert_eq!(f32::from_bytes(&actual), &data);
+}
@@ -2,6 +2,7 @@ use common::*;
 use constant_array_kernel::ConstantArrayKernel;
 use cubecl_core as cubecl;
 use cubecl_core::prelude::*;
+use cubecl_core::{Compiler, ExecutionMode};
 use cubecl_wgpu::WgpuRuntime;
 use execute_unary_kernel::ExecuteUnaryKernel;
 use kernel_elect::KernelElect;
@@ -27,6 +28,23 @@ pub fn slice_assign() {
     assert_eq!(compile(kernel), expected);
 }
 
+#[test]
+pub fn slice_assign_read_only_bindings() {
+    let kernel = SliceAssignKernel::<WgpuRuntime>::new(settings(1, 1), tensor(), tensor());
+    let mut shader = <<TestRuntime as Runtime>::Compiler as Compiler>::compile(
+        kernel.define(),
+        ExecutionMode::Checked,
+    );
+    shader.read_only_bindings = true;
+
+    // The layout entries are created from the same check.
+    assert!(shader.is_read_only(&shader.inputs[0]));
+    assert!(!shader.is_read_only(&shader.outputs[0]));
+    assert!(shader
+        .to_string()
+        .contains("var<storage, read> input_0_global"));
+}
+
 #[cube(launch, create_dummy_kernel)]
 pub fn kernel_sum(output: &mut Tensor<f32>) {
     let val = output[UNIT_POS];
@@ -0,0 +1,18 @@
+use cubecl_wgpu::{try_init_setup, AutoGraphicsApi, RuntimeOptions, WgpuDevice, WgpuSetupError};
+
+#[test]
+fn setup_reports_missing_required_features() {
+    // No adapter supports every wgpu feature, since some are specific to a backend.
+    let result = try_init_setup::<AutoGraphicsApi>(
+        &WgpuDevice::DefaultDevice,
+        RuntimeOptions {
+            required_features: wgpu::Features::all(),
+            ..Default::default()
+        },
+    );
+
+    match result {
+        Err(WgpuSetupError::MissingFeatures { missing }) => assert!(!missing.is_empty()),
+        other => panic!("Expected missing features, got {:?}", other.err()),
+    }
+}
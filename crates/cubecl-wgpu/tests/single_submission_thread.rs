@@ -0,0 +1,36 @@
+use cubecl_core as cubecl;
+use cubecl_core::prelude::*;
+use cubecl_wgpu::{init_setup, AutoGraphicsApi, RuntimeOptions, WgpuDevice, WgpuRuntime};
+
+#[cube(launch)]
+fn double_kernel(input: &Array<f32>, output: &mut Array<f32>) {
+    output[UNIT_POS] = input[UNIT_POS] * 2.0;
+}
+
+#[test]
+fn kernel_runs_on_a_single_submission_thread() {
+    let device = WgpuDevice::DefaultDevice;
+    // Runs in its own test binary, so no client was created for the device with other options.
+    init_setup::<AutoGraphicsApi>(
+        &device,
+        RuntimeOptions {
+            single_submission_thread: true,
+            ..Default::default()
+        },
+    );
+    let client = WgpuRuntime::client(&device);
+
+    let input = client.create(f32::as_bytes(&[1.0, 2.0, 3.0, 4.0]));
+    let output = client.empty(4 * core::mem::size_of::<f32>());
+
+    double_kernel::launch::<WgpuRuntime>(
+        &client,
+        CubeCount::Static(1, 1, 1),
+        CubeDim::new(4, 1, 1),
+        unsafe { ArrayArg::from_raw_parts::<f32>(&input, 4, 1) },
+        unsafe { ArrayArg::from_raw_parts::<f32>(&output, 4, 1) },
+    );
+
+    let actual = client.read(output.binding());
+    assert_eq!(f32::from_bytes(&actual), &[2.0, 4.0, 6.0, 8.0]);
+}